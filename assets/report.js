@@ -0,0 +1,81 @@
+(function () {
+  "use strict";
+
+  // 點擊表頭排序；數字欄位以數值比較，第一次點擊由大到小
+  function cellValue(row, index) {
+    var text = row.cells[index] ? row.cells[index].textContent.trim() : "";
+    var number = parseFloat(text);
+    return isNaN(number) || !/^-?\d/.test(text) ? text.toLowerCase() : number;
+  }
+
+  document.querySelectorAll("table.sortable").forEach(function (table) {
+    var headers = table.querySelectorAll("th");
+    headers.forEach(function (th) {
+      th.addEventListener("click", function () {
+        var index = th.cellIndex;
+        var numeric = th.classList.contains("num");
+        var order = th.dataset.order ? (th.dataset.order === "asc" ? "desc" : "asc") : (numeric ? "desc" : "asc");
+        headers.forEach(function (other) {
+          delete other.dataset.order;
+          other.removeAttribute("aria-sort");
+        });
+        th.dataset.order = order;
+        th.setAttribute("aria-sort", order === "asc" ? "ascending" : "descending");
+        var body = table.tBodies[0];
+        var rows = Array.prototype.slice.call(body.rows);
+        rows.sort(function (a, b) {
+          var x = cellValue(a, index);
+          var y = cellValue(b, index);
+          var result = x < y ? -1 : x > y ? 1 : 0;
+          return order === "asc" ? result : -result;
+        });
+        rows.forEach(function (row) { body.appendChild(row); });
+      });
+    });
+  });
+
+  // 函數與型別的全文搜尋
+  var index = JSON.parse(document.getElementById("search-index").textContent);
+  var input = document.getElementById("search");
+  var results = document.getElementById("search-results");
+  var limit = 50;
+
+  function render(query) {
+    results.innerHTML = "";
+    if (!query) {
+      return;
+    }
+    var terms = query.toLowerCase().split(/\s+/).filter(Boolean);
+    var matches = index.filter(function (entry) {
+      var text = (entry.name + " " + entry.kind + " " + entry.file + " " + entry.text).toLowerCase();
+      return terms.every(function (term) { return text.indexOf(term) !== -1; });
+    });
+    matches.slice(0, limit).forEach(function (entry) {
+      var li = document.createElement("li");
+      var link = document.createElement("a");
+      link.href = "#" + entry.anchor;
+      var kind = document.createElement("span");
+      kind.className = "kind";
+      kind.textContent = entry.kind;
+      link.appendChild(kind);
+      link.appendChild(document.createTextNode(entry.name));
+      var file = document.createElement("span");
+      file.className = "file";
+      file.textContent = entry.file + ":" + entry.line;
+      li.appendChild(link);
+      li.appendChild(file);
+      results.appendChild(li);
+    });
+    if (matches.length > limit) {
+      var more = document.createElement("li");
+      more.textContent = input.dataset.more.replace("{count}", matches.length - limit);
+      results.appendChild(more);
+    } else if (matches.length === 0) {
+      var none = document.createElement("li");
+      none.textContent = input.dataset.none;
+      results.appendChild(none);
+    }
+  }
+
+  input.addEventListener("input", function () { render(input.value.trim()); });
+})();
@@ -0,0 +1,339 @@
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use std::future::Future;
+use std::time::Duration;
+use tracing::{info, warn, error, debug};
+use crate::cache::AnalysisCache;
+use crate::chunking::chunk_source;
+use crate::config::Config;
+use crate::locale::Catalog;
+use crate::models::{AIAnalysis, CrateOverview, ItemInfo, ModuleOverview, ProjectSummary};
+use crate::provider::{ChatRequest, LlmProvider};
+use crate::structured::{parse_response, ResponseSchema, ValidationError};
+
+const MAX_RETRIES: u32 = 5;
+const RETRY_DELAY_MS: u64 = 1000;
+/// 檔案分析提示詞的版本，修改提示詞或 `AIAnalysis` 結構時需要遞增，讓舊的快取失效
+pub const PROMPT_VERSION: u32 = 1;
+/// 未設定 `temperature` 時檔案分析使用的溫度；總結則沿用服務提供者的預設值
+const ANALYSIS_TEMPERATURE: f32 = 0.2;
+
+/// AI 分析所需的服務提供者與設定
+pub struct Analyzer {
+    provider: Box<dyn LlmProvider>,
+    catalog: Catalog,
+    /// 提示詞範本的雜湊值，語言或提示詞不同的快取不會互相沿用
+    prompt_hash: String,
+    max_input_tokens: usize,
+    /// 設定檔或 profile 指定的溫度
+    temperature: Option<f32>,
+    cache: Option<AnalysisCache>,
+}
+
+impl Analyzer {
+    pub fn new(config: &Config, catalog: Catalog) -> Self {
+        Self {
+            provider: crate::provider::from_config(config),
+            prompt_hash: catalog.prompt_hash(),
+            catalog,
+            max_input_tokens: config.max_input_tokens,
+            temperature: config.temperature,
+            cache: if config.cache { AnalysisCache::open(PROMPT_VERSION) } else { None },
+        }
+    }
+
+    /// 測試用：使用指定的服務提供者，不讀寫快取
+    #[cfg(test)]
+    pub fn with_provider(provider: Box<dyn LlmProvider>, catalog: Catalog) -> Self {
+        Self {
+            provider,
+            prompt_hash: catalog.prompt_hash(),
+            catalog,
+            max_input_tokens: crate::config::Config::default().max_input_tokens,
+            temperature: None,
+            cache: None,
+        }
+    }
+
+    pub fn provider(&self) -> &dyn LlmProvider {
+        self.provider.as_ref()
+    }
+
+    /// 提示詞與回答語言所使用的語言目錄
+    pub fn catalog(&self) -> &Catalog {
+        &self.catalog
+    }
+
+    pub fn prompt_hash(&self) -> &str {
+        &self.prompt_hash
+    }
+
+    /// 單次請求中內容的估計 token 上限
+    pub fn max_input_tokens(&self) -> usize {
+        self.max_input_tokens
+    }
+
+    /// 記錄本次執行的快取命中次數
+    pub fn log_cache_stats(&self) {
+        if let Some(cache) = &self.cache {
+            cache.log_stats();
+        }
+    }
+
+    /// 分析單一檔案；內容、模型與提示詞版本都相同時直接使用快取的結果
+    pub async fn analyze_file(
+        &self,
+        code: &str,
+        items: &[ItemInfo],
+        file_path: &str,
+    ) -> Result<Option<AIAnalysis>> {
+        let Some(cache) = &self.cache else {
+            return self.analyze_uncached(code, items, file_path).await;
+        };
+        let key = cache.key(
+            self.provider.kind().as_str(),
+            self.provider.model(),
+            &self.prompt_hash,
+            self.analysis_temperature(),
+            self.max_input_tokens,
+            code,
+        );
+        if let Some(analysis) = cache.get(&key) {
+            info!("使用快取的 AI 分析：{}", file_path);
+            return Ok(Some(analysis));
+        }
+        let analysis = self.analyze_uncached(code, items, file_path).await?;
+        if let Some(analysis) = &analysis {
+            cache.put(&key, self.provider.model(), analysis);
+        }
+        Ok(analysis)
+    }
+
+    /// 超過輸入上限時依項目邊界切塊分析，再合併各塊結果
+    async fn analyze_uncached(
+        &self,
+        code: &str,
+        items: &[ItemInfo],
+        file_path: &str,
+    ) -> Result<Option<AIAnalysis>> {
+        let chunks = chunk_source(code, items, self.max_input_tokens);
+        if chunks.len() == 1 {
+            return do_ai_analysis_with_retry(self.provider(), &self.catalog, self.analysis_temperature(), code, file_path).await;
+        }
+
+        info!("檔案超過輸入上限，分為 {} 個區塊分析：{}", chunks.len(), file_path);
+        let mut partials = Vec::new();
+        for (index, chunk) in chunks.iter().enumerate() {
+            debug!("區塊 {}/{}：第 {}-{} 行", index + 1, chunks.len(), chunk.start_line, chunk.end_line);
+            let context = self.catalog.prompt(&self.catalog.prompts.chunk_context, &[
+                ("file", file_path),
+                ("index", &(index + 1).to_string()),
+                ("count", &chunks.len().to_string()),
+                ("start", &chunk.start_line.to_string()),
+                ("end", &chunk.end_line.to_string()),
+            ]);
+            let label = format!("AI 分析（{} 第 {}/{} 部分）", file_path, index + 1, chunks.len());
+            let result = with_retry(&label, |feedback| {
+                do_ai_analysis(self.provider(), &self.catalog, self.analysis_temperature(), &chunk.text, Some(&context), feedback)
+            }).await;
+            match result {
+                Some(partial) => partials.push(partial),
+                None => warn!("略過分析失敗的區塊：{} 第 {}-{} 行", file_path, chunk.start_line, chunk.end_line),
+            }
+        }
+        Ok(merge_analyses(partials))
+    }
+
+    /// 由目錄內各檔案的摘要總結一個模組
+    pub async fn summarize_module(
+        &self,
+        path: &str,
+        crate_name: &str,
+        digest: &str,
+        part: Option<(usize, usize)>,
+    ) -> Option<ModuleOverview> {
+        let part = self.part_note(part);
+        let prompt = self.catalog.prompt(&self.catalog.prompts.module_summary, &[
+            ("crate", crate_name),
+            ("path", path),
+            ("part", &part),
+            ("digest", digest),
+        ]);
+        let label = format!("模組總結（{}）", path);
+        self.summarize(&label, "module_overview", prompt).await
+    }
+
+    /// 由各模組的總結總結一個 crate
+    pub async fn summarize_crate(
+        &self,
+        name: &str,
+        digest: &str,
+        part: Option<(usize, usize)>,
+    ) -> Option<CrateOverview> {
+        let part = self.part_note(part);
+        let prompt = self.catalog.prompt(&self.catalog.prompts.crate_summary, &[
+            ("crate", name),
+            ("part", &part),
+            ("digest", digest),
+        ]);
+        let label = format!("crate 總結（{}）", name);
+        self.summarize(&label, "crate_overview", prompt).await
+    }
+
+    /// 由各 crate 的總結生成整個專案的總結
+    pub async fn summarize_workspace(
+        &self,
+        digest: &str,
+        part: Option<(usize, usize)>,
+    ) -> Option<ProjectSummary> {
+        info!("開始生成專案總結");
+        let part = self.part_note(part);
+        let prompt = self.catalog.prompt(&self.catalog.prompts.workspace_summary, &[("part", &part), ("digest", digest)]);
+        self.summarize("專案總結生成", "project_summary", prompt).await
+    }
+
+    async fn summarize<T: DeserializeOwned + JsonSchema>(
+        &self,
+        label: &str,
+        schema_name: &'static str,
+        prompt: String,
+    ) -> Option<T> {
+        with_retry(label, |feedback| {
+            let request = ChatRequest {
+                system: self.catalog.prompt(&self.catalog.prompts.summary_system, &[]),
+                prompt: with_feedback(&self.catalog, prompt.clone(), feedback),
+                temperature: self.temperature,
+                timeout: None,
+                response_schema: Some(ResponseSchema::of::<T>(schema_name)),
+            };
+            async move { request_structured(self.provider(), &request).await }
+        }).await
+    }
+
+    fn analysis_temperature(&self) -> f32 {
+        self.temperature.unwrap_or(ANALYSIS_TEMPERATURE)
+    }
+
+    /// 內容分批總結時，說明目前是第幾部分
+    fn part_note(&self, part: Option<(usize, usize)>) -> String {
+        match part {
+            Some((index, count)) => self.catalog.prompt(&self.catalog.prompts.part_note, &[
+                ("index", &index.to_string()),
+                ("count", &count.to_string()),
+            ]),
+            None => String::new(),
+        }
+    }
+}
+
+pub async fn do_ai_analysis_with_retry(
+    provider: &dyn LlmProvider,
+    catalog: &Catalog,
+    temperature: f32,
+    code: &str,
+    file_path: &str,
+) -> Result<Option<AIAnalysis>> {
+    let label = format!("AI 分析（{}）", file_path);
+    Ok(with_retry(&label, |feedback| do_ai_analysis(provider, catalog, temperature, code, None, feedback)).await)
+}
+
+/// 合併同一檔案各區塊的分析結果，依名稱去除重複項目
+fn merge_analyses(partials: Vec<AIAnalysis>) -> Option<AIAnalysis> {
+    let mut partials = partials.into_iter();
+    let mut merged = partials.next()?;
+    for partial in partials {
+        for name in partial.main_functions {
+            if !merged.main_functions.contains(&name) {
+                merged.main_functions.push(name);
+            }
+        }
+        for core_struct in partial.core_structs {
+            if !merged.core_structs.iter().any(|s| s.name == core_struct.name) {
+                merged.core_structs.push(core_struct);
+            }
+        }
+        for error_type in partial.error_types {
+            if !merged.error_types.contains(&error_type) {
+                merged.error_types.push(error_type);
+            }
+        }
+        for detail in partial.functions_details {
+            if !merged.functions_details.iter().any(|d| d.name == detail.name) {
+                merged.functions_details.push(detail);
+            }
+        }
+        if !partial.code_complexity.is_empty() && !merged.code_complexity.contains(&partial.code_complexity) {
+            if !merged.code_complexity.is_empty() {
+                merged.code_complexity.push('\n');
+            }
+            merged.code_complexity.push_str(&partial.code_complexity);
+        }
+    }
+    Some(merged)
+}
+
+/// 重試失敗的請求。若失敗原因是回應欄位不符合格式，下一次請求會附上錯誤說明讓模型修正。
+async fn with_retry<T, F, Fut>(label: &str, mut attempt: F) -> Option<T>
+where
+    F: FnMut(Option<String>) -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut feedback = None;
+    for retries in 0..MAX_RETRIES {
+        match attempt(feedback.take()).await {
+            Ok(value) => return Some(value),
+            Err(e) => {
+                if let Some(invalid) = e.downcast_ref::<ValidationError>() {
+                    feedback = Some(invalid.to_string());
+                }
+                if retries < MAX_RETRIES - 1 {
+                    let delay = RETRY_DELAY_MS * (retries as u64 + 1);
+                    warn!("{}失敗 (重試 {}/{}): {}", label, retries + 1, MAX_RETRIES, e);
+                    tokio::time::sleep(Duration::from_millis(delay)).await;
+                } else {
+                    error!("{}在重試{}次後仍然失敗：{}", label, MAX_RETRIES, e);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// 將上一次的驗證錯誤附加到提示詞
+fn with_feedback(catalog: &Catalog, prompt: String, feedback: Option<String>) -> String {
+    match feedback {
+        Some(feedback) => catalog.prompt(&catalog.prompts.feedback, &[("prompt", &prompt), ("feedback", &feedback)]),
+        None => prompt,
+    }
+}
+
+/// 送出要求結構化輸出的請求，並將回應解析為 `T`
+async fn request_structured<T: DeserializeOwned>(
+    provider: &dyn LlmProvider,
+    request: &ChatRequest,
+) -> Result<T> {
+    let content = provider.complete(request).await?;
+    debug!("準備解析的回應：{}", content);
+    parse_response(&content)
+}
+
+async fn do_ai_analysis(
+    provider: &dyn LlmProvider,
+    catalog: &Catalog,
+    temperature: f32,
+    code: &str,
+    context: Option<&str>,
+    feedback: Option<String>,
+) -> Result<AIAnalysis> {
+    let prompt = catalog.prompt(&catalog.prompts.analysis, &[("context", context.unwrap_or_default()), ("code", code)]);
+
+    let request = ChatRequest {
+        system: catalog.prompt(&catalog.prompts.analysis_system, &[]),
+        prompt: with_feedback(catalog, prompt, feedback),
+        temperature: Some(temperature),
+        timeout: Some(Duration::from_secs(30)),
+        response_schema: Some(ResponseSchema::of::<AIAnalysis>("ai_analysis")),
+    };
+    request_structured(provider, &request).await
+}
@@ -1,9 +1,16 @@
-use std::path::Path;
-use anyhow::Result;
-use crate::models::{CodeStats, ProjectAnalysis};
-use git2::Repository;
+use std::path::{Path, PathBuf};
+use anyhow::{Result, anyhow};
+use futures::stream::{self, StreamExt};
+use crate::lexer::{classify_lines, LineKind};
+use crate::items::Inventory;
+use crate::models::{CodeStats, FileAnalysis, ProjectAnalysis, ReportMetadata};
+use crate::ai::Analyzer;
+use crate::locale::Catalog;
+use chrono::{DateTime, Utc};
+use ignore::WalkBuilder;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
 use std::collections::HashSet;
-use tracing::info;
+use tracing::{error, info};
 
 pub fn analyze_code(content: &str) -> CodeStats {
     let mut stats = CodeStats {
@@ -11,138 +18,704 @@ pub fn analyze_code(content: &str) -> CodeStats {
         blank_lines: 0,
         comment_lines: 0,
         code_lines: 0,
+        doc_comment_lines: 0,
+        inner_doc_lines: 0,
+        attribute_lines: 0,
     };
     
-    let mut in_block_comment = false;
-    
-    for line in content.lines() {
-        let trimmed = line.trim();
+    for kind in classify_lines(content) {
         stats.loc += 1;
-        
-        if trimmed.is_empty() {
-            stats.blank_lines += 1;
-            continue;
-        }
-        
-        if in_block_comment {
-            stats.comment_lines += 1;
-            if trimmed.contains("*/") {
-                in_block_comment = false;
+        match kind {
+            LineKind::Blank => stats.blank_lines += 1,
+            LineKind::Code => stats.code_lines += 1,
+            LineKind::Attribute => {
+                stats.code_lines += 1;
+                stats.attribute_lines += 1;
+            }
+            LineKind::Comment => stats.comment_lines += 1,
+            LineKind::OuterDoc => {
+                stats.comment_lines += 1;
+                stats.doc_comment_lines += 1;
+            }
+            LineKind::InnerDoc => {
+                stats.comment_lines += 1;
+                stats.inner_doc_lines += 1;
             }
-            continue;
         }
-        
-        if trimmed.starts_with("/*") {
-            stats.comment_lines += 1;
-            in_block_comment = true;
-            if !trimmed.contains("*/") {
+    }
+    
+    stats
+}
+
+/// 檔案探索的 include／exclude 條件，樣式與 `.gitignore` 相同，路徑相對於專案根目錄
+pub struct FileFilter {
+    include: Option<Gitignore>,
+    exclude: Gitignore,
+}
+
+impl FileFilter {
+    pub fn new(project_path: &Path, include: &[String], exclude: &[String]) -> Result<Self> {
+        let include = if include.is_empty() {
+            None
+        } else {
+            Some(build_matcher(project_path, include)?)
+        };
+        Ok(Self { include, exclude: build_matcher(project_path, exclude)? })
+    }
+
+    fn excluded(&self, relative: &Path) -> bool {
+        self.exclude.matched_path_or_any_parents(relative, false).is_ignore()
+    }
+
+    fn included(&self, relative: &Path) -> bool {
+        self.include
+            .as_ref()
+            .is_none_or(|include| include.matched_path_or_any_parents(relative, false).is_ignore())
+    }
+}
+
+/// 檢查並編譯 `.gitignore` 樣式的規則
+pub fn build_matcher(project_path: &Path, patterns: &[String]) -> Result<Gitignore> {
+    let mut builder = GitignoreBuilder::new(project_path);
+    for pattern in patterns {
+        builder
+            .add_line(None, pattern)
+            .map_err(|e| anyhow!("無效的檔案樣式：{}（{}）", pattern, e))?;
+    }
+    Ok(builder.build()?)
+}
+
+/// 遞迴尋找專案中的 Rust 檔案，結果依路徑排序。
+///
+/// 遵循 `.gitignore`、`.ignore` 與 `.git/info/exclude`（不在 Git 倉庫中也適用），略過隱藏目錄與
+/// `target` 目錄，再套用 `filter` 的 exclude 與 include 條件。
+pub fn discover_rust_files(project_path: &Path, filter: &FileFilter) -> Vec<PathBuf> {
+    walk(project_path, filter, |path| path.extension().is_some_and(|ext| ext == "rs"))
+        .into_iter()
+        .filter(|path| {
+            let included = filter.included(path.strip_prefix(project_path).unwrap_or(path));
+            if !included {
+                tracing::debug!("排除檔案：{}", path.display());
+            }
+            included
+        })
+        .collect()
+}
+
+/// 尋找專案中的 `Cargo.toml`，結果依路徑排序。與 [`discover_rust_files`] 略過相同的目錄與檔案，
+/// 但不套用 include 條件，因此只分析部分檔案時仍能找到這些檔案所屬的 crate。
+pub fn discover_manifests(project_path: &Path, filter: &FileFilter) -> Vec<PathBuf> {
+    walk(project_path, filter, |path| path.file_name().is_some_and(|name| name == "Cargo.toml"))
+}
+
+/// 走訪專案中未被忽略或排除、且符合 `wanted` 的檔案
+fn walk(project_path: &Path, filter: &FileFilter, wanted: impl Fn(&Path) -> bool) -> Vec<PathBuf> {
+    let root = project_path.to_path_buf();
+    let exclude = filter.exclude.clone();
+    let walker = WalkBuilder::new(project_path)
+        .require_git(false)
+        .filter_entry(move |entry| {
+            if entry.depth() == 0 || !entry.file_type().is_some_and(|t| t.is_dir()) {
+                return true;
+            }
+            let relative = entry.path().strip_prefix(&root).unwrap_or(entry.path());
+            let skip = entry.file_name() == "target"
+                || exclude.matched_path_or_any_parents(relative, true).is_ignore();
+            if skip {
+                tracing::debug!("跳過目錄：{}", entry.path().display());
+            }
+            !skip
+        })
+        .build();
+
+    let mut files = Vec::new();
+    for entry in walker {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                tracing::warn!("無法讀取：{}", e);
                 continue;
             }
-            in_block_comment = false;
-        } else if trimmed.starts_with("//") {
-            stats.comment_lines += 1;
-        } else {
-            stats.code_lines += 1;
+        };
+        let path = entry.path();
+        if !entry.file_type().is_some_and(|t| t.is_file()) || !wanted(path) {
+            continue;
+        }
+        let relative = path.strip_prefix(project_path).unwrap_or(path);
+        if filter.excluded(relative) {
+            tracing::debug!("排除檔案：{}", path.display());
+            continue;
         }
+        files.push(entry.into_path());
     }
+    files.sort();
+    files
+}
+
+/// 計算相對於專案根目錄的路徑
+pub fn relative_path(project_path: &Path, path: &Path) -> String {
+    path.strip_prefix(project_path)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .to_string()
+}
+
+/// 分析單一檔案：本地統計與項目清單，提供 AI 服務時再進行 AI 分析。
+/// 無法讀取檔案時回傳 `None`。
+pub async fn analyze_file(
+    project_path: &Path,
+    path: &Path,
+    analyzer: Option<&Analyzer>,
+) -> Option<FileAnalysis> {
+    tracing::debug!("分析檔案：{}", path.display());
     
-    stats
+    let code_str = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            error!("無法讀取檔案：{} - {}", path.display(), e);
+            return None;
+        }
+    };
+    
+    let stats = analyze_code(&code_str);
+    let relative_path = relative_path(project_path, path);
+    let Inventory { items, imports } = inventory(&code_str, &relative_path);
+    
+    let ai_analysis = match analyzer {
+        Some(analyzer) => {
+            info!("開始對檔案進行 AI 分析：{}", relative_path);
+            match analyzer.analyze_file(&code_str, &items, &relative_path).await {
+                Ok(Some(ai_result)) => {
+                    info!("AI 分析成功：{}", relative_path);
+                    Some(ai_result)
+                }
+                Ok(None) => None,
+                Err(e) => {
+                    error!("AI 分析系統錯誤：{} - {}", relative_path, e);
+                    None
+                }
+            }
+        }
+        None => None,
+    };
+    
+    Some(FileAnalysis {
+        file_path: relative_path,
+        loc: stats.loc,
+        blank_lines: stats.blank_lines,
+        comment_lines: stats.comment_lines,
+        code_lines: stats.code_lines,
+        doc_comment_lines: stats.doc_comment_lines,
+        inner_doc_lines: stats.inner_doc_lines,
+        attribute_lines: stats.attribute_lines,
+        model: analyzer
+            .filter(|_| ai_analysis.is_some())
+            .map(|analyzer| analyzer.provider().model().to_string()),
+        items,
+        imports: Some(imports),
+        ai_analysis,
+        ai_stale: false,
+        analyzed_at: Some(Utc::now()),
+    })
+}
+
+/// 重新計算檔案的行數統計、項目清單與 `use` 路徑，保留 AI 分析。`update` 對未重新分析的
+/// 檔案使用，補上舊版報告缺少的欄位（例如 `imports`），避免架構圖遺漏這些檔案的依賴。
+fn refresh_local(project_path: &Path, analysis: &mut FileAnalysis) {
+    let path = project_path.join(&analysis.file_path);
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) => {
+            error!("無法讀取檔案：{} - {}", path.display(), e);
+            return;
+        }
+    };
+    let stats = analyze_code(&content);
+    let Inventory { items, imports } = inventory(&content, &analysis.file_path);
+    analysis.loc = stats.loc;
+    analysis.blank_lines = stats.blank_lines;
+    analysis.comment_lines = stats.comment_lines;
+    analysis.code_lines = stats.code_lines;
+    analysis.doc_comment_lines = stats.doc_comment_lines;
+    analysis.inner_doc_lines = stats.inner_doc_lines;
+    analysis.attribute_lines = stats.attribute_lines;
+    analysis.items = items;
+    analysis.imports = Some(imports);
+}
+
+/// 以最多 `jobs` 個並行分析檔案。結果依檔案路徑排序，與完成順序無關。
+pub async fn analyze_files(
+    project_path: &Path,
+    files: &[PathBuf],
+    analyzer: Option<&Analyzer>,
+    jobs: usize,
+) -> Vec<FileAnalysis> {
+    let mut analyses: Vec<FileAnalysis> = stream::iter(files)
+        .map(|path| analyze_file(project_path, path, analyzer))
+        .buffer_unordered(jobs.max(1))
+        .filter_map(|analysis| async move { analysis })
+        .collect()
+        .await;
+    analyses.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+    analyses
+}
+
+/// 擷取檔案的項目清單與 `use` 宣告，解析失敗時記錄警告並回傳空清單
+pub fn inventory(content: &str, file_path: &str) -> Inventory {
+    match crate::items::extract_inventory(content) {
+        Ok(inventory) => inventory,
+        Err(e) => {
+            tracing::warn!("無法擷取項目清單：{} - {}", file_path, e);
+            Inventory::default()
+        }
+    }
+}
+
+/// 收集報告的產生資訊；`analyzer` 為 `None` 代表未進行 AI 分析
+pub fn report_metadata(
+    project_path: &Path,
+    analyzer: Option<&Analyzer>,
+    started_at: DateTime<Utc>,
+) -> ReportMetadata {
+    ReportMetadata {
+        generator_version: env!("CARGO_PKG_VERSION").to_string(),
+        provider: analyzer.map(|a| a.provider().kind().to_string()),
+        model: analyzer.map(|a| a.provider().model().to_string()),
+        prompt_hash: analyzer.map(|a| a.prompt_hash().to_string()),
+        language: analyzer.map(|a| a.catalog().code.clone()),
+        git_commit: crate::git::head_commit(project_path),
+        git_dirty: crate::git::is_dirty(project_path),
+        started_at: Some(started_at),
+        finished_at: Some(Utc::now()),
+    }
 }
 
-pub async fn update_report(report_path: &str, project_path: &str, args: &crate::Args) -> Result<()> {
+/// 更新 JSON 報告：讀取報告與設定後以 [`reconcile_report`] 對齊目前的檔案樹，再寫回報告
+pub async fn update_report(
+    report_path: &str,
+    project_path: &str,
+    since: Option<&str>,
+    args: &crate::Args,
+) -> Result<()> {
+    let started_at = Utc::now();
     // 讀取現有的報告
-    let report_content = std::fs::read_to_string(report_path)?;
-    let mut project_analysis: ProjectAnalysis = serde_json::from_str(&report_content)?;
+    let mut project_analysis = crate::report::load(report_path)?;
+    let repo = crate::git::ProjectRepo::discover(Path::new(project_path))?;
+
+    let project_path = Path::new(project_path);
+    let mut config = crate::config::get_effective_config(project_path, args)?;
+    if !args.skip_ai {
+        config.resolve_api_key()?;
+    }
+    let analyzer = Analyzer::new(&config, Catalog::load(&config.language)?);
+    let use_ai = !args.skip_ai && !config.missing_api_key();
+    if !args.skip_ai && config.missing_api_key() {
+        tracing::warn!("跳過 AI 分析（{} 需要 API key，但 API key 為空）", config.provider);
+    }
+    let ai_analyzer = if use_ai { Some(&analyzer) } else { None };
+    match &project_analysis.metadata.language {
+        Some(previous) if use_ai && *previous != analyzer.catalog().code => tracing::warn!(
+            "報告先前以 {} 分析，本次使用 {}；未變更的檔案會保留原本語言的分析",
+            previous,
+            analyzer.catalog().code
+        ),
+        _ => {}
+    }
+
+    let filter = FileFilter::new(project_path, &config.include, &config.exclude)?;
+    reconcile_report(&mut project_analysis, &repo, project_path, since, &filter, ai_analyzer, config.jobs).await?;
+    analyzer.log_cache_stats();
+
+    // 未進行 AI 分析時，沿用先前產生 AI 結果的服務提供者與模型
+    let mut metadata = report_metadata(project_path, ai_analyzer, started_at);
+    if !use_ai {
+        let previous = std::mem::take(&mut project_analysis.metadata);
+        metadata.provider = previous.provider;
+        metadata.model = previous.model;
+        metadata.prompt_hash = previous.prompt_hash;
+        metadata.language = previous.language;
+    }
+    project_analysis.metadata = metadata;
     
-    // 取得 Git 倉庫
-    let repo = Repository::open(project_path)?;
-    let mut options = git2::StatusOptions::new();
-    options.include_untracked(true);
+    // 寫入更新後的報告
+    crate::report::save(report_path, &mut project_analysis)?;
+    info!("報告已更新：{}（{} 個檔案）", report_path, project_analysis.summary.total_files);
     
-    // 取得修改過的檔案
-    let statuses = repo.statuses(Some(&mut options))?;
-    let mut modified_files = HashSet::new();
-    for entry in statuses.iter() {
-        if let Some(path) = entry.path() {
-            modified_files.insert(path.to_string());
-        }
+    Ok(())
+}
+
+/// 將報告與目前的檔案樹對齊：加入新檔案、移除已刪除或排除的檔案、沿用重新命名檔案的分析，
+/// 重新分析 `since`（未指定時為報告記錄的提交）之後修改過的檔案，並更新各層級的總結。
+/// 未提供 `analyzer` 時只重新計算本地資訊，AI 分析與總結沿用先前的結果。
+pub async fn reconcile_report(
+    project_analysis: &mut ProjectAnalysis,
+    repo: &crate::git::ProjectRepo,
+    project_path: &Path,
+    since: Option<&str>,
+    filter: &FileFilter,
+    analyzer: Option<&Analyzer>,
+    jobs: usize,
+) -> Result<()> {
+    // 基準版本之後的變更
+    let since = since.map(str::to_string).or_else(|| project_analysis.metadata.git_commit.clone());
+    match &since {
+        Some(rev) => info!("比對 {} 之後的變更", rev),
+        None => tracing::warn!("報告未記錄來源提交，只比對工作目錄中尚未提交的變更"),
     }
+    let changes = crate::git::changes_since(repo, since.as_deref())?;
     
-    // 重新分析修改過的檔案
-    let project_path = Path::new(project_path);
+    let current: HashSet<String> = discover_rust_files(project_path, filter)
+        .iter()
+        .map(|path| relative_path(project_path, path))
+        .collect();
+    let mut to_analyze: HashSet<String> = HashSet::new();
+    // 影響模組總結的檔案：新增、修改、刪除與重新命名
+    let mut affected: HashSet<String> = HashSet::new();
+    
+    // 重新命名的檔案沿用先前的分析，內容也有修改時再重新分析
+    let reported: HashSet<String> = project_analysis.file_analyses
+        .iter()
+        .map(|analysis| analysis.file_path.clone())
+        .collect();
     for analysis in &mut project_analysis.file_analyses {
-        if modified_files.contains(&analysis.file_path) {
-            let file_path = project_path.join(&analysis.file_path);
-            info!("重新分析檔案：{}", file_path.display());
-            
-            let code_str = std::fs::read_to_string(&file_path)?;
-            let stats = analyze_code(&code_str);
-            
-            analysis.loc = stats.loc;
-            analysis.blank_lines = stats.blank_lines;
-            analysis.comment_lines = stats.comment_lines;
-            analysis.code_lines = stats.code_lines;
-            
-            // 如果有 API key，重新進行 AI 分析
-            if !args.skip_ai {
-                let config = crate::config::get_effective_config(project_path)?;
-                let api_url = args.api_url.as_deref().unwrap_or(&config.api_url);
-                let api_key = args.api_key.as_deref().unwrap_or(&config.api_key);
-                let model = args.model.as_deref().unwrap_or(&config.model);
-
-                if !api_key.is_empty() {
-                    info!("開始對檔案進行 AI 分析：{}", analysis.file_path);
-                    analysis.ai_analysis = match crate::openai::do_ai_analysis_with_retry(
-                        api_url,
-                        api_key,
-                        model,
-                        &code_str,
-                        &analysis.file_path,
-                    ).await {
-                        Ok(Some(ai_result)) => {
-                            info!("AI 分析成功：{}", analysis.file_path);
-                            Some(ai_result)
-                        }
-                        Ok(None) => None,
-                        Err(e) => {
-                            tracing::error!("AI 分析錯誤：{} - {}", analysis.file_path, e);
-                            None
-                        }
-                    };
-                }
-            }
+        let Some(new_path) = changes.renamed.get(&analysis.file_path) else {
+            continue;
+        };
+        if !current.contains(new_path) || reported.contains(new_path) {
+            continue;
+        }
+        info!("檔案已重新命名：{} -> {}", analysis.file_path, new_path);
+        affected.insert(analysis.file_path.clone());
+        affected.insert(new_path.clone());
+        if !changes.same_as_base(repo, &analysis.file_path, &project_path.join(new_path)) {
+            to_analyze.insert(new_path.clone());
         }
+        analysis.file_path = new_path.clone();
     }
     
-    // 如果有 API key，重新生成專案總結
-    if !args.skip_ai {
-        let config = crate::config::get_effective_config(project_path)?;
-        let api_url = args.api_url.as_deref().unwrap_or(&config.api_url);
-        let api_key = args.api_key.as_deref().unwrap_or(&config.api_key);
-        let model = args.model.as_deref().unwrap_or(&config.model);
-
-        if !api_key.is_empty() {
-            match crate::openai::generate_project_summary_with_retry(
-                &project_analysis.file_analyses,
-                api_url,
-                api_key,
-                model
-            ).await {
-                Ok(Some(summary)) => {
-                    project_analysis.summary = summary;
-                }
-                Ok(None) => {}
-                Err(e) => {
-                    tracing::error!("專案總結錯誤：{}", e);
+    // 移除已刪除的檔案
+    project_analysis.file_analyses.retain(|analysis| {
+        let exists = current.contains(&analysis.file_path);
+        if !exists {
+            info!("移除已刪除或排除的檔案：{}", analysis.file_path);
+            affected.insert(analysis.file_path.clone());
+        }
+        exists
+    });
+    
+    // 新增的檔案與修改過的檔案
+    let rename_targets: HashSet<&String> = changes.renamed.values().collect();
+    let reported: HashSet<&str> = project_analysis.file_analyses
+        .iter()
+        .map(|analysis| analysis.file_path.as_str())
+        .collect();
+    for path in &current {
+        if !reported.contains(path.as_str()) {
+            info!("新增檔案：{}", path);
+            to_analyze.insert(path.clone());
+        } else if changes.changed.contains(path) && !rename_targets.contains(path) {
+            to_analyze.insert(path.clone());
+        }
+    }
+    
+    // 未修改的檔案只在本地重新擷取，不呼叫 AI
+    for analysis in &mut project_analysis.file_analyses {
+        if !to_analyze.contains(&analysis.file_path) {
+            refresh_local(project_path, analysis);
+        }
+    }
+
+    let mut changed: Vec<PathBuf> = to_analyze.iter().map(|path| project_path.join(path)).collect();
+    changed.sort();
+    for path in &changed {
+        info!("重新分析檔案：{}", path.display());
+    }
+    let reanalyzed = analyze_files(project_path, &changed, analyzer, jobs).await;
+    for updated in reanalyzed {
+        match project_analysis.file_analyses
+            .iter_mut()
+            .find(|analysis| analysis.file_path == updated.file_path)
+        {
+            Some(analysis) => {
+                // 未進行 AI 分析或 AI 分析失敗時保留先前的結果，並標記為過期
+                let previous_ai = analysis.ai_analysis.take();
+                let previous_model = analysis.model.take();
+                *analysis = updated;
+                if analysis.ai_analysis.is_none() && previous_ai.is_some() {
+                    if analyzer.is_some() {
+                        tracing::warn!("AI 重新分析失敗，保留先前的分析並標記為過期：{}", analysis.file_path);
+                    }
+                    analysis.ai_analysis = previous_ai;
+                    analysis.model = previous_model;
+                    analysis.ai_stale = true;
                 }
             }
+            None => project_analysis.file_analyses.push(updated),
         }
     }
+    project_analysis.file_analyses.sort_by(|a, b| a.file_path.cmp(&b.file_path));
     
-    // 寫入更新後的報告
-    let json_report = serde_json::to_string_pretty(&project_analysis)?;
-    std::fs::write(report_path, &json_report)?;
-    info!("報告已更新：{}", report_path);
-    
+    // 重新計算各層級的統計；AI 可用時只重新總結受變更影響的模組與其上層，否則沿用先前的總結
+    affected.extend(to_analyze.iter().cloned());
+    let previous = crate::summary::Previous { report: project_analysis, changed: &affected };
+    let mut summaries = crate::summary::summarize_project(
+        project_path,
+        filter,
+        &project_analysis.file_analyses,
+        analyzer,
+        Some(&previous),
+        jobs,
+    ).await;
+    if analyzer.is_none() {
+        summaries.keep_overviews(project_analysis);
+    }
+    project_analysis.summary = summaries.summary;
+    project_analysis.crates = summaries.crates;
+    project_analysis.modules = summaries.modules;
     Ok(())
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::provider::tests::FakeProvider;
+    use std::time::Duration;
+
+    /// 在暫存目錄建立含有指定檔案的專案
+    pub(crate) fn temp_project(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rs2know-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        for (path, content) in files {
+            let path = dir.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        dir
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn results_are_sorted_by_path() {
+        let dir = temp_project("order", &[("a.rs", "fn a() {}\n"), ("m/b.rs", "fn b() {}\n"), ("z.rs", "fn z() {}\n")]);
+        // 依路徑排在前面的檔案回應較慢，完成順序與路徑順序相反
+        let provider = FakeProvider {
+            delay: Some(|prompt| {
+                let seconds = [("fn a", 3), ("fn b", 2), ("fn z", 1)].iter().find(|(code, _)| prompt.contains(code)).map_or(0, |(_, s)| *s);
+                Duration::from_secs(seconds)
+            }),
+            ..Default::default()
+        };
+        let analyzer = Analyzer::with_provider(Box::new(provider.clone()), Catalog::load("en").unwrap());
+        let files = [dir.join("a.rs"), dir.join("m/b.rs"), dir.join("z.rs")];
+        let analyses = analyze_files(&dir, &files, Some(&analyzer), 3).await;
+
+        assert_eq!(provider.schemas(), ["ai_analysis"; 3]);
+        let paths: Vec<&str> = analyses.iter().map(|a| a.file_path.as_str()).collect();
+        assert_eq!(paths, ["a.rs", "m/b.rs", "z.rs"]);
+        assert!(analyses.iter().all(|a| a.ai_analysis.is_some()));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn discovered(dir: &Path, include: &[&str], exclude: &[&str]) -> Vec<String> {
+        let strings = |patterns: &[&str]| patterns.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        let filter = FileFilter::new(dir, &strings(include), &strings(exclude)).unwrap();
+        discover_rust_files(dir, &filter).iter().map(|path| relative_path(dir, path)).collect()
+    }
+
+    #[test]
+    fn ignore_files_apply_outside_git() {
+        let dir = temp_project("ignore", &[
+            (".gitignore", "generated/\n*.gen.rs\n"),
+            (".ignore", "scratch.rs\n"),
+            ("src/lib.rs", ""),
+            ("src/model.gen.rs", ""),
+            ("src/scratch.rs", ""),
+            ("generated/out.rs", ""),
+            ("target/debug/build/out.rs", ""),
+            ("crates/a/target/x.rs", ""),
+            ("crates/a/src/lib.rs", ""),
+            (".hidden/x.rs", ""),
+            ("README.md", ""),
+        ]);
+        assert!(!dir.join(".git").exists());
+        assert_eq!(discovered(&dir, &[], &[]), ["crates/a/src/lib.rs", "src/lib.rs"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn exclude_takes_precedence_over_include() {
+        let dir = temp_project("filter", &[
+            ("Cargo.toml", ""),
+            ("src/lib.rs", ""),
+            ("src/gen/a.rs", ""),
+            ("src/gen/keep.rs", ""),
+            ("src/net/mod.rs", ""),
+            ("tests/it.rs", ""),
+            ("benches/b.rs", ""),
+        ]);
+        assert_eq!(discovered(&dir, &["src/"], &[]), ["src/gen/a.rs", "src/gen/keep.rs", "src/lib.rs", "src/net/mod.rs"]);
+        assert_eq!(discovered(&dir, &["src/", "tests/"], &["src/gen/"]), ["src/lib.rs", "src/net/mod.rs", "tests/it.rs"]);
+        // exclude 中的 `!` 重新納入檔案，但 include 仍需符合
+        assert_eq!(
+            discovered(&dir, &["*.rs"], &["src/gen/*", "!src/gen/keep.rs", "benches"]),
+            ["src/gen/keep.rs", "src/lib.rs", "src/net/mod.rs", "tests/it.rs"]
+        );
+        assert_eq!(discovered(&dir, &["**/mod.rs"], &[]), ["src/net/mod.rs"]);
+
+        // Cargo.toml 不套用 include 條件，但仍套用 exclude 條件
+        let filter = FileFilter::new(&dir, &["src/net/".to_string()], &[]).unwrap();
+        assert_eq!(discover_manifests(&dir, &filter), [dir.join("Cargo.toml")]);
+        let filter = FileFilter::new(&dir, &[], &["Cargo.toml".to_string()]).unwrap();
+        assert!(discover_manifests(&dir, &filter).is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn invalid_patterns_are_reported() {
+        let dir = temp_project("patterns", &[("src/lib.rs", "")]);
+        let error = build_matcher(&dir, &["src/".to_string(), "src/{a,b".to_string()]).unwrap_err().to_string();
+        assert!(error.contains("src/{a,b"), "{}", error);
+        assert!(build_matcher(&dir, &["src\\".to_string()]).is_err());
+        assert!(FileFilter::new(&dir, &["**/{a".to_string()], &[]).is_err());
+        assert!(FileFilter::new(&dir, &[], &["[z-a]".to_string()]).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// 重新命名時仍能辨識為相同檔案的內容
+    fn long_file(name: &str) -> String {
+        (0..20).map(|i| format!("pub fn {}_{}() -> u32 {{ {} }}\n", name, i, i)).collect()
+    }
+
+    /// 分析專案中的所有檔案並建立報告；先前的 AI 分析以 `previous` 標記
+    async fn initial_report(dir: &Path, filter: &FileFilter, commit: Option<String>) -> ProjectAnalysis {
+        let mut report = crate::markdown::tests::sample_report();
+        report.file_analyses = analyze_files(dir, &discover_rust_files(dir, filter), None, 1).await;
+        for analysis in &mut report.file_analyses {
+            let mut ai: crate::models::AIAnalysis = crate::structured::parse_response(
+                r#"{"main_functions":["previous"],"core_structs":[],"error_types":[],"functions_details":[]}"#,
+            )
+            .unwrap();
+            ai.code_complexity = analysis.file_path.clone();
+            analysis.ai_analysis = Some(ai);
+        }
+        report.modules.clear();
+        report.crates.clear();
+        report.metadata.git_commit = commit;
+        report
+    }
+
+    fn ai_marker(report: &ProjectAnalysis, path: &str) -> String {
+        let analysis = report.file_analyses.iter().find(|a| a.file_path == path).unwrap();
+        analysis.ai_analysis.as_ref().unwrap().main_functions.join(",")
+    }
+
+    fn paths(report: &ProjectAnalysis) -> Vec<&str> {
+        report.file_analyses.iter().map(|a| a.file_path.as_str()).collect()
+    }
+
+    fn analyzed_files(provider: &FakeProvider) -> Vec<String> {
+        let calls = provider.calls.lock().unwrap();
+        let mut files: Vec<String> = calls
+            .iter()
+            .filter(|(schema, _)| schema == "ai_analysis")
+            .map(|(_, prompt)| prompt.clone())
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[tokio::test]
+    async fn update_reconciles_renames_deletes_and_additions() {
+        let (dir, repo) = crate::git::tests::init_repo("update", &[
+            ("src/lib.rs", "mod keep;\n"),
+            ("src/keep.rs", "pub fn keep() {}\n"),
+            ("src/old.rs", &long_file("old")),
+            ("src/moved.rs", &long_file("moved")),
+            ("src/gone.rs", "pub fn gone() {}\n"),
+            ("src/excluded.rs", "pub fn excluded() {}\n"),
+        ]);
+        let base = crate::git::tests::commit_all(&repo, "base");
+        let mut report = initial_report(&dir, &FileFilter::new(&dir, &[], &[]).unwrap(), Some(base)).await;
+
+        // 已提交：修改內容並重新命名、只重新命名
+        std::fs::remove_file(dir.join("src/old.rs")).unwrap();
+        std::fs::write(dir.join("src/new.rs"), long_file("old").replace("old_0() -> u32 { 0 }", "old_0() -> u32 { 100 }")).unwrap();
+        std::fs::rename(dir.join("src/moved.rs"), dir.join("src/moved2.rs")).unwrap();
+        crate::git::tests::commit_all(&repo, "rename");
+        // 尚未提交：刪除與新增
+        std::fs::remove_file(dir.join("src/gone.rs")).unwrap();
+        std::fs::write(dir.join("src/added.rs"), "pub fn added() {}\n").unwrap();
+
+        let provider = FakeProvider::default();
+        let analyzer = Analyzer::with_provider(Box::new(provider.clone()), Catalog::load("en").unwrap());
+        let filter = FileFilter::new(&dir, &[], &["src/excluded.rs".to_string()]).unwrap();
+        let project = crate::git::ProjectRepo::discover(&dir).unwrap();
+        reconcile_report(&mut report, &project, &dir, None, &filter, Some(&analyzer), 2).await.unwrap();
+
+        assert_eq!(paths(&report), ["src/added.rs", "src/keep.rs", "src/lib.rs", "src/moved2.rs", "src/new.rs"]);
+        // 只有新增與內容有修改的檔案重新分析
+        let analyzed = analyzed_files(&provider);
+        assert_eq!(analyzed.len(), 2);
+        assert!(analyzed.iter().any(|prompt| prompt.contains("pub fn added()")));
+        assert!(analyzed.iter().any(|prompt| prompt.contains("old_0() -> u32 { 100 }")));
+        assert_eq!(ai_marker(&report, "src/new.rs"), "fake");
+        assert_eq!(ai_marker(&report, "src/added.rs"), "fake");
+        assert_eq!(ai_marker(&report, "src/moved2.rs"), "previous");
+        assert_eq!(ai_marker(&report, "src/keep.rs"), "previous");
+        assert!(report.file_analyses.iter().all(|a| !a.ai_stale));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn failed_reanalysis_keeps_previous_analysis() {
+        let (dir, repo) = crate::git::tests::init_repo("update-stale", &[("src/lib.rs", "pub fn a() {}\n")]);
+        let base = crate::git::tests::commit_all(&repo, "base");
+        let filter = FileFilter::new(&dir, &[], &[]).unwrap();
+        let mut report = initial_report(&dir, &filter, Some(base)).await;
+        std::fs::write(dir.join("src/lib.rs"), "pub fn a() {}\n\npub fn b() {}\n").unwrap();
+
+        let provider = FakeProvider { fail: true, ..Default::default() };
+        let analyzer = Analyzer::with_provider(Box::new(provider.clone()), Catalog::load("en").unwrap());
+        let project = crate::git::ProjectRepo::discover(&dir).unwrap();
+        reconcile_report(&mut report, &project, &dir, None, &filter, Some(&analyzer), 1).await.unwrap();
+
+        let analysis = &report.file_analyses[0];
+        assert!(!analyzed_files(&provider).is_empty());
+        assert_eq!(ai_marker(&report, "src/lib.rs"), "previous");
+        assert!(analysis.ai_stale);
+        // 本地資訊仍然更新
+        assert_eq!(analysis.loc, 3);
+        assert_eq!(analysis.items.len(), 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn update_in_a_repository_subdirectory() {
+        let (root, repo) = crate::git::tests::init_repo("update-subdir", &[
+            ("proj/src/a.rs", &long_file("a")),
+            ("proj/src/lib.rs", "mod b;\n"),
+            ("other/src/x.rs", "pub fn x() {}\n"),
+        ]);
+        let base = crate::git::tests::commit_all(&repo, "base");
+        let dir = root.join("proj");
+        let filter = FileFilter::new(&dir, &[], &[]).unwrap();
+        let mut report = initial_report(&dir, &filter, Some(base)).await;
+        assert_eq!(paths(&report), ["src/a.rs", "src/lib.rs"]);
+
+        std::fs::rename(dir.join("src/a.rs"), dir.join("src/b.rs")).unwrap();
+        std::fs::write(root.join("other/src/x.rs"), "pub fn x() -> u32 { 1 }\n").unwrap();
+        crate::git::tests::commit_all(&repo, "rename");
+        std::fs::write(root.join("other/src/y.rs"), "pub fn y() {}\n").unwrap();
+
+        let provider = FakeProvider::default();
+        let analyzer = Analyzer::with_provider(Box::new(provider.clone()), Catalog::load("en").unwrap());
+        let project = crate::git::ProjectRepo::discover(&dir).unwrap();
+        reconcile_report(&mut report, &project, &dir, None, &filter, Some(&analyzer), 1).await.unwrap();
+
+        // 重新命名以專案目錄為準，專案之外的變更不影響報告
+        assert_eq!(paths(&report), ["src/b.rs", "src/lib.rs"]);
+        assert!(analyzed_files(&provider).is_empty());
+        assert_eq!(ai_marker(&report, "src/b.rs"), "previous");
+        assert_eq!(report.file_analyses[0].ai_analysis.as_ref().unwrap().code_complexity, "src/a.rs");
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
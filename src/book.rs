@@ -0,0 +1,197 @@
+//! 以 mdBook 的目錄結構輸出報告：首頁為專案總結，每個目錄與原始碼檔案各一頁，
+//! `SUMMARY.md` 依專案的目錄樹排列各頁面。專案根目錄的頁面為 `root.md`。
+//!
+//! 頁面路徑取自報告中的檔案路徑，寫入前會檢查路徑只包含一般的路徑成分，不會寫到 `src` 之外。
+//!
+//! 各頁面以 `templates/book/` 中的範本產生，與單一檔案的報告共用 `macros.md.tera`。
+
+use anyhow::{Result, anyhow};
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use tera::Context;
+use tracing::info;
+use crate::locale::Catalog;
+use crate::markdown::{base_context, directories, engine, error_chain, DirectoryContext};
+use crate::models::ProjectAnalysis;
+
+const TEMPLATES: &[(&str, &str)] = &[
+    ("book/index.md", include_str!("../templates/book/index.md.tera")),
+    ("book/directory.md", include_str!("../templates/book/directory.md.tera")),
+    ("book/file.md", include_str!("../templates/book/file.md.tera")),
+];
+
+/// 專案根目錄的頁面；不會與目錄（`<名稱>/index.md`）或檔案（`<名稱>.rs.md`）的頁面衝突
+const ROOT_PAGE: &str = "root.md";
+
+/// `SUMMARY.md` 中的一層目錄；沒有檔案的中間目錄 `dir` 為 `None`
+#[derive(Default)]
+struct TreeNode<'d, 'a> {
+    dir: Option<&'d DirectoryContext<'a>>,
+    children: BTreeMap<&'d str, TreeNode<'d, 'a>>,
+}
+
+/// 將報告寫成 mdBook 目錄：`book.toml`、`src/SUMMARY.md` 與各頁面
+pub async fn generate_book(report: &ProjectAnalysis, catalog: &Catalog, output_dir: &str) -> Result<()> {
+    let output_dir = Path::new(output_dir);
+    let src = output_dir.join("src");
+    std::fs::create_dir_all(&src)?;
+
+    let tera = engine(TEMPLATES).map_err(|e| anyhow!("無法載入 mdBook 範本：{}", error_chain(&e)))?;
+    let context = base_context(report, catalog)?;
+    // 頁面結尾統一保留一個換行
+    let render = |name: &str, context: &Context| {
+        tera.render(name, context)
+            .map(|page| format!("{}\n", page.trim_end()))
+            .map_err(|e| anyhow!("無法產生 mdBook 頁面 {}：{}", name, error_chain(&e)))
+    };
+
+    let title = catalog.report.get("title").cloned().unwrap_or_default();
+    let mut book = toml::Table::new();
+    book.insert("title".to_string(), title.clone().into());
+    book.insert("language".to_string(), catalog.code.clone().into());
+    book.insert("src".to_string(), "src".into());
+    let mut config = toml::Table::new();
+    config.insert("book".to_string(), book.into());
+    std::fs::write(output_dir.join("book.toml"), toml::to_string(&config)?)?;
+
+    std::fs::write(src.join("index.md"), render("book/index.md", &context)?)?;
+
+    let directories = directories(report, catalog);
+    let mut pages = 1;
+    for dir in &directories {
+        let dir_path = page_path(&src, Path::new(&dir.key))?;
+        std::fs::create_dir_all(&dir_path)?;
+        let mut page_context = context.clone();
+        page_context.insert("dir", dir);
+        let index = if dir.key.is_empty() { ROOT_PAGE } else { "index.md" };
+        std::fs::write(dir_path.join(index), render("book/directory.md", &page_context)?)?;
+        pages += 1;
+        for file in &dir.files {
+            let mut page_context = context.clone();
+            page_context.insert("dir", dir);
+            page_context.insert("file", file);
+            let page = page_path(&src, &Path::new(&dir.key).join(format!("{}.md", file.name)))?;
+            std::fs::write(page, render("book/file.md", &page_context)?)?;
+            pages += 1;
+        }
+    }
+
+    std::fs::write(src.join("SUMMARY.md"), summary(&title, &directories))?;
+    info!("mdBook 已生成並寫入 {}（{} 頁）", output_dir.display(), pages);
+    Ok(())
+}
+
+/// 報告中的相對路徑轉為 `src` 之下的路徑。忽略 `.`，拒絕 `..`、絕對路徑等其他路徑成分，
+/// 避免報告的內容讓頁面寫到輸出目錄之外。
+fn page_path(src: &Path, relative: &Path) -> Result<PathBuf> {
+    let mut path = src.to_path_buf();
+    for component in relative.components() {
+        match component {
+            Component::Normal(part) => path.push(part),
+            Component::CurDir => {}
+            _ => return Err(anyhow!("報告中的路徑不安全，拒絕寫入：{}", relative.display())),
+        }
+    }
+    if !path.starts_with(src) {
+        return Err(anyhow!("頁面路徑不在 {} 之中：{}", src.display(), path.display()));
+    }
+    Ok(path)
+}
+
+/// 產生 `SUMMARY.md`：首頁、專案根目錄的頁面與其中的檔案，接著依目錄樹列出各目錄與其中的檔案
+fn summary(title: &str, directories: &[DirectoryContext<'_>]) -> String {
+    let mut root = TreeNode::default();
+    for dir in directories {
+        let mut node = &mut root;
+        for component in dir.key.split('/').filter(|c| !c.is_empty()) {
+            node = node.children.entry(component).or_default();
+        }
+        node.dir = Some(dir);
+    }
+
+    let mut content = format!("# Summary\n\n[{}](index.md)\n\n", title);
+    if let Some(dir) = root.dir {
+        content.push_str(&format!("- [{}]({})\n", dir.name, ROOT_PAGE));
+        for file in &dir.files {
+            content.push_str(&format!("  - [{}]({})\n", file.name, link(&format!("{}.md", file.name))));
+        }
+    }
+    push_tree(&mut content, &root, "", 0);
+    content
+}
+
+fn push_tree(content: &mut String, node: &TreeNode<'_, '_>, prefix: &str, depth: usize) {
+    let indent = "  ".repeat(depth);
+    for (name, child) in &node.children {
+        let path = format!("{}{}", prefix, name);
+        match child.dir {
+            Some(dir) => {
+                content.push_str(&format!("{}- [{}]({})\n", indent, name, link(&format!("{}/index.md", path))));
+                for file in &dir.files {
+                    let page = format!("{}/{}.md", path, file.name);
+                    content.push_str(&format!("{}  - [{}]({})\n", indent, file.name, link(&page)));
+                }
+            }
+            // 沒有檔案的中間目錄以草稿章節表示
+            None => content.push_str(&format!("{}- [{}]()\n", indent, name)),
+        }
+        push_tree(content, child, &format!("{}/", path), depth + 1);
+    }
+}
+
+/// mdBook 以 Markdown 連結解析 `SUMMARY.md`，空白與括號需要編碼
+fn link(path: &str) -> String {
+    path.replace('%', "%25")
+        .replace(' ', "%20")
+        .replace('(', "%28")
+        .replace(')', "%29")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown::tests::sample_report;
+
+    fn output_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rs2know-book-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn page_path_rejects_escapes() {
+        let src = Path::new("/book/src");
+        assert_eq!(page_path(src, Path::new("./a/b.rs.md")).unwrap(), Path::new("/book/src/a/b.rs.md"));
+        for unsafe_path in ["../a.rs.md", "a/../../b.rs.md", "/etc/passwd"] {
+            assert!(page_path(src, Path::new(unsafe_path)).is_err(), "{}", unsafe_path);
+        }
+    }
+
+    #[tokio::test]
+    async fn root_directory_has_a_page() {
+        let report = sample_report();
+        let catalog = Catalog::load("zh-TW").unwrap();
+        let dir = output_dir("root");
+        generate_book(&report, &catalog, dir.to_str().unwrap()).await.unwrap();
+
+        let root = std::fs::read_to_string(dir.join("src").join(ROOT_PAGE)).unwrap();
+        assert!(root.starts_with(&format!("# {}\n", catalog.report["root_dir"])));
+        assert!(root.contains(". 的用途"));
+        assert!(root.contains("(main.rs.md)"));
+        let summary = std::fs::read_to_string(dir.join("src/SUMMARY.md")).unwrap();
+        assert!(summary.contains(&format!("- [{}](root.md)\n  - [main.rs](main.rs.md)\n", catalog.report["root_dir"])));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn rejects_paths_outside_src() {
+        let mut report = sample_report();
+        report.file_analyses[0].file_path = "../../escaped.rs".to_string();
+        let catalog = Catalog::load("en").unwrap();
+        let dir = output_dir("escape");
+        assert!(generate_book(&report, &catalog, dir.to_str().unwrap()).await.is_err());
+        // `src/../../escaped.rs` 會落在輸出目錄的上一層
+        assert!(!dir.parent().unwrap().join("escaped.rs.md").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,238 @@
+//! 以內容定址的 AI 分析快取。
+//!
+//! 快取鍵為提示詞版本、服務提供者、模型、溫度、輸入上限與檔案內容的 SHA-256，因此
+//! 檔案內容、模型、提示詞、溫度或切塊方式任一改變都會重新分析。快取項目存放在
+//! `~/.cache/rs2know/analysis/<鍵的前兩個字元>/<鍵>.json`。
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{debug, info, warn};
+use crate::models::AIAnalysis;
+
+const CACHE_DIR: &str = "rs2know/analysis";
+
+/// 寫入時借用分析結果，讀取時取得所有權
+#[derive(Serialize, Deserialize)]
+struct CacheEntry<A = AIAnalysis> {
+    prompt_version: u32,
+    model: String,
+    /// 寫入時間（Unix 秒數）
+    created_at: u64,
+    analysis: A,
+}
+
+/// 磁碟上的分析快取，並記錄本次執行的命中次數
+pub struct AnalysisCache {
+    dir: PathBuf,
+    prompt_version: u32,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+fn cache_root() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join(CACHE_DIR))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+impl AnalysisCache {
+    /// 找不到使用者快取目錄時回傳 `None`
+    pub fn open(prompt_version: u32) -> Option<Self> {
+        Some(Self {
+            dir: cache_root()?,
+            prompt_version,
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        })
+    }
+
+    /// 計算快取鍵；`prompt_hash` 區分不同語言或自訂的提示詞，`max_input_tokens` 決定
+    /// 檔案是否切塊分析以及切塊的位置，與 `temperature` 同樣會影響結果
+    pub fn key(
+        &self,
+        provider: &str,
+        model: &str,
+        prompt_hash: &str,
+        temperature: f32,
+        max_input_tokens: usize,
+        content: &str,
+    ) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.prompt_version.to_le_bytes());
+        hasher.update(temperature.to_le_bytes());
+        hasher.update((max_input_tokens as u64).to_le_bytes());
+        for part in [provider, model, prompt_hash, content] {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        format!("{:x}", hasher.finalize())
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(&key[..2]).join(format!("{}.json", key))
+    }
+
+    pub fn get(&self, key: &str) -> Option<AIAnalysis> {
+        let entry = fs::read_to_string(self.path(key))
+            .ok()
+            .and_then(|content| serde_json::from_str::<CacheEntry>(&content).ok())
+            .filter(|entry| entry.prompt_version == self.prompt_version);
+        match entry {
+            Some(entry) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                debug!("AI 快取命中：{}", key);
+                Some(entry.analysis)
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// 寫入失敗只記錄警告，不影響分析結果
+    pub fn put(&self, key: &str, model: &str, analysis: &AIAnalysis) {
+        let entry = CacheEntry {
+            prompt_version: self.prompt_version,
+            model: model.to_string(),
+            created_at: now(),
+            analysis,
+        };
+        if let Err(e) = self.write(key, &entry) {
+            warn!("無法寫入 AI 快取：{}", e);
+        }
+    }
+
+    fn write(&self, key: &str, entry: &CacheEntry<&AIAnalysis>) -> Result<()> {
+        let path = self.path(key);
+        let dir = path.parent().ok_or_else(|| anyhow!("無效的快取路徑"))?;
+        fs::create_dir_all(dir)?;
+        // 先寫入暫存檔再改名，避免並行分析時讀到寫到一半的檔案
+        let tmp = dir.join(format!("{}.{}.tmp", key, std::process::id()));
+        fs::write(&tmp, serde_json::to_string(entry)?)?;
+        fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    pub fn log_stats(&self) {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        if hits + misses > 0 {
+            info!("AI 快取：命中 {}，未命中 {}（{}）", hits, misses, self.dir.display());
+        }
+    }
+}
+
+/// 刪除超過 `older_than` 的快取項目，以及提示詞版本不同的項目；`all` 為真時清空快取。
+/// 回傳刪除的項目數量。
+pub fn prune(prompt_version: u32, older_than: Duration, all: bool) -> Result<usize> {
+    let root = cache_root().ok_or_else(|| anyhow!("無法找到使用者快取目錄"))?;
+    if !root.exists() {
+        return Ok(0);
+    }
+    let cutoff = now().saturating_sub(older_than.as_secs());
+    let mut removed = 0;
+    for entry in walkdir::WalkDir::new(&root).into_iter().filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let cached = fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<CacheEntry>(&content).ok());
+        let stale = all || match cached {
+            Some(cached) => cached.prompt_version != prompt_version || cached.created_at < cutoff,
+            // 無法解析的項目（包含中斷留下的暫存檔）一律刪除
+            None => true,
+        };
+        if stale {
+            fs::remove_file(path)?;
+            removed += 1;
+        }
+    }
+    // 移除清空後的分片目錄；非空目錄會刪除失敗，直接忽略
+    if let Ok(shards) = fs::read_dir(&root) {
+        for shard in shards.filter_map(|e| e.ok()) {
+            let _ = fs::remove_dir(shard.path());
+        }
+    }
+    info!("已刪除 {} 個快取項目：{}", removed, root.display());
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache() -> AnalysisCache {
+        AnalysisCache {
+            dir: PathBuf::new(),
+            prompt_version: 1,
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+
+    /// 快取鍵的所有輸入
+    #[derive(Clone)]
+    struct KeyInput {
+        provider: &'static str,
+        model: &'static str,
+        prompt_hash: &'static str,
+        temperature: f32,
+        max_input_tokens: usize,
+        content: &'static str,
+    }
+
+    impl KeyInput {
+        fn key(&self, cache: &AnalysisCache) -> String {
+            cache.key(self.provider, self.model, self.prompt_hash, self.temperature, self.max_input_tokens, self.content)
+        }
+    }
+
+    type Change = fn(&mut KeyInput);
+
+    #[test]
+    fn key_depends_on_every_input() {
+        let cache = cache();
+        let base = KeyInput {
+            provider: "openai",
+            model: "gpt-4o-mini",
+            prompt_hash: "hash",
+            temperature: 0.2,
+            max_input_tokens: 12000,
+            content: "fn main() {}",
+        };
+        assert_eq!(base.key(&cache), base.clone().key(&cache));
+
+        let changes: [(&str, Change); 7] = [
+            ("provider", |k| k.provider = "anthropic"),
+            ("model", |k| k.model = "gpt-4o"),
+            ("prompt_hash", |k| k.prompt_hash = "other"),
+            ("temperature", |k| k.temperature = 0.7),
+            ("max_input_tokens", |k| k.max_input_tokens = 4000),
+            ("content", |k| k.content = "fn main() { }"),
+            // 各欄位之間有分隔，不會因為邊界移動而相同
+            ("provider/model boundary", |k| (k.provider, k.model) = ("openaigpt", "-4o-mini")),
+        ];
+        for (name, change) in changes {
+            let mut input = base.clone();
+            change(&mut input);
+            assert_ne!(input.key(&cache), base.key(&cache), "{}", name);
+        }
+
+        let key = base.key(&cache);
+        let newer = AnalysisCache { prompt_version: 2, ..cache };
+        assert_ne!(base.key(&newer), key, "prompt_version");
+    }
+}
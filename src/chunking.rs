@@ -0,0 +1,208 @@
+//! 將超過模型輸入上限的原始碼依項目邊界切塊。
+//!
+//! 切塊優先沿著頂層項目（函數、impl 等）的邊界；單一項目仍然過大時，改以其成員
+//! （例如 impl 中的方法）為邊界；仍然過大時才逐行切分。項目前方的文件註解與屬性
+//! 會與項目放在同一塊。
+
+use crate::models::ItemInfo;
+use crate::tokens::estimate_tokens;
+
+/// 原始碼的一段，行號從 1 開始且包含結尾
+#[derive(Debug, Clone)]
+pub struct Chunk {
+    pub start_line: usize,
+    pub end_line: usize,
+    pub text: String,
+}
+
+struct Chunker<'a> {
+    lines: Vec<&'a str>,
+    items: &'a [ItemInfo],
+    max_tokens: usize,
+}
+
+impl Chunker<'_> {
+    fn text(&self, start: usize, end: usize) -> String {
+        self.lines[start - 1..end].join("\n")
+    }
+
+    fn tokens(&self, start: usize, end: usize) -> usize {
+        self.lines[start - 1..end].iter().map(|l| estimate_tokens(l) + 1).sum()
+    }
+
+    /// 以 `cuts` 中的行號為段落結尾切分 `start..=end`
+    fn split_at(start: usize, end: usize, cuts: &[usize]) -> Vec<(usize, usize)> {
+        let mut segments = Vec::new();
+        let mut from = start;
+        for &cut in cuts {
+            if cut >= from && cut < end {
+                segments.push((from, cut));
+                from = cut + 1;
+            }
+        }
+        segments.push((from, end));
+        segments
+    }
+
+    /// 將段落細分到每段都不超過上限
+    fn refine(&self, start: usize, end: usize, depth: usize) -> Vec<(usize, usize)> {
+        if self.tokens(start, end) <= self.max_tokens {
+            return vec![(start, end)];
+        }
+        // 改以段落內成員項目的結尾為邊界
+        let mut cuts: Vec<usize> = self.items
+            .iter()
+            .filter(|item| item.parent.is_some())
+            .filter(|item| item.start_line >= start && item.end_line < end)
+            .map(|item| item.end_line)
+            .collect();
+        cuts.sort_unstable();
+        cuts.dedup();
+        if cuts.is_empty() || depth > 2 {
+            return self.split_lines(start, end);
+        }
+        Self::split_at(start, end, &cuts)
+            .into_iter()
+            .flat_map(|(from, to)| self.refine(from, to, depth + 1))
+            .collect()
+    }
+
+    fn split_lines(&self, start: usize, end: usize) -> Vec<(usize, usize)> {
+        let mut segments = Vec::new();
+        let mut from = start;
+        let mut used = 0;
+        for line in start..=end {
+            let cost = estimate_tokens(self.lines[line - 1]) + 1;
+            if used + cost > self.max_tokens && line > from {
+                segments.push((from, line - 1));
+                from = line;
+                used = 0;
+            }
+            used += cost;
+        }
+        segments.push((from, end));
+        segments
+    }
+
+    /// 將相鄰段落合併成不超過上限的區塊
+    fn pack(&self, segments: Vec<(usize, usize)>) -> Vec<Chunk> {
+        let mut chunks: Vec<Chunk> = Vec::new();
+        let mut current: Option<(usize, usize)> = None;
+        for (start, end) in segments {
+            current = match current {
+                Some((from, _)) if self.tokens(from, end) <= self.max_tokens => Some((from, end)),
+                Some((from, to)) => {
+                    chunks.push(Chunk { start_line: from, end_line: to, text: self.text(from, to) });
+                    Some((start, end))
+                }
+                None => Some((start, end)),
+            };
+        }
+        if let Some((from, to)) = current {
+            chunks.push(Chunk { start_line: from, end_line: to, text: self.text(from, to) });
+        }
+        chunks
+    }
+}
+
+/// 將原始碼切成每塊估計不超過 `max_tokens` 的區塊；未超過上限時回傳單一區塊
+pub fn chunk_source(content: &str, items: &[ItemInfo], max_tokens: usize) -> Vec<Chunk> {
+    let lines: Vec<&str> = content.lines().collect();
+    if lines.is_empty() || estimate_tokens(content) <= max_tokens {
+        return vec![Chunk {
+            start_line: 1,
+            end_line: lines.len(),
+            text: content.to_string(),
+        }];
+    }
+
+    let chunker = Chunker {
+        lines,
+        items,
+        max_tokens: max_tokens.max(1),
+    };
+    let last_line = chunker.lines.len();
+    let mut top_level: Vec<usize> = items
+        .iter()
+        .filter(|item| item.parent.is_none())
+        .map(|item| item.end_line)
+        .collect();
+    top_level.sort_unstable();
+    top_level.dedup();
+
+    let segments = Chunker::split_at(1, last_line, &top_level)
+        .into_iter()
+        .flat_map(|(start, end)| chunker.refine(start, end, 1))
+        .collect();
+    chunker.pack(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::items::extract_inventory;
+    use crate::models::ItemKind;
+
+    const FIXTURE: &str = include_str!("../tests/fixtures/chunking/service.rs");
+    const MAX_TOKENS: usize = 60;
+
+    fn chunks() -> (Vec<ItemInfo>, Vec<Chunk>) {
+        let items = extract_inventory(FIXTURE).unwrap().items;
+        let chunks = chunk_source(FIXTURE, &items, MAX_TOKENS);
+        (items, chunks)
+    }
+
+    #[test]
+    fn small_sources_are_a_single_chunk() {
+        let items = extract_inventory(FIXTURE).unwrap().items;
+        let chunks = chunk_source(FIXTURE, &items, usize::MAX);
+        assert_eq!(chunks.len(), 1);
+        assert_eq!((chunks[0].start_line, chunks[0].end_line), (1, FIXTURE.lines().count()));
+        assert_eq!(chunks[0].text, FIXTURE);
+    }
+
+    #[test]
+    fn chunks_cover_every_line_once() {
+        let (_, chunks) = chunks();
+        let lines: Vec<&str> = FIXTURE.lines().collect();
+        assert!(chunks.len() > 1);
+        let mut next = 1;
+        for chunk in &chunks {
+            assert_eq!(chunk.start_line, next);
+            assert!(chunk.end_line >= chunk.start_line);
+            assert_eq!(chunk.text, lines[chunk.start_line - 1..chunk.end_line].join("\n"));
+            assert!(estimate_tokens(&chunk.text) <= MAX_TOKENS, "{}-{}", chunk.start_line, chunk.end_line);
+            next = chunk.end_line + 1;
+        }
+        assert_eq!(next, lines.len() + 1);
+    }
+
+    #[test]
+    fn splits_follow_item_boundaries() {
+        let (items, chunks) = chunks();
+        let registry = items.iter().find(|item| item.kind == ItemKind::Impl).unwrap();
+        let report = items.iter().find(|item| item.name == "report").unwrap();
+        let ends = |top_level: bool| -> Vec<usize> {
+            items.iter().filter(|item| item.parent.is_none() == top_level).map(|item| item.end_line).collect()
+        };
+        let (top_ends, member_ends) = (ends(true), ends(false));
+
+        let mut member_splits = 0;
+        let mut line_splits = 0;
+        for chunk in &chunks[..chunks.len() - 1] {
+            let end = chunk.end_line;
+            if end > report.start_line && end < report.end_line {
+                // 過長的方法退回逐行切分
+                line_splits += 1;
+            } else if end > registry.start_line && end < registry.end_line {
+                // 過長的 impl 以方法為邊界
+                assert!(member_ends.contains(&end), "第 {} 行不是方法的結尾", end);
+                member_splits += 1;
+            } else {
+                assert!(top_ends.contains(&end), "第 {} 行不是頂層項目的結尾", end);
+            }
+        }
+        assert!(member_splits > 0);
+        assert!(line_splits > 0);
+    }
+}
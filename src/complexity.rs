@@ -0,0 +1,229 @@
+//! 由語法樹計算函數的複雜度指標。
+//!
+//! - 循環複雜度（cyclomatic）：1 加上每個 `if`、`while`、`for`、match guard、
+//!   多出的 match 分支以及每個 `&&` / `||`。
+//! - 認知複雜度（cognitive）：依 SonarSource 的定義，控制流程結構 +1 並加上目前的
+//!   巢狀層級；`else if` / `else` 各 +1；每段相同的邏輯運算子序列 +1；帶標籤的
+//!   `break` / `continue` 與直接遞迴呼叫各 +1。
+//!
+//! 函數內部宣告的巢狀項目不計入外層函數。
+
+use syn::visit::{self, Visit};
+use syn::{BinOp, Block, Expr, Signature};
+use crate::models::ComplexityMetrics;
+
+struct Walker<'a> {
+    fn_name: &'a str,
+    cyclomatic: u32,
+    cognitive: u32,
+    nesting: u32,
+    max_nesting: u32,
+}
+
+impl Walker<'_> {
+    fn nested<F: FnOnce(&mut Self)>(&mut self, f: F) {
+        self.nesting += 1;
+        self.max_nesting = self.max_nesting.max(self.nesting);
+        f(self);
+        self.nesting -= 1;
+    }
+
+    /// 控制流程結構：+1 並加上巢狀層級
+    fn structure(&mut self) {
+        self.cognitive += 1 + self.nesting;
+    }
+
+    /// 將連續的 `&&` / `||` 展開，回傳運算子序列與非邏輯運算元
+    fn flatten_logical<'e>(expr: &'e Expr, ops: &mut Vec<bool>, leaves: &mut Vec<&'e Expr>) {
+        match expr {
+            Expr::Binary(bin) if matches!(bin.op, BinOp::And(_) | BinOp::Or(_)) => {
+                Self::flatten_logical(&bin.left, ops, leaves);
+                ops.push(matches!(bin.op, BinOp::And(_)));
+                Self::flatten_logical(&bin.right, ops, leaves);
+            }
+            _ => leaves.push(expr),
+        }
+    }
+
+    fn is_recursive_call(&self, func: &Expr) -> bool {
+        match func {
+            Expr::Path(path) => path.path.segments.last().is_some_and(|s| s.ident == self.fn_name),
+            _ => false,
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for Walker<'_> {
+    fn visit_expr_if(&mut self, node: &'ast syn::ExprIf) {
+        self.structure();
+        self.cyclomatic += 1;
+        self.visit_expr(&node.cond);
+        self.nested(|w| w.visit_block(&node.then_branch));
+
+        let mut else_branch = node.else_branch.as_ref().map(|(_, e)| e.as_ref());
+        while let Some(expr) = else_branch {
+            self.cognitive += 1;
+            match expr {
+                Expr::If(else_if) => {
+                    self.cyclomatic += 1;
+                    self.visit_expr(&else_if.cond);
+                    self.nested(|w| w.visit_block(&else_if.then_branch));
+                    else_branch = else_if.else_branch.as_ref().map(|(_, e)| e.as_ref());
+                }
+                other => {
+                    self.nested(|w| w.visit_expr(other));
+                    else_branch = None;
+                }
+            }
+        }
+    }
+
+    fn visit_expr_match(&mut self, node: &'ast syn::ExprMatch) {
+        self.structure();
+        self.cyclomatic += node.arms.len().saturating_sub(1) as u32;
+        self.visit_expr(&node.expr);
+        self.nested(|w| {
+            for arm in &node.arms {
+                if let Some((_, guard)) = &arm.guard {
+                    w.cyclomatic += 1;
+                    w.visit_expr(guard);
+                }
+                w.visit_expr(&arm.body);
+            }
+        });
+    }
+
+    fn visit_expr_while(&mut self, node: &'ast syn::ExprWhile) {
+        self.structure();
+        self.cyclomatic += 1;
+        self.visit_expr(&node.cond);
+        self.nested(|w| w.visit_block(&node.body));
+    }
+
+    fn visit_expr_for_loop(&mut self, node: &'ast syn::ExprForLoop) {
+        self.structure();
+        self.cyclomatic += 1;
+        self.visit_expr(&node.expr);
+        self.nested(|w| w.visit_block(&node.body));
+    }
+
+    fn visit_expr_loop(&mut self, node: &'ast syn::ExprLoop) {
+        self.structure();
+        self.nested(|w| w.visit_block(&node.body));
+    }
+
+    fn visit_expr_closure(&mut self, node: &'ast syn::ExprClosure) {
+        self.nested(|w| w.visit_expr(&node.body));
+    }
+
+    fn visit_expr_binary(&mut self, node: &'ast syn::ExprBinary) {
+        if !matches!(node.op, BinOp::And(_) | BinOp::Or(_)) {
+            visit::visit_expr_binary(self, node);
+            return;
+        }
+        let mut ops = Vec::new();
+        let mut leaves = Vec::new();
+        Self::flatten_logical(&node.left, &mut ops, &mut leaves);
+        ops.push(matches!(node.op, BinOp::And(_)));
+        Self::flatten_logical(&node.right, &mut ops, &mut leaves);
+
+        self.cyclomatic += ops.len() as u32;
+        self.cognitive += 1 + ops.windows(2).filter(|w| w[0] != w[1]).count() as u32;
+        for leaf in leaves {
+            self.visit_expr(leaf);
+        }
+    }
+
+    fn visit_expr_break(&mut self, node: &'ast syn::ExprBreak) {
+        if node.label.is_some() {
+            self.cognitive += 1;
+        }
+        visit::visit_expr_break(self, node);
+    }
+
+    fn visit_expr_continue(&mut self, node: &'ast syn::ExprContinue) {
+        if node.label.is_some() {
+            self.cognitive += 1;
+        }
+    }
+
+    fn visit_expr_call(&mut self, node: &'ast syn::ExprCall) {
+        if self.is_recursive_call(&node.func) {
+            self.cognitive += 1;
+        }
+        visit::visit_expr_call(self, node);
+    }
+
+    fn visit_expr_method_call(&mut self, node: &'ast syn::ExprMethodCall) {
+        let on_self = matches!(&*node.receiver, Expr::Path(p) if p.path.is_ident("self"));
+        if on_self && node.method == self.fn_name {
+            self.cognitive += 1;
+        }
+        visit::visit_expr_method_call(self, node);
+    }
+
+    fn visit_item(&mut self, _node: &'ast syn::Item) {
+        // 巢狀項目另行計算
+    }
+}
+
+/// 計算單一函數的複雜度指標
+pub fn measure(sig: &Signature, block: &Block) -> ComplexityMetrics {
+    let fn_name = sig.ident.to_string();
+    let mut walker = Walker {
+        fn_name: &fn_name,
+        cyclomatic: 1,
+        cognitive: 0,
+        nesting: 0,
+        max_nesting: 0,
+    };
+    walker.visit_block(block);
+    ComplexityMetrics {
+        cyclomatic: walker.cyclomatic,
+        cognitive: walker.cognitive,
+        max_nesting: walker.max_nesting,
+        parameters: sig.inputs.len() as u32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = include_str!("../tests/fixtures/complexity/functions.rs");
+
+    /// (函數, [循環複雜度, 認知複雜度, 最大巢狀深度, 參數數量])
+    const EXPECTED: &[(&str, [u32; 4])] = &[
+        ("straight", [1, 0, 0, 1]),
+        ("nested_if_match", [6, 5, 2, 2]),
+        ("logical", [7, 5, 1, 4]),
+        ("closures", [2, 3, 2, 1]),
+        ("search", [4, 8, 3, 2]),
+        ("countdown", [3, 6, 3, 1]),
+        ("outer", [1, 0, 0, 0]),
+    ];
+
+    #[test]
+    fn fixture_scores() {
+        let file = syn::parse_file(FIXTURE).unwrap();
+        let functions: Vec<_> = file
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                syn::Item::Fn(f) => Some(f),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(functions.len(), EXPECTED.len());
+        for (function, (name, expected)) in functions.iter().zip(EXPECTED) {
+            assert_eq!(function.sig.ident, name);
+            let metrics = measure(&function.sig, &function.block);
+            assert_eq!(
+                [metrics.cyclomatic, metrics.cognitive, metrics.max_nesting, metrics.parameters],
+                *expected,
+                "{}",
+                name
+            );
+        }
+    }
+}
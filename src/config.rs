@@ -1,120 +1,859 @@
 use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
 use std::fs;
-use tracing::{info, warn};
-use dialoguer::{Input, theme::ColorfulTheme, Select};
+use tracing::{debug, info, warn};
+use dialoguer::{Input, Password, theme::ColorfulTheme, Select};
+use crate::provider::{self, ProviderKind};
 
 const CONFIG_FILE: &str = ".pj.yml";
-const GLOBAL_CONFIG_DIR: &str = ".config/rs2know";
+pub const GLOBAL_CONFIG_DIR: &str = ".config/rs2know";
 
-#[derive(Debug, Serialize, Deserialize)]
+/// 設定檔中可以使用的欄位，依 `config show` 列出的順序
+pub const KEYS: &[&str] = &[
+    "provider",
+    "api_url",
+    "api_key",
+    "api_key_env",
+    "api_key_file",
+    "api_key_command",
+    "model",
+    "temperature",
+    "jobs",
+    "requests_per_minute",
+    "tokens_per_minute",
+    "max_input_tokens",
+    "cache",
+    "language",
+    "include",
+    "exclude",
+];
+
+/// API 金鑰的來源欄位；同一來源只會採用其中一個
+const KEY_SOURCES: &[&str] = &["api_key", "api_key_env", "api_key_file", "api_key_command"];
+
+/// 環境變數的前綴，例如 `RS2KNOW_MODEL` 對應 `model`
+const ENV_PREFIX: &str = "RS2KNOW_";
+
+/// 合併所有來源後的有效設定。
+///
+/// 來源的優先順序由低到高為：內建預設值、全局配置、專案配置、選用的 profile、
+/// `RS2KNOW_*` 環境變數、命令列參數；每個欄位各自取優先順序最高的來源。
 pub struct Config {
+    /// 選用的 profile
+    pub profile: Option<String>,
+    pub provider: ProviderKind,
     pub api_url: String,
+    /// API 金鑰；以其他來源設定時，由 [`Config::resolve_api_key`] 取得
     pub api_key: String,
+    /// 存放 API 金鑰的環境變數名稱，例如 `OPENAI_API_KEY`
+    pub api_key_env: Option<String>,
+    /// 存放 API 金鑰的檔案，內容前後的空白會被去除
+    pub api_key_file: Option<String>,
+    /// 輸出 API 金鑰的指令，例如 `pass show openai`
+    pub api_key_command: Option<String>,
     pub model: String,
+    /// 請求使用的溫度；未設定時檔案分析使用 0.2，總結沿用服務提供者的預設值
+    pub temperature: Option<f32>,
+    /// 同時進行的檔案分析數量
+    pub jobs: usize,
+    /// 每分鐘請求數上限
+    pub requests_per_minute: Option<u32>,
+    /// 每分鐘 token 數上限
+    pub tokens_per_minute: Option<u32>,
+    /// 單次請求中程式碼的估計 token 上限，超過時切塊分析
+    pub max_input_tokens: usize,
+    /// 是否使用 `~/.cache/rs2know` 中的 AI 分析快取
+    pub cache: bool,
+    /// 提示詞、AI 回答與 Markdown 報告使用的語言
+    pub language: String,
+    /// 只分析符合這些 `.gitignore` 樣式的檔案；空的清單表示不限制
+    pub include: Vec<String>,
+    /// 排除符合這些 `.gitignore` 樣式的檔案或目錄
+    pub exclude: Vec<String>,
+    /// 各欄位的來源；未列出的欄位為內建預設值
+    origins: BTreeMap<&'static str, Origin>,
+}
+
+/// 設定值的來源
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Origin {
+    Default,
+    Global(PathBuf),
+    Project(PathBuf),
+    /// 設定檔中的 profile
+    Profile(String, PathBuf),
+    Env,
+    Cli,
+}
+
+/// 設定檔（全局或專案的 `.pj.yml`）的內容。所有欄位都可以省略，
+/// 省略的欄位沿用優先順序較低的來源。
+///
+/// `profiles` 中的每個 profile 使用相同的欄位，以 `--profile`、`RS2KNOW_PROFILE` 或
+/// `default_profile` 選用後覆寫設定檔本身的欄位。
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct ConfigFile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_profile: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<ProviderKind>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_env: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_file: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_command: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jobs: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requests_per_minute: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokens_per_minute: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_input_tokens: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exclude: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, ConfigFile>,
+}
+
+fn default_jobs() -> usize {
+    4
+}
+
+fn default_max_input_tokens() -> usize {
+    12_000
+}
+
+fn default_cache() -> bool {
+    true
+}
+
+fn default_language() -> String {
+    crate::locale::DEFAULT_LANGUAGE.to_string()
+}
+
+fn default_exclude() -> Vec<String> {
+    vec!["vendor/".to_string()]
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
-            api_url: "https://api.openai.com/v1/".to_string(),
+            profile: None,
+            provider: ProviderKind::default(),
+            api_url: ProviderKind::default().default_url().to_string(),
             api_key: String::new(),
-            model: "gpt-4o-mini".to_string(),
+            api_key_env: None,
+            api_key_file: None,
+            api_key_command: None,
+            model: ProviderKind::default().default_model().to_string(),
+            temperature: None,
+            jobs: default_jobs(),
+            requests_per_minute: None,
+            tokens_per_minute: None,
+            max_input_tokens: default_max_input_tokens(),
+            cache: default_cache(),
+            language: default_language(),
+            include: Vec::new(),
+            exclude: default_exclude(),
+            origins: BTreeMap::new(),
         }
     }
 }
 
 impl Config {
-    pub fn load(project_dir: &Path) -> Result<Self> {
-        let config_path = project_dir.join(CONFIG_FILE);
-        if config_path.exists() {
-            let content = fs::read_to_string(&config_path)?;
-            Ok(serde_yaml::from_str(&content)?)
-        } else {
-            // Try to load from global config
-            if let Some(global_config) = Self::load_global()? {
-                Ok(global_config)
-            } else {
-                Ok(Self::default())
+    /// 依序套用各來源；`layers` 的優先順序由低到高
+    fn from_layers(layers: Vec<(ConfigFile, Origin)>) -> Self {
+        let mut config = Self::default();
+        for (layer, origin) in layers {
+            config.apply(layer, origin);
+        }
+        config
+    }
+
+    /// 以一個來源中有設定的欄位覆寫目前的值
+    fn apply(&mut self, layer: ConfigFile, origin: Origin) {
+        let layer = layer.without_empty();
+        if let Some(provider) = layer.provider {
+            // 切換提供者但未指定端點或模型時，改用新提供者的預設值
+            if provider != self.provider && layer.api_url.is_none() {
+                self.api_url = provider.default_url().to_string();
+                self.origins.insert("api_url", Origin::Default);
             }
+            if provider != self.provider && layer.model.is_none() {
+                self.model = provider.default_model().to_string();
+                self.origins.insert("model", Origin::Default);
+            }
+            self.provider = provider;
+            self.origins.insert("provider", origin.clone());
         }
+        // 金鑰的來源以設定了任一來源欄位、優先順序最高的來源為準，
+        // 例如專案配置的 `api_key_command` 會取代全局配置中的 `api_key`
+        let sources = [&layer.api_key, &layer.api_key_env, &layer.api_key_file, &layer.api_key_command];
+        if sources.iter().any(|source| source.is_some()) {
+            for (key, source) in KEY_SOURCES.iter().zip(sources) {
+                match source {
+                    Some(_) => self.origins.insert(key, origin.clone()),
+                    None => self.origins.remove(key),
+                };
+            }
+            self.api_key = layer.api_key.unwrap_or_default();
+            self.api_key_env = layer.api_key_env;
+            self.api_key_file = layer.api_key_file;
+            self.api_key_command = layer.api_key_command;
+        }
+        macro_rules! set {
+            ($($field:ident),*) => {$(
+                if let Some(value) = layer.$field {
+                    self.$field = value.into();
+                    self.origins.insert(stringify!($field), origin.clone());
+                }
+            )*};
+        }
+        set!(api_url, model, temperature, jobs, requests_per_minute, tokens_per_minute, max_input_tokens, cache, language, include, exclude);
     }
 
-    pub fn save(&self, project_dir: &Path) -> Result<()> {
-        let config_path = project_dir.join(CONFIG_FILE);
-        let content = serde_yaml::to_string(self)?;
-        fs::write(&config_path, content)?;
+    /// 欄位目前的值；未設定的選用欄位為 `None`
+    pub fn value(&self, key: &str) -> Option<String> {
+        match key {
+            "profile" => self.profile.clone(),
+            "provider" => Some(self.provider.to_string()),
+            "api_url" => Some(self.api_url.clone()),
+            "api_key" => Some(self.api_key.clone()).filter(|key| !key.is_empty()),
+            "api_key_env" => self.api_key_env.clone(),
+            "api_key_file" => self.api_key_file.clone(),
+            "api_key_command" => self.api_key_command.clone(),
+            "model" => Some(self.model.clone()),
+            "temperature" => self.temperature.map(|v| v.to_string()),
+            "jobs" => Some(self.jobs.to_string()),
+            "requests_per_minute" => self.requests_per_minute.map(|v| v.to_string()),
+            "tokens_per_minute" => self.tokens_per_minute.map(|v| v.to_string()),
+            "max_input_tokens" => Some(self.max_input_tokens.to_string()),
+            "cache" => Some(self.cache.to_string()),
+            "language" => Some(self.language.clone()),
+            "include" => Some(self.include.join(",")).filter(|v| !v.is_empty()),
+            "exclude" => Some(self.exclude.join(",")).filter(|v| !v.is_empty()),
+            _ => None,
+        }
+    }
+
+    /// 欄位的來源
+    pub fn origin(&self, key: &str) -> &Origin {
+        self.origins.get(key).unwrap_or(&Origin::Default)
+    }
+
+    /// 未直接設定 `api_key` 時，依序由 `api_key_env` 指定的環境變數、`api_key_file` 的檔案內容
+    /// 或 `api_key_command` 的輸出取得金鑰。只在需要呼叫 API 時執行，以免 `--skip-ai` 也觸發指令。
+    pub fn resolve_api_key(&mut self) -> Result<()> {
+        if !self.api_key.is_empty() || !self.provider.requires_api_key() {
+            return Ok(());
+        }
+        if let Some(name) = &self.api_key_env {
+            match std::env::var(name) {
+                Ok(key) => self.api_key = key.trim().to_string(),
+                Err(_) => warn!("api_key_env 指定的環境變數 {} 未設定", name),
+            }
+        } else if let Some(file) = &self.api_key_file {
+            let path = expand_home(file);
+            let key = fs::read_to_string(&path)
+                .map_err(|e| anyhow!("無法讀取 API 金鑰檔案：{} - {}", path.display(), e))?;
+            self.api_key = key.trim().to_string();
+        } else if let Some(command) = &self.api_key_command {
+            self.api_key = run_key_command(command)?;
+        }
+        if !self.api_key.is_empty() {
+            debug!("API 金鑰：{}", mask_secret(&self.api_key));
+        }
         Ok(())
     }
 
-    pub fn load_global() -> Result<Option<Self>> {
-        if let Some(home) = dirs::home_dir() {
-            let global_config_path = home.join(GLOBAL_CONFIG_DIR).join(CONFIG_FILE);
-            if global_config_path.exists() {
-                let content = fs::read_to_string(&global_config_path)?;
-                Ok(Some(serde_yaml::from_str(&content)?))
-            } else {
-                Ok(None)
+    /// 提供者需要 API 金鑰但未設定時，AI 分析無法進行
+    pub fn missing_api_key(&self) -> bool {
+        self.provider.requires_api_key() && self.api_key.is_empty()
+    }
+}
+
+impl ConfigFile {
+    /// 讀取設定檔；檔案不存在時回傳 `None`
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(path)?;
+        // 空白的檔案視為沒有設定任何欄位
+        if content.trim().is_empty() {
+            return Ok(Some(Self::default()));
+        }
+        let file: Self = serde_yaml::from_str(&content)
+            .map_err(|e| anyhow!("無法解析配置文件：{} - {}", path.display(), e))?;
+        for (name, profile) in &file.profiles {
+            if profile.default_profile.is_some() || !profile.profiles.is_empty() {
+                return Err(anyhow!("profile {} 中不能設定 default_profile 或 profiles：{}", name, path.display()));
+            }
+        }
+        file.validate().map_err(|e| anyhow!("配置文件 {}：{}", path.display(), e))?;
+        Ok(Some(file))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_yaml::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// 以字串設定欄位的值，依欄位的型別解析並檢查範圍
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        let trimmed = value.trim();
+        if trimmed.is_empty() {
+            return Err(anyhow!("{} 的值不能為空；要移除設定請使用 rs2know config unset {}", key, key));
+        }
+        let text = Some(trimmed.to_string());
+        // 在副本上修改，檢查失敗時保留原本的值
+        let mut updated = self.clone();
+        match key {
+            "default_profile" => updated.default_profile = text,
+            "provider" => {
+                let provider = <ProviderKind as clap::ValueEnum>::from_str(trimmed, true)
+                    .map_err(|_| anyhow!("不支援的服務提供者：{}（可用：openai、anthropic、ollama）", value))?;
+                updated.provider = Some(provider);
             }
-        } else {
-            Ok(None)
+            "api_url" => {
+                let url = reqwest::Url::parse(trimmed).map_err(|e| anyhow!("api_url 不是有效的網址：{}（{}）", value, e))?;
+                if !matches!(url.scheme(), "http" | "https") {
+                    return Err(anyhow!("api_url 必須是 http 或 https 網址：{}", value));
+                }
+                updated.api_url = text;
+            }
+            "api_key" => updated.api_key = text,
+            "api_key_env" => updated.api_key_env = text,
+            "api_key_file" => updated.api_key_file = text,
+            "api_key_command" => updated.api_key_command = text,
+            "model" => updated.model = text,
+            "temperature" => updated.temperature = Some(parse_value(key, trimmed)?),
+            "jobs" => updated.jobs = Some(parse_value(key, trimmed)?),
+            "requests_per_minute" => updated.requests_per_minute = Some(parse_value(key, trimmed)?),
+            "tokens_per_minute" => updated.tokens_per_minute = Some(parse_value(key, trimmed)?),
+            "max_input_tokens" => updated.max_input_tokens = Some(parse_value(key, trimmed)?),
+            "cache" => {
+                let cache = match trimmed.to_lowercase().as_str() {
+                    "true" | "1" | "yes" | "on" => true,
+                    "false" | "0" | "no" | "off" => false,
+                    _ => return Err(anyhow!("cache 的值必須是 true 或 false：{}", value)),
+                };
+                updated.cache = Some(cache);
+            }
+            "language" => {
+                crate::locale::Catalog::load(trimmed)?;
+                updated.language = text;
+            }
+            "include" => updated.include = Some(parse_patterns(trimmed)?),
+            "exclude" => updated.exclude = Some(parse_patterns(trimmed)?),
+            _ => return Err(unknown_key(key)),
         }
+        updated.check(key)?;
+        *self = updated;
+        Ok(())
     }
 
-    pub fn save_global(&self) -> Result<()> {
-        if let Some(home) = dirs::home_dir() {
-            let global_config_dir = home.join(GLOBAL_CONFIG_DIR);
-            fs::create_dir_all(&global_config_dir)?;
-            let global_config_path = global_config_dir.join(CONFIG_FILE);
-            let content = serde_yaml::to_string(self)?;
-            fs::write(&global_config_path, content)?;
-            Ok(())
-        } else {
-            Err(anyhow!("無法找到使用者主目錄"))
+    /// 檢查數值欄位的範圍；`NaN` 等無法比較的值也視為無效
+    fn check(&self, key: &str) -> Result<()> {
+        match key {
+            "temperature" => check_range(key, self.temperature, 0.0, Some(2.0)),
+            "jobs" => check_range(key, self.jobs, 1, Some(256)),
+            "requests_per_minute" => check_range(key, self.requests_per_minute, 1, None),
+            "tokens_per_minute" => check_range(key, self.tokens_per_minute, 1, None),
+            "max_input_tokens" => check_range(key, self.max_input_tokens, 256, None),
+            _ => Ok(()),
+        }
+    }
+
+    /// 檢查檔案（與其中各 profile）所有欄位的範圍
+    fn validate(&self) -> Result<()> {
+        for key in KEYS {
+            self.check(key)?;
+        }
+        for (name, profile) in &self.profiles {
+            profile.validate().map_err(|e| anyhow!("profile {}：{}", name, e))?;
+        }
+        Ok(())
+    }
+
+    /// 移除欄位的設定；回傳欄位原本是否有設定
+    pub fn unset(&mut self, key: &str) -> Result<bool> {
+        let was_set = self.get(key)?.is_some();
+        match key {
+            "default_profile" => self.default_profile = None,
+            "provider" => self.provider = None,
+            "api_url" => self.api_url = None,
+            "api_key" => self.api_key = None,
+            "api_key_env" => self.api_key_env = None,
+            "api_key_file" => self.api_key_file = None,
+            "api_key_command" => self.api_key_command = None,
+            "model" => self.model = None,
+            "temperature" => self.temperature = None,
+            "jobs" => self.jobs = None,
+            "requests_per_minute" => self.requests_per_minute = None,
+            "tokens_per_minute" => self.tokens_per_minute = None,
+            "max_input_tokens" => self.max_input_tokens = None,
+            "cache" => self.cache = None,
+            "language" => self.language = None,
+            "include" => self.include = None,
+            "exclude" => self.exclude = None,
+            _ => return Err(unknown_key(key)),
+        }
+        Ok(was_set)
+    }
+
+    /// 檔案中欄位的值；未設定時為 `None`
+    pub fn get(&self, key: &str) -> Result<Option<String>> {
+        let value = match key {
+            "default_profile" => self.default_profile.clone(),
+            "provider" => self.provider.map(|v| v.to_string()),
+            "api_url" => self.api_url.clone(),
+            "api_key" => self.api_key.clone(),
+            "api_key_env" => self.api_key_env.clone(),
+            "api_key_file" => self.api_key_file.clone(),
+            "api_key_command" => self.api_key_command.clone(),
+            "model" => self.model.clone(),
+            "temperature" => self.temperature.map(|v| v.to_string()),
+            "jobs" => self.jobs.map(|v| v.to_string()),
+            "requests_per_minute" => self.requests_per_minute.map(|v| v.to_string()),
+            "tokens_per_minute" => self.tokens_per_minute.map(|v| v.to_string()),
+            "max_input_tokens" => self.max_input_tokens.map(|v| v.to_string()),
+            "cache" => self.cache.map(|v| v.to_string()),
+            "language" => self.language.clone(),
+            "include" => self.include.as_ref().map(|v| v.join(",")),
+            "exclude" => self.exclude.as_ref().map(|v| v.join(",")),
+            _ => return Err(unknown_key(key)),
+        };
+        Ok(value)
+    }
+
+    /// 檔案中所有有設定的欄位，profile 中的欄位以 `profiles.<名稱>.<欄位>` 表示
+    pub fn entries(&self) -> Vec<(String, String)> {
+        let mut entries: Vec<(String, String)> = std::iter::once("default_profile")
+            .chain(KEYS.iter().copied())
+            .filter_map(|key| Some((key.to_string(), self.get(key).ok()??)))
+            .collect();
+        for (name, profile) in &self.profiles {
+            for (key, value) in profile.entries() {
+                entries.push((format!("profiles.{}.{}", name, key), value));
+            }
+        }
+        entries
+    }
+
+    /// 將空字串視為未設定，例如舊版 `init` 產生的 `api_key: ''` 不會蓋過全局配置的金鑰
+    fn without_empty(self) -> Self {
+        let non_empty = |value: Option<String>| value.filter(|v| !v.trim().is_empty());
+        Self {
+            api_url: non_empty(self.api_url),
+            api_key: non_empty(self.api_key),
+            api_key_env: non_empty(self.api_key_env),
+            api_key_file: non_empty(self.api_key_file),
+            api_key_command: non_empty(self.api_key_command),
+            default_profile: non_empty(self.default_profile),
+            model: non_empty(self.model),
+            language: non_empty(self.language),
+            ..self
+        }
+    }
+
+    /// 由 `RS2KNOW_*` 環境變數讀取設定
+    fn from_env() -> Result<Self> {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    /// 以 `lookup` 取得各欄位對應的環境變數，與 `config set` 使用相同的解析與範圍檢查
+    fn from_vars(lookup: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let mut file = Self::default();
+        for key in KEYS {
+            let name = env_var(key);
+            if let Some(value) = lookup(&name).map(|value| value.trim().to_string()) {
+                if value.is_empty() {
+                    continue;
+                }
+                file.set(key, &value).map_err(|e| anyhow!("環境變數 {}：{}", name, e))?;
+            }
+        }
+        Ok(file)
+    }
+
+    /// 命令列參數中指定的設定
+    fn from_args(args: &crate::Args) -> Self {
+        Self {
+            provider: args.provider,
+            api_url: args.api_url.clone(),
+            api_key: args.api_key.clone(),
+            model: args.model.clone(),
+            jobs: args.jobs,
+            requests_per_minute: args.rpm,
+            tokens_per_minute: args.tpm,
+            max_input_tokens: args.max_input_tokens,
+            cache: args.no_cache.then_some(false),
+            language: args.lang.clone(),
+            include: Some(args.include.clone()).filter(|v| !v.is_empty()),
+            exclude: Some(args.exclude.clone()).filter(|v| !v.is_empty()),
+            ..Self::default()
         }
     }
 }
 
-pub async fn configure_interactive(project_dir: &Path, global: bool) -> Result<()> {
+impl From<&Config> for ConfigFile {
+    fn from(config: &Config) -> Self {
+        Self {
+            provider: Some(config.provider),
+            api_url: Some(config.api_url.clone()),
+            api_key: Some(config.api_key.clone()),
+            api_key_env: config.api_key_env.clone(),
+            api_key_file: config.api_key_file.clone(),
+            api_key_command: config.api_key_command.clone(),
+            model: Some(config.model.clone()),
+            temperature: config.temperature,
+            jobs: Some(config.jobs),
+            requests_per_minute: config.requests_per_minute,
+            tokens_per_minute: config.tokens_per_minute,
+            max_input_tokens: Some(config.max_input_tokens),
+            cache: Some(config.cache),
+            language: Some(config.language.clone()),
+            include: Some(config.include.clone()),
+            exclude: Some(config.exclude.clone()),
+            ..Self::default()
+        }
+    }
+}
+
+impl std::fmt::Display for Origin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Origin::Default => f.write_str("預設值"),
+            Origin::Global(path) => write!(f, "全局配置 {}", path.display()),
+            Origin::Project(path) => write!(f, "專案配置 {}", path.display()),
+            Origin::Profile(name, path) => write!(f, "profile {}（{}）", name, path.display()),
+            Origin::Env => f.write_str("環境變數"),
+            Origin::Cli => f.write_str("命令列參數"),
+        }
+    }
+}
+
+impl std::fmt::Debug for Config {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Config")
+            .field("profile", &self.profile)
+            .field("provider", &self.provider)
+            .field("api_url", &self.api_url)
+            .field("api_key", &mask_secret(&self.api_key))
+            .field("api_key_env", &self.api_key_env)
+            .field("api_key_file", &self.api_key_file)
+            .field("api_key_command", &self.api_key_command)
+            .field("model", &self.model)
+            .field("temperature", &self.temperature)
+            .field("jobs", &self.jobs)
+            .field("requests_per_minute", &self.requests_per_minute)
+            .field("tokens_per_minute", &self.tokens_per_minute)
+            .field("max_input_tokens", &self.max_input_tokens)
+            .field("cache", &self.cache)
+            .field("language", &self.language)
+            .field("include", &self.include)
+            .field("exclude", &self.exclude)
+            .finish()
+    }
+}
+
+impl std::fmt::Debug for ConfigFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConfigFile")
+            .field("default_profile", &self.default_profile)
+            .field("provider", &self.provider)
+            .field("api_url", &self.api_url)
+            .field("api_key", &self.api_key.as_deref().map(mask_secret))
+            .field("api_key_env", &self.api_key_env)
+            .field("api_key_file", &self.api_key_file)
+            .field("api_key_command", &self.api_key_command)
+            .field("model", &self.model)
+            .field("temperature", &self.temperature)
+            .field("jobs", &self.jobs)
+            .field("requests_per_minute", &self.requests_per_minute)
+            .field("tokens_per_minute", &self.tokens_per_minute)
+            .field("max_input_tokens", &self.max_input_tokens)
+            .field("cache", &self.cache)
+            .field("language", &self.language)
+            .field("include", &self.include)
+            .field("exclude", &self.exclude)
+            .field("profiles", &self.profiles)
+            .finish()
+    }
+}
+
+/// 遮蔽金鑰，只保留開頭與結尾少數字元供辨識，例如 `sk-…a1b2`
+pub fn mask_secret(secret: &str) -> String {
+    let chars: Vec<char> = secret.chars().collect();
+    if chars.is_empty() {
+        String::new()
+    } else if chars.len() < 12 {
+        "*".repeat(8)
+    } else {
+        let head: String = chars[..3].iter().collect();
+        let tail: String = chars[chars.len() - 4..].iter().collect();
+        format!("{}…{}", head, tail)
+    }
+}
+
+/// 以系統的 shell 執行指令並以其標準輸出作為金鑰；標準輸入與錯誤輸出沿用終端機，
+/// 讓 `pass`、`gpg` 等工具可以詢問密碼
+fn run_key_command(command: &str) -> Result<String> {
+    let mut process = if cfg!(windows) {
+        let mut process = std::process::Command::new("cmd");
+        process.args(["/C", command]);
+        process
+    } else {
+        let mut process = std::process::Command::new("sh");
+        process.args(["-c", command]);
+        process
+    };
+    let output = process
+        .stderr(std::process::Stdio::inherit())
+        .output()
+        .map_err(|e| anyhow!("無法執行取得 API 金鑰的指令：{} - {}", command, e))?;
+    if !output.status.success() {
+        return Err(anyhow!("取得 API 金鑰的指令執行失敗（{}）：{}", output.status, command));
+    }
+    let key = String::from_utf8(output.stdout)
+        .map_err(|_| anyhow!("取得 API 金鑰的指令輸出不是有效的 UTF-8：{}", command))?;
+    // 只取第一行，`pass show` 等工具會在後面附加其他欄位
+    let key = key.lines().next().unwrap_or_default().trim().to_string();
+    if key.is_empty() {
+        return Err(anyhow!("取得 API 金鑰的指令沒有輸出：{}", command));
+    }
+    Ok(key)
+}
+
+/// 從標準輸入讀取一行，去除前後空白
+fn read_stdin_line() -> Result<String> {
+    let mut line = String::new();
+    std::io::stdin().read_line(&mut line)?;
+    Ok(line.trim().to_string())
+}
+
+/// 命令列 `--api-key` 的值：`-` 表示從標準輸入讀取。直接寫在命令列的金鑰會留在 shell 歷史與
+/// 行程清單中，仍然接受但記錄警告。
+pub fn cli_api_key(value: &str) -> Result<String> {
+    if value == "-" {
+        let key = read_stdin_line()?;
+        if key.is_empty() {
+            return Err(anyhow!("--api-key -：標準輸入沒有提供 API 金鑰"));
+        }
+        return Ok(key);
+    }
+    if !value.is_empty() {
+        warn!(
+            "--api-key 的金鑰會留在 shell 歷史與行程清單中，已不建議使用；請改用 `--api-key -` 從標準輸入讀取，或設定 api_key_env、api_key_file、api_key_command"
+        );
+    }
+    Ok(value.to_string())
+}
+
+/// 展開路徑開頭的 `~/`
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// 依欄位的型別解析數值
+fn parse_value<T>(key: &str, value: &str) -> Result<T>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    value.parse().map_err(|e| anyhow!("{} 的值無效：{}（{}）", key, value, e))
+}
+
+/// 檢查數值是否不小於 `min`，且在有上限時不大於 `max`；未設定的值不檢查
+fn check_range<T>(key: &str, value: Option<T>, min: T, max: Option<T>) -> Result<()>
+where
+    T: PartialOrd + std::fmt::Display,
+{
+    let Some(value) = value else {
+        return Ok(());
+    };
+    // NaN 與任何值比較都不成立，必須另外排除
+    if value.partial_cmp(&min).is_none() {
+        return Err(anyhow!("{} 的值無效：{}", key, value));
+    }
+    match max {
+        Some(max) if value < min || value > max => {
+            Err(anyhow!("{} 的值必須介於 {} 與 {} 之間：{}", key, min, max, value))
+        }
+        None if value < min => Err(anyhow!("{} 的值不能小於 {}：{}", key, min, value)),
+        _ => Ok(()),
+    }
+}
+
+/// 解析以逗號分隔的 `.gitignore` 樣式清單並檢查語法
+fn parse_patterns(value: &str) -> Result<Vec<String>> {
+    let patterns: Vec<String> = value
+        .split(',')
+        .map(str::trim)
+        .filter(|pattern| !pattern.is_empty())
+        .map(str::to_string)
+        .collect();
+    if patterns.is_empty() {
+        return Err(anyhow!("樣式清單不能為空：{}", value));
+    }
+    crate::analysis::build_matcher(Path::new(""), &patterns)?;
+    Ok(patterns)
+}
+
+fn unknown_key(key: &str) -> anyhow::Error {
+    anyhow!("未知的設定欄位：{}（可用的欄位：default_profile、{}）", key, KEYS.join("、"))
+}
+
+/// 欄位對應的環境變數名稱
+pub fn env_var(key: &str) -> String {
+    format!("{}{}", ENV_PREFIX, key.to_uppercase())
+}
+
+pub fn project_config_path(project_dir: &Path) -> PathBuf {
+    project_dir.join(CONFIG_FILE)
+}
+
+pub fn global_config_path() -> Option<PathBuf> {
+    Some(dirs::home_dir()?.join(GLOBAL_CONFIG_DIR).join(CONFIG_FILE))
+}
+
+/// 全局與專案的設定檔，優先順序由低到高；`global_only` 時只讀取全局配置
+fn file_layers(project_dir: &Path, global_only: bool) -> Result<Vec<(ConfigFile, Origin)>> {
+    let mut layers = Vec::new();
+    if let Some(path) = global_config_path() {
+        if let Some(file) = ConfigFile::load(&path)? {
+            layers.push((file, Origin::Global(path)));
+        }
+    }
+    if !global_only {
+        let path = project_config_path(project_dir);
+        if let Some(file) = ConfigFile::load(&path)? {
+            layers.push((file, Origin::Project(path)));
+        }
+    }
+    Ok(layers)
+}
+
+/// 在設定檔之後加上各設定檔中名為 `profile` 的設定，全局配置的 profile 優先順序較低
+fn with_profile(mut layers: Vec<(ConfigFile, Origin)>, profile: &str) -> Result<Vec<(ConfigFile, Origin)>> {
+    let mut profiles = Vec::new();
+    for (file, origin) in &layers {
+        if let (Some(settings), Origin::Global(path) | Origin::Project(path)) = (file.profiles.get(profile), origin) {
+            profiles.push((settings.clone(), Origin::Profile(profile.to_string(), path.clone())));
+        }
+    }
+    if profiles.is_empty() {
+        let available: BTreeSet<&String> = layers.iter().flat_map(|(file, _)| file.profiles.keys()).collect();
+        let available: Vec<&str> = available.into_iter().map(String::as_str).collect();
+        return Err(anyhow!(
+            "找不到 profile：{}（可用的 profile：{}）",
+            profile,
+            if available.is_empty() { "無".to_string() } else { available.join("、") }
+        ));
+    }
+    layers.extend(profiles);
+    Ok(layers)
+}
+
+pub async fn configure_interactive(project_dir: &Path, global: bool, profile: Option<&str>) -> Result<()> {
     let theme = ColorfulTheme::default();
-    let current_config = if global {
-        Config::load_global()?.unwrap_or_default()
+    let (path, origin) = if global {
+        let path = global_config_path().ok_or_else(|| anyhow!("無法找到使用者主目錄"))?;
+        (path.clone(), Origin::Global(path))
     } else {
-        Config::load(project_dir)?
+        let path = project_config_path(project_dir);
+        (path.clone(), Origin::Project(path))
+    };
+    // 只修改正在設定的檔案（指定 profile 時為檔案中的該 profile）；
+    // 提示的預設值為此檔案與優先順序較低的來源合併後的結果
+    let below = if global { Vec::new() } else { file_layers(project_dir, true)? };
+    let mut root = ConfigFile::load(&path)?.unwrap_or_default();
+    let effective = |root: &ConfigFile, section: &ConfigFile| -> Result<Config> {
+        let mut root = root.clone();
+        let mut layers = below.clone();
+        match profile {
+            Some(name) => {
+                root.profiles.insert(name.to_string(), section.clone());
+                layers.push((root, origin.clone()));
+                layers = with_profile(layers, name)?;
+            }
+            None => layers.push((section.clone(), origin.clone())),
+        }
+        Ok(Config::from_layers(layers))
+    };
+    let mut file = match profile {
+        Some(name) => root.profiles.get(name).cloned().unwrap_or_default(),
+        None => root.clone(),
     };
+    let current_config = effective(&root, &file)?;
 
     println!("\n🔧 RS2Know 配置設定");
     println!("==================");
-    if global {
-        println!("正在設定全局配置\n");
-    } else {
-        println!("正在設定專案配置\n");
+    match (global, profile) {
+        (true, Some(name)) => println!("正在設定全局配置的 profile {}\n", name),
+        (false, Some(name)) => println!("正在設定專案配置的 profile {}\n", name),
+        (true, None) => println!("正在設定全局配置\n"),
+        (false, None) => println!("正在設定專案配置\n"),
     }
 
+    // Provider selection
+    let provider_index = Select::with_theme(&theme)
+        .with_prompt("選擇服務提供者")
+        .default(ProviderKind::ALL.iter().position(|p| *p == current_config.provider).unwrap_or(0))
+        .items(ProviderKind::ALL)
+        .interact()?;
+    let provider_kind = ProviderKind::ALL[provider_index];
+    let initial_url = if provider_kind == current_config.provider {
+        current_config.api_url.clone()
+    } else {
+        provider_kind.default_url().to_string()
+    };
+
     // API URL
     let api_url: String = Input::with_theme(&theme)
         .with_prompt("API URL")
-        .with_initial_text(&current_config.api_url)
+        .with_initial_text(&initial_url)
         .interact_text()?;
+    file.provider = Some(provider_kind);
+    file.api_url = Some(api_url);
 
     // API Key
-    let api_key: String = Input::with_theme(&theme)
-        .with_prompt("API Key")
-        .with_initial_text(&current_config.api_key)
-        .interact_text()?;
+    if provider_kind.requires_api_key() {
+        configure_api_key(&theme, &mut file, provider_kind)?;
+    }
 
     // Model selection
-    let models = match crate::openai::get_available_models(&api_url, &api_key).await {
-        Ok(models) => {
-            info!("成功獲取可用模型列表");
-            models
-        }
-        Err(e) => {
-            warn!("無法獲取模型列表：{}，使用預設列表", e);
-            vec!["gpt-4".to_string(), "gpt-4o-mini".to_string(), "gpt-3.5-turbo".to_string()]
-        }
-    };
+    let mut candidate = effective(&root, &file)?;
+    if let Err(e) = candidate.resolve_api_key() {
+        warn!("無法取得 API 金鑰，改用預設的模型清單：{}", e);
+    }
+    let models = provider::available_models(provider::from_config(&candidate).as_ref()).await;
+    info!("已取得可用模型列表");
 
     let default_index = models.iter()
         .position(|m| m == &current_config.model)
@@ -126,23 +865,132 @@ pub async fn configure_interactive(project_dir: &Path, global: bool) -> Result<(
         .items(&models)
         .interact()?;
 
-    let new_config = Config {
-        api_url,
-        api_key,
-        model: models[model_index].clone(),
-    };
+    // Language selection
+    let languages = crate::locale::available();
+    let language_items: Vec<String> = languages
+        .iter()
+        .map(|code| match crate::locale::Catalog::load(code) {
+            Ok(catalog) => format!("{}（{}）", code, catalog.name),
+            Err(_) => code.clone(),
+        })
+        .collect();
+    let language_index = Select::with_theme(&theme)
+        .with_prompt("選擇語言")
+        .default(languages.iter().position(|l| *l == current_config.language).unwrap_or(0))
+        .items(&language_items)
+        .interact()?;
 
+    file.model = Some(models[model_index].clone());
+    file.language = Some(languages[language_index].clone());
+    match profile {
+        Some(name) => {
+            root.profiles.insert(name.to_string(), file);
+        }
+        None => root = file,
+    }
+    root.save(&path)?;
     if global {
-        new_config.save_global()?;
-        info!("已更新全局配置");
+        info!("已更新全局配置：{}", path.display());
     } else {
-        new_config.save(project_dir)?;
-        info!("已更新專案配置");
+        info!("已更新專案配置：{}", path.display());
     }
 
     Ok(())
 }
 
+/// 選擇 API 金鑰的來源。直接輸入的金鑰以密碼的方式輸入，不會顯示在終端機上；
+/// 其他來源只在配置檔中記錄環境變數名稱、檔案路徑或指令。
+fn configure_api_key(theme: &ColorfulTheme, file: &mut ConfigFile, provider: ProviderKind) -> Result<()> {
+    let sources = [
+        "輸入金鑰（儲存在配置檔）",
+        "環境變數",
+        "金鑰檔案",
+        "執行指令（例如 pass show openai）",
+        "沿用其他來源的設定",
+    ];
+    let current = [&file.api_key, &file.api_key_env, &file.api_key_file, &file.api_key_command]
+        .iter()
+        .position(|source| source.is_some())
+        .unwrap_or(sources.len() - 1);
+    let source = Select::with_theme(theme)
+        .with_prompt("API 金鑰來源")
+        .default(current)
+        .items(&sources)
+        .interact()?;
+
+    let previous_key = file.api_key.take();
+    let previous_env = file.api_key_env.take();
+    let previous_file = file.api_key_file.take();
+    let previous_command = file.api_key_command.take();
+    match source {
+        0 => {
+            let prompt = if previous_key.is_some() { "API Key（留空保留目前的金鑰）" } else { "API Key" };
+            let key = Password::with_theme(theme)
+                .with_prompt(prompt)
+                .allow_empty_password(true)
+                .interact()?;
+            file.api_key = Some(key).filter(|key| !key.is_empty()).or(previous_key);
+        }
+        1 => {
+            let default_env = match provider {
+                ProviderKind::Anthropic => "ANTHROPIC_API_KEY",
+                _ => "OPENAI_API_KEY",
+            };
+            let name: String = Input::with_theme(theme)
+                .with_prompt("環境變數名稱")
+                .with_initial_text(previous_env.as_deref().unwrap_or(default_env))
+                .interact_text()?;
+            file.api_key_env = Some(name);
+        }
+        2 => {
+            let path: String = Input::with_theme(theme)
+                .with_prompt("金鑰檔案路徑")
+                .with_initial_text(previous_file.as_deref().unwrap_or_default())
+                .interact_text()?;
+            file.api_key_file = Some(path);
+        }
+        3 => {
+            let command: String = Input::with_theme(theme)
+                .with_prompt("取得金鑰的指令")
+                .with_initial_text(previous_command.as_deref().unwrap_or_default())
+                .interact_text()?;
+            file.api_key_command = Some(command);
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// 列出有效設定；`with_origin` 時附上各欄位的來源
+pub fn show_config(config: &Config, with_origin: bool) {
+    for line in config_lines(config, with_origin) {
+        println!("{}", line);
+    }
+}
+
+/// `config show` 輸出的各行
+fn config_lines(config: &Config, with_origin: bool) -> Vec<String> {
+    std::iter::once(&"profile")
+        .chain(KEYS)
+        .map(|key| {
+            let value = match config.value(key) {
+                Some(value) if *key == "api_key" => mask_secret(&value),
+                Some(value) => value,
+                None => "（未設定）".to_string(),
+            };
+            if with_origin {
+                let origin = match config.origin(key) {
+                    Origin::Env => format!("環境變數 {}", env_var(key)),
+                    origin => origin.to_string(),
+                };
+                format!("{}: {}  # {}", key, value, origin)
+            } else {
+                format!("{}: {}", key, value)
+            }
+        })
+        .collect()
+}
+
 pub fn init_project(project_dir: &Path) -> Result<()> {
     // 檢查是否已經存在配置文件
     let config_path = project_dir.join(CONFIG_FILE);
@@ -150,9 +998,14 @@ pub fn init_project(project_dir: &Path) -> Result<()> {
         return Err(anyhow!("配置文件已存在：{}", config_path.display()));
     }
 
-    // 創建默認配置
-    let config = Config::default();
-    config.save(project_dir)?;
+    // 以註解列出所有欄位的預設值；未填寫的欄位沿用全局配置、環境變數或預設值
+    let defaults = serde_yaml::to_string(&ConfigFile::from(&Config::default()))?;
+    let mut content = String::from("# rs2know 專案配置。未填寫的欄位依序沿用全局配置與內建預設值，\n");
+    content.push_str("# RS2KNOW_* 環境變數與命令列參數則會覆寫此檔案的設定。\n");
+    for line in defaults.lines() {
+        content.push_str(&format!("# {}\n", line));
+    }
+    fs::write(&config_path, content)?;
     info!("已創建配置文件：{}", config_path.display());
 
     // 檢查並更新 .gitignore
@@ -176,21 +1029,549 @@ pub fn init_project(project_dir: &Path) -> Result<()> {
     Ok(())
 }
 
-pub fn update_config(project_dir: &Path, updates: Config, global: bool) -> Result<()> {
-    if global {
-        updates.save_global()?;
-        info!("已更新全局配置");
+/// 讀取專案或全局配置，以 `update` 修改後寫回；指定 `profile` 時修改檔案中的該 profile。
+/// 全局配置不存在時會建立，專案配置則需要先以 `rs2know init` 建立。
+pub fn update_config(
+    project_dir: &Path,
+    global: bool,
+    profile: Option<&str>,
+    update: impl FnOnce(&mut ConfigFile) -> Result<()>,
+) -> Result<PathBuf> {
+    let path = if global {
+        global_config_path().ok_or_else(|| anyhow!("無法找到使用者主目錄"))?
+    } else {
+        let path = project_config_path(project_dir);
+        if !path.exists() {
+            return Err(anyhow!("配置文件不存在，請先執行 rs2know init：{}", path.display()));
+        }
+        path
+    };
+    let mut file = ConfigFile::load(&path)?.unwrap_or_default();
+    match profile {
+        Some(name) => {
+            let section = file.profiles.entry(name.to_string()).or_default();
+            update(section)?;
+            // 移除最後一個欄位後不保留空的 profile
+            if section.entries().is_empty() {
+                file.profiles.remove(name);
+            }
+        }
+        None => update(&mut file)?,
+    }
+    file.save(&path)?;
+    Ok(path)
+}
+
+/// 拆開 `profiles.<名稱>.<欄位>` 形式的欄位名稱；`--profile` 與欄位名稱中的 profile 不能衝突
+fn split_key<'a>(key: &'a str, profile: Option<&'a str>) -> Result<(Option<&'a str>, &'a str)> {
+    let (name, field) = match key.strip_prefix("profiles.").and_then(|rest| rest.rsplit_once('.')) {
+        Some((name, field)) => {
+            if profile.is_some_and(|profile| profile != name) {
+                return Err(anyhow!("欄位 {} 與 --profile {} 指定的 profile 不同", key, profile.unwrap_or_default()));
+            }
+            (Some(name), field)
+        }
+        None => (profile, key),
+    };
+    if name.is_some() && field == "default_profile" {
+        return Err(anyhow!("default_profile 只能設定在配置檔的最上層"));
+    }
+    Ok((name, field))
+}
+
+/// `config set`：設定專案或全局配置中的一個欄位。值為 `-` 時從標準輸入讀取，避免金鑰留在 shell 歷史中
+pub fn set_value(project_dir: &Path, global: bool, profile: Option<&str>, key: &str, value: &str) -> Result<()> {
+    let (profile, field) = split_key(key, profile)?;
+    let value = if value == "-" {
+        read_stdin_line()?
     } else {
-        let config_path = project_dir.join(CONFIG_FILE);
-        if !config_path.exists() {
-            return Err(anyhow!("配置文件不存在，請先執行 rs2know init"));
+        value.to_string()
+    };
+    let path = update_config(project_dir, global, profile, |file| file.set(field, &value))?;
+    if field == "api_key" {
+        warn!("API 金鑰以明文寫入 {}；建議改用 api_key_env、api_key_file 或 api_key_command", path.display());
+    }
+    if field == "default_profile" {
+        let defined = file_layers(project_dir, global)?.iter().any(|(file, _)| file.profiles.contains_key(&value));
+        if !defined {
+            warn!("尚未定義 profile：{}", value);
         }
-        updates.save(project_dir)?;
-        info!("已更新項目配置");
+    }
+    info!("已設定 {}：{}", key, path.display());
+    Ok(())
+}
+
+/// `config unset`：移除專案或全局配置中的一個欄位
+pub fn unset_value(project_dir: &Path, global: bool, profile: Option<&str>, key: &str) -> Result<()> {
+    let (profile, field) = split_key(key, profile)?;
+    let mut was_set = false;
+    let path = update_config(project_dir, global, profile, |file| {
+        was_set = file.unset(field)?;
+        Ok(())
+    })?;
+    if was_set {
+        info!("已移除 {}：{}", key, path.display());
+    } else {
+        warn!("{} 未在 {} 中設定", key, path.display());
+    }
+    Ok(())
+}
+
+/// `config get`：輸出一個欄位的有效值（API 金鑰會被遮蔽）；未設定時回傳錯誤
+pub fn print_value(config: &Config, key: &str) -> Result<()> {
+    if key != "profile" && !KEYS.contains(&key) {
+        return Err(unknown_key(key));
+    }
+    let value = config.value(key).ok_or_else(|| anyhow!("{} 未設定", key))?;
+    let value = if key == "api_key" { mask_secret(&value) } else { value };
+    println!("{}", value);
+    Ok(())
+}
+
+/// `config list`：列出專案或全局配置中有設定的欄位
+pub fn list_values(project_dir: &Path, global: bool) -> Result<()> {
+    let path = if global {
+        global_config_path().ok_or_else(|| anyhow!("無法找到使用者主目錄"))?
+    } else {
+        project_config_path(project_dir)
+    };
+    let file = ConfigFile::load(&path)?.ok_or_else(|| anyhow!("配置文件不存在：{}", path.display()))?;
+    for (key, value) in file.entries() {
+        let value = if key == "api_key" || key.ends_with(".api_key") { mask_secret(&value) } else { value };
+        println!("{}: {}", key, value);
     }
     Ok(())
 }
 
-pub fn get_effective_config(project_dir: &Path) -> Result<Config> {
-    Config::load(project_dir)
+/// 合併內建預設值、全局配置、專案配置、環境變數與命令列參數的有效設定
+pub fn get_effective_config(project_dir: &Path, args: &crate::Args) -> Result<Config> {
+    resolve(
+        file_layers(project_dir, false)?,
+        args.profile.clone(),
+        std::env::var(env_var("profile")).ok(),
+        ConfigFile::from_env()?,
+        ConfigFile::from_args(args),
+    )
+}
+
+/// 在設定檔之上加入選用的 profile、環境變數與命令列參數。
+///
+/// 選用的 profile：命令列參數（`cli_profile`）、環境變數（`env_profile`），接著是專案與全局配置的
+/// `default_profile`。
+fn resolve(
+    mut layers: Vec<(ConfigFile, Origin)>,
+    cli_profile: Option<String>,
+    env_profile: Option<String>,
+    env: ConfigFile,
+    cli: ConfigFile,
+) -> Result<Config> {
+    let profile = cli_profile
+        .map(|name| (name, Origin::Cli))
+        .or_else(|| Some((env_profile.filter(|name| !name.is_empty())?, Origin::Env)))
+        .or_else(|| {
+            layers.iter().rev().find_map(|(file, origin)| {
+                let name = file.default_profile.clone().filter(|name| !name.is_empty())?;
+                Some((name, origin.clone()))
+            })
+        });
+    if let Some((name, _)) = &profile {
+        layers = with_profile(layers, name)?;
+    }
+    layers.push((env, Origin::Env));
+    layers.push((cli, Origin::Cli));
+    let mut config = Config::from_layers(layers);
+    if let Some((name, origin)) = profile {
+        debug!("使用 profile：{}", name);
+        config.profile = Some(name);
+        config.origins.insert("profile", origin);
+    }
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn global() -> Origin {
+        Origin::Global(PathBuf::from("global.yml"))
+    }
+
+    #[test]
+    fn switching_provider_resets_model() {
+        let config = Config::from_layers(vec![
+            (ConfigFile { model: Some("gpt-4o".into()), ..Default::default() }, global()),
+            (ConfigFile { provider: Some(ProviderKind::Anthropic), ..Default::default() }, Origin::Cli),
+        ]);
+        assert_eq!(config.model, ProviderKind::Anthropic.default_model());
+        assert_eq!(config.api_url, ProviderKind::Anthropic.default_url());
+        assert_eq!(config.origin("model"), &Origin::Default);
+        assert_eq!(config.origin("provider"), &Origin::Cli);
+
+        // 同一層指定模型時沿用指定的模型
+        let config = Config::from_layers(vec![(
+            ConfigFile { provider: Some(ProviderKind::Ollama), model: Some("qwen2.5-coder".into()), ..Default::default() },
+            global(),
+        )]);
+        assert_eq!(config.model, "qwen2.5-coder");
+        assert_eq!(config.origin("model"), &global());
+    }
+
+    fn project() -> Origin {
+        Origin::Project(PathBuf::from("project.yml"))
+    }
+
+    /// 內建預設值 < 全局配置 < 專案配置 < 環境變數 < 命令列參數
+    fn stack(global_file: ConfigFile, project_file: ConfigFile, env: ConfigFile, cli: ConfigFile) -> Config {
+        Config::from_layers(vec![(global_file, global()), (project_file, project()), (env, Origin::Env), (cli, Origin::Cli)])
+    }
+
+    #[test]
+    fn layers_merge_per_field() {
+        let config = stack(
+            ConfigFile {
+                model: Some("global-model".into()),
+                jobs: Some(2),
+                temperature: Some(0.1),
+                language: Some("en".into()),
+                ..Default::default()
+            },
+            ConfigFile {
+                model: Some("project-model".into()),
+                jobs: Some(3),
+                // 空字串視為未設定，不會蓋過全局配置
+                language: Some("".into()),
+                api_url: Some("  ".into()),
+                ..Default::default()
+            },
+            ConfigFile { jobs: Some(5), ..Default::default() },
+            ConfigFile { cache: Some(false), ..Default::default() },
+        );
+        let expected: &[(&str, &str, Origin)] = &[
+            ("provider", "openai", Origin::Default),
+            ("api_url", ProviderKind::OpenAi.default_url(), Origin::Default),
+            ("model", "project-model", project()),
+            ("temperature", "0.1", global()),
+            ("jobs", "5", Origin::Env),
+            ("cache", "false", Origin::Cli),
+            ("language", "en", global()),
+            ("max_input_tokens", "12000", Origin::Default),
+            ("exclude", "vendor/", Origin::Default),
+        ];
+        for (key, value, origin) in expected {
+            assert_eq!(config.value(key).as_deref(), Some(*value), "{}", key);
+            assert_eq!(config.origin(key), origin, "{}", key);
+        }
+        assert_eq!(config.value("requests_per_minute"), None);
+    }
+
+    #[test]
+    fn higher_key_source_replaces_lower() {
+        let cases: &[(ConfigFile, ConfigFile, &str, Origin)] = &[
+            // 專案配置的指令取代全局配置的金鑰
+            (
+                ConfigFile { api_key: Some("sk-global".into()), ..Default::default() },
+                ConfigFile { api_key_command: Some("pass show openai".into()), ..Default::default() },
+                "api_key_command",
+                project(),
+            ),
+            // 專案配置的空字串金鑰不取代全局配置的環境變數
+            (
+                ConfigFile { api_key_env: Some("OPENAI_API_KEY".into()), ..Default::default() },
+                ConfigFile { api_key: Some("".into()), ..Default::default() },
+                "api_key_env",
+                global(),
+            ),
+            (
+                ConfigFile { api_key_file: Some("~/.key".into()), ..Default::default() },
+                ConfigFile { api_key_env: Some("KEY".into()), ..Default::default() },
+                "api_key_env",
+                project(),
+            ),
+        ];
+        for (lower, higher, source, origin) in cases {
+            let config = stack(lower.clone(), higher.clone(), ConfigFile::default(), ConfigFile::default());
+            for key in KEY_SOURCES {
+                if key == source {
+                    assert!(config.value(key).is_some(), "{}", key);
+                    assert_eq!(config.origin(key), origin, "{}", key);
+                } else {
+                    assert_eq!(config.value(key), None, "{} 應被 {} 取代", key, source);
+                    assert_eq!(config.origin(key), &Origin::Default, "{}", key);
+                }
+            }
+        }
+
+        // 命令列的金鑰取代配置檔中的所有來源
+        let config = stack(
+            ConfigFile { api_key_command: Some("pass show openai".into()), ..Default::default() },
+            ConfigFile::default(),
+            ConfigFile::default(),
+            ConfigFile { api_key: Some("sk-cli".into()), ..Default::default() },
+        );
+        assert_eq!(config.api_key, "sk-cli");
+        assert_eq!(config.api_key_command, None);
+        assert_eq!(config.origin("api_key"), &Origin::Cli);
+    }
+
+    #[test]
+    fn show_origin() {
+        let config = stack(
+            ConfigFile { model: Some("gpt-4o".into()), ..Default::default() },
+            ConfigFile { api_key: Some("sk-project-0123456789".into()), ..Default::default() },
+            ConfigFile { jobs: Some(8), ..Default::default() },
+            ConfigFile::default(),
+        );
+        let lines = config_lines(&config, true);
+        assert_eq!(lines.len(), KEYS.len() + 1);
+        assert!(lines.contains(&"profile: （未設定）  # 預設值".to_string()));
+        assert!(lines.contains(&"model: gpt-4o  # 全局配置 global.yml".to_string()));
+        assert!(lines.contains(&"api_key: sk-…6789  # 專案配置 project.yml".to_string()));
+        assert!(lines.contains(&"jobs: 8  # 環境變數 RS2KNOW_JOBS".to_string()));
+        assert!(config_lines(&config, false).contains(&"jobs: 8".to_string()));
+    }
+
+    #[test]
+    fn debug_masks_api_key() {
+        let secret = "sk-proj-0123456789abcdef";
+        let mut file = ConfigFile { api_key: Some(secret.into()), ..Default::default() };
+        file.profiles.insert("cloud".into(), file.clone());
+        let config = Config::from_layers(vec![(file.clone(), global())]);
+        for debug in [format!("{:?}", file), format!("{:#?}", file), format!("{:?}", config)] {
+            assert!(!debug.contains(secret), "{}", debug);
+            assert!(debug.contains(&mask_secret(secret)), "{}", debug);
+        }
+    }
+
+    #[test]
+    fn set_get_unset_round_trip() {
+        let mut file = ConfigFile::default();
+        for (key, value, stored) in [
+            ("provider", "Anthropic", "anthropic"),
+            ("api_url", " http://localhost:8080/v1 ", "http://localhost:8080/v1"),
+            ("model", "gpt-4o", "gpt-4o"),
+            ("temperature", "0.5", "0.5"),
+            ("jobs", "8", "8"),
+            ("requests_per_minute", "60", "60"),
+            ("max_input_tokens", "256", "256"),
+            ("cache", "off", "false"),
+            ("language", "en", "en"),
+            ("exclude", "vendor/, target/,", "vendor/,target/"),
+            ("default_profile", "local", "local"),
+        ] {
+            file.set(key, value).unwrap();
+            assert_eq!(file.get(key).unwrap().as_deref(), Some(stored), "{}", key);
+            assert!(file.unset(key).unwrap(), "{}", key);
+            assert_eq!(file.get(key).unwrap(), None, "{}", key);
+            assert!(!file.unset(key).unwrap(), "{}", key);
+        }
+        assert!(file.entries().is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        let mut file = ConfigFile::default();
+        file.set("temperature", "1").unwrap();
+        for (key, value) in [
+            ("temperature", "NaN"),
+            ("temperature", "inf"),
+            ("temperature", "2.5"),
+            ("temperature", "-0.1"),
+            ("jobs", "0"),
+            ("jobs", "257"),
+            ("requests_per_minute", "0"),
+            ("max_input_tokens", "255"),
+            ("max_input_tokens", "many"),
+            ("cache", "maybe"),
+            ("provider", "gemini"),
+            ("api_url", "ftp://example.com"),
+            ("language", "xx"),
+            ("model", "  "),
+        ] {
+            assert!(file.set(key, value).is_err(), "{} = {}", key, value);
+        }
+        // 失敗的設定不會改變原本的值
+        assert_eq!(file.get("temperature").unwrap().as_deref(), Some("1"));
+
+        for key in ["nope", "profile", "profiles"] {
+            assert!(file.set(key, "1").is_err(), "{}", key);
+            assert!(file.get(key).is_err(), "{}", key);
+            assert!(file.unset(key).is_err(), "{}", key);
+        }
+    }
+
+    #[test]
+    fn profile_keys() {
+        assert_eq!(split_key("model", None).unwrap(), (None, "model"));
+        assert_eq!(split_key("model", Some("local")).unwrap(), (Some("local"), "model"));
+        assert_eq!(split_key("profiles.local.model", None).unwrap(), (Some("local"), "model"));
+        assert_eq!(split_key("profiles.local.model", Some("local")).unwrap(), (Some("local"), "model"));
+        // profile 名稱可以包含點
+        assert_eq!(split_key("profiles.v1.2.jobs", None).unwrap(), (Some("v1.2"), "jobs"));
+        assert!(split_key("profiles.local.model", Some("cloud")).is_err());
+        assert!(split_key("profiles.local.default_profile", None).is_err());
+        assert!(split_key("default_profile", Some("local")).is_err());
+
+        let mut file = ConfigFile::default();
+        file.profiles.entry("local".into()).or_default().set("jobs", "2").unwrap();
+        file.set("jobs", "8").unwrap();
+        assert_eq!(
+            file.entries(),
+            [("jobs".to_string(), "8".to_string()), ("profiles.local.jobs".to_string(), "2".to_string())]
+        );
+    }
+
+    #[test]
+    fn env_checks_ranges() {
+        let vars = |pairs: &'static [(&'static str, &'static str)]| {
+            move |name: &str| pairs.iter().find(|(key, _)| *key == name).map(|(_, value)| value.to_string())
+        };
+        let file = ConfigFile::from_vars(vars(&[("RS2KNOW_JOBS", " 2 "), ("RS2KNOW_MODEL", "")])).unwrap();
+        assert_eq!(file.jobs, Some(2));
+        assert_eq!(file.model, None);
+        for pairs in [&[("RS2KNOW_TEMPERATURE", "NaN")], &[("RS2KNOW_JOBS", "0")], &[("RS2KNOW_TOKENS_PER_MINUTE", "0")]] {
+            let error = ConfigFile::from_vars(vars(pairs)).unwrap_err().to_string();
+            assert!(error.contains(pairs[0].0), "{}", error);
+        }
+    }
+
+    #[test]
+    fn load_checks_ranges() {
+        let dir = std::env::temp_dir().join(format!("rs2know-config-ranges-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(CONFIG_FILE);
+        for (content, valid) in [
+            ("temperature: 0.7\njobs: 2\n", true),
+            ("temperature: .nan\n", false),
+            ("temperature: 3\n", false),
+            ("jobs: 0\n", false),
+            ("tokens_per_minute: 0\n", false),
+            ("max_input_tokens: 10\n", false),
+            ("profiles:\n  fast:\n    jobs: 1000\n", false),
+        ] {
+            fs::write(&path, content).unwrap();
+            assert_eq!(ConfigFile::load(&path).is_ok(), valid, "{}", content);
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn with_profiles(file: ConfigFile, profiles: &[(&str, ConfigFile)]) -> ConfigFile {
+        let profiles = profiles.iter().map(|(name, profile)| (name.to_string(), profile.clone())).collect();
+        ConfigFile { profiles, ..file }
+    }
+
+    fn profile_origin(name: &str, origin: Origin) -> Origin {
+        match origin {
+            Origin::Global(path) | Origin::Project(path) => Origin::Profile(name.to_string(), path),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn profile_selection_order() {
+        let layers = || {
+            vec![
+                (
+                    with_profiles(
+                        ConfigFile { default_profile: Some("global".into()), ..Default::default() },
+                        &[("global", ConfigFile { jobs: Some(2), ..Default::default() })],
+                    ),
+                    global(),
+                ),
+                (
+                    with_profiles(
+                        ConfigFile { default_profile: Some("project".into()), ..Default::default() },
+                        &[
+                            ("project", ConfigFile { jobs: Some(3), ..Default::default() }),
+                            ("fast", ConfigFile { jobs: Some(8), ..Default::default() }),
+                        ],
+                    ),
+                    project(),
+                ),
+            ]
+        };
+        let select = |cli: Option<&str>, env: Option<&str>| {
+            resolve(layers(), cli.map(Into::into), env.map(Into::into), ConfigFile::default(), ConfigFile::default())
+                .unwrap()
+        };
+        // (--profile, RS2KNOW_PROFILE, 選用的 profile, 來源, jobs)
+        type Case<'a> = (Option<&'a str>, Option<&'a str>, &'a str, Origin, &'a str);
+        let cases: &[Case] = &[
+            // 專案配置的 default_profile 優先於全局配置
+            (None, None, "project", project(), "3"),
+            (None, Some(""), "project", project(), "3"),
+            (None, Some("global"), "global", Origin::Env, "2"),
+            (Some("fast"), Some("global"), "fast", Origin::Cli, "8"),
+        ];
+        for (cli, env, name, origin, jobs) in cases {
+            let config = select(*cli, *env);
+            assert_eq!(config.value("profile").as_deref(), Some(*name));
+            assert_eq!(config.origin("profile"), origin, "{}", name);
+            assert_eq!(config.value("jobs").as_deref(), Some(*jobs), "{}", name);
+        }
+        assert_eq!(select(Some("global"), None).origin("jobs"), &profile_origin("global", global()));
+
+        // 沒有 default_profile 時不使用 profile
+        let config = resolve(vec![(ConfigFile::default(), global())], None, None, ConfigFile::default(), ConfigFile::default())
+            .unwrap();
+        assert_eq!(config.value("profile"), None);
+    }
+
+    #[test]
+    fn unknown_profile_is_an_error() {
+        let layers = vec![(
+            with_profiles(ConfigFile::default(), &[("fast", ConfigFile::default()), ("slow", ConfigFile::default())]),
+            global(),
+        )];
+        let error = resolve(layers.clone(), Some("missing".into()), None, ConfigFile::default(), ConfigFile::default())
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("找不到 profile：missing"), "{}", error);
+        assert!(error.contains("fast、slow"), "{}", error);
+
+        // default_profile 指向不存在的 profile 也是錯誤
+        let layers = vec![(ConfigFile { default_profile: Some("gone".into()), ..Default::default() }, project())];
+        let error = resolve(layers, None, None, ConfigFile::default(), ConfigFile::default()).unwrap_err().to_string();
+        assert!(error.contains("找不到 profile：gone（可用的 profile：無）"), "{}", error);
+    }
+
+    #[test]
+    fn profile_in_both_files_merges_per_field() {
+        let layers = vec![
+            (
+                with_profiles(
+                    ConfigFile { model: Some("global-model".into()), ..Default::default() },
+                    &[("ci", ConfigFile { jobs: Some(2), temperature: Some(0.5), cache: Some(false), ..Default::default() })],
+                ),
+                global(),
+            ),
+            (
+                with_profiles(
+                    ConfigFile { model: Some("project-model".into()), jobs: Some(16), ..Default::default() },
+                    &[("ci", ConfigFile { jobs: Some(4), language: Some("en".into()), ..Default::default() })],
+                ),
+                project(),
+            ),
+        ];
+        let config = resolve(
+            layers,
+            Some("ci".into()),
+            None,
+            ConfigFile { temperature: Some(0.0), ..Default::default() },
+            ConfigFile::default(),
+        )
+        .unwrap();
+        let expected: &[(&str, &str, Origin)] = &[
+            // 專案配置的 profile > 全局配置的 profile > 專案配置本身
+            ("jobs", "4", profile_origin("ci", project())),
+            ("language", "en", profile_origin("ci", project())),
+            ("cache", "false", profile_origin("ci", global())),
+            ("model", "project-model", project()),
+            // 環境變數仍優先於 profile
+            ("temperature", "0", Origin::Env),
+        ];
+        for (key, value, origin) in expected {
+            assert_eq!(config.value(key).as_deref(), Some(*value), "{}", key);
+            assert_eq!(config.origin(key), origin, "{}", key);
+        }
+    }
 }
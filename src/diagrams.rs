@@ -0,0 +1,803 @@
+//! 由報告在本地產生架構圖：模組樹、模組之間的 `use` 依賴、crate 之間的依賴（`Cargo.toml`
+//! 的 `[dependencies]` 與跨 crate 的 `use`），以及型別實作 trait 的關係。圖可以輸出為 Mermaid（嵌入 Markdown 報告）、SVG（嵌入 HTML 報告）或
+//! Graphviz DOT 檔案。
+//!
+//! 模組由檔案路徑推得（`src/a/mod.rs` 與 `src/a.rs` 都是模組 `a`，`src/main.rs`、`src/lib.rs`
+//! 是 crate 根），不屬於 `src/` 的檔案（例如 `build.rs`、`tests/`）與 `src/bin/` 中的執行檔不列入。
+
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::Path;
+use tracing::info;
+use crate::models::{ItemKind, ProjectAnalysis};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Shape {
+    Box,
+    Round,
+    Hexagon,
+}
+
+struct Node {
+    label: String,
+    shape: Shape,
+    /// 專案外部的項目，以虛線表示
+    external: bool,
+}
+
+/// 有向圖；節點以字串為鍵，輸出時依鍵排序以保持穩定
+pub struct Graph {
+    direction: &'static str,
+    nodes: BTreeMap<String, Node>,
+    edges: BTreeSet<(String, String)>,
+}
+
+impl Graph {
+    fn new(direction: &'static str) -> Self {
+        Self { direction, nodes: BTreeMap::new(), edges: BTreeSet::new() }
+    }
+
+    fn node(&mut self, key: &str, label: &str, shape: Shape, external: bool) {
+        self.nodes.entry(key.to_string()).or_insert_with(|| Node {
+            label: label.to_string(),
+            shape,
+            external,
+        });
+    }
+
+    fn edge(&mut self, from: &str, to: &str) {
+        if from != to {
+            self.edges.insert((from.to_string(), to.to_string()));
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+
+    /// Mermaid 的節點 ID 只能使用英數字，因此依排序後的位置編號
+    fn ids(&self) -> BTreeMap<&str, String> {
+        self.nodes.keys().enumerate().map(|(i, key)| (key.as_str(), format!("n{}", i))).collect()
+    }
+
+    pub fn to_mermaid(&self) -> String {
+        let ids = self.ids();
+        let mut out = format!("graph {}\n", self.direction);
+        for (key, node) in &self.nodes {
+            let label = node.label.replace('"', "#quot;");
+            let (open, close) = match node.shape {
+                Shape::Box => ("[\"", "\"]"),
+                Shape::Round => ("(\"", "\")"),
+                Shape::Hexagon => ("{{\"", "\"}}"),
+            };
+            out.push_str(&format!("    {}{}{}{}\n", ids[key.as_str()], open, label, close));
+        }
+        for (from, to) in &self.edges {
+            out.push_str(&format!("    {} --> {}\n", ids[from.as_str()], ids[to.as_str()]));
+        }
+        let external: Vec<&str> = self.nodes
+            .iter()
+            .filter(|(_, node)| node.external)
+            .map(|(key, _)| ids[key.as_str()].as_str())
+            .collect();
+        if !external.is_empty() {
+            out.push_str("    classDef external stroke-dasharray: 5 5\n");
+            out.push_str(&format!("    class {} external\n", external.join(",")));
+        }
+        out
+    }
+
+    pub fn to_dot(&self, name: &str) -> String {
+        let quote = |text: &str| format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""));
+        let rankdir = if self.direction == "TD" { "TB" } else { self.direction };
+        let mut out = format!("digraph {} {{\n    rankdir={};\n    node [fontname=\"sans-serif\"];\n", name, rankdir);
+        for (key, node) in &self.nodes {
+            let shape = match node.shape {
+                Shape::Box => "box",
+                Shape::Round => "ellipse",
+                Shape::Hexagon => "hexagon",
+            };
+            let style = if node.external { ", style=dashed" } else { "" };
+            out.push_str(&format!("    {} [label={}, shape={}{}];\n", quote(key), quote(&node.label), shape, style));
+        }
+        for (from, to) in &self.edges {
+            out.push_str(&format!("    {} -> {};\n", quote(from), quote(to)));
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// 以簡單的分層配置繪成 SVG，不需要 Mermaid 或 Graphviz，可以直接嵌入離線的 HTML 報告。
+    /// `name` 用於區分同一頁面中各圖的箭頭標記。
+    pub fn to_svg(&self, name: &str) -> String {
+        let layout = Layout::new(self);
+        let mut out = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" class=\"diagram\" width=\"{w:.0}\" height=\"{h:.0}\" viewBox=\"0 0 {w:.0} {h:.0}\" font-family=\"sans-serif\" font-size=\"{font}\">\n",
+            w = layout.width,
+            h = layout.height,
+            font = SVG_FONT_SIZE,
+        );
+        out.push_str(&format!(
+            "<defs><marker id=\"arrow-{}\" viewBox=\"0 0 10 10\" refX=\"10\" refY=\"5\" markerWidth=\"8\" markerHeight=\"8\" orient=\"auto-start-reverse\"><path d=\"M0,0 L10,5 L0,10 z\" fill=\"#57606a\"/></marker></defs>\n",
+            name
+        ));
+        for (from, to) in &self.edges {
+            let (a, b) = (&layout.boxes[from.as_str()], &layout.boxes[to.as_str()]);
+            let (x1, y1) = a.boundary(b.cx, b.cy);
+            let (x2, y2) = b.boundary(a.cx, a.cy);
+            out.push_str(&format!(
+                "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"#57606a\" marker-end=\"url(#arrow-{})\"/>\n",
+                x1, y1, x2, y2, name
+            ));
+        }
+        for (key, node) in &self.nodes {
+            let b = &layout.boxes[key.as_str()];
+            let dash = if node.external { " stroke-dasharray=\"5 5\"" } else { "" };
+            let (left, top, right, bottom) = (b.cx - b.w / 2.0, b.cy - b.h / 2.0, b.cx + b.w / 2.0, b.cy + b.h / 2.0);
+            let shape = match node.shape {
+                Shape::Box => format!("<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\"", left, top, b.w, b.h),
+                Shape::Round => format!(
+                    "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" rx=\"{:.1}\"",
+                    left, top, b.w, b.h, b.h / 2.0
+                ),
+                Shape::Hexagon => {
+                    let inset = b.h / 2.0;
+                    format!(
+                        "<polygon points=\"{:.1},{:.1} {:.1},{:.1} {:.1},{:.1} {:.1},{:.1} {:.1},{:.1} {:.1},{:.1}\"",
+                        left, b.cy, left + inset, top, right - inset, top, right, b.cy, right - inset, bottom, left + inset, bottom
+                    )
+                }
+            };
+            out.push_str(&format!("{} fill=\"#f6f8fa\" stroke=\"#57606a\"{}/>\n", shape, dash));
+            out.push_str(&format!(
+                "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\" dominant-baseline=\"central\" fill=\"#1f2328\">{}</text>\n",
+                b.cx, b.cy, escape_xml(&node.label)
+            ));
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+}
+
+const SVG_FONT_SIZE: f64 = 13.0;
+const NODE_HEIGHT: f64 = 30.0;
+const LAYER_GAP: f64 = 50.0;
+const NODE_GAP: f64 = 20.0;
+const MARGIN: f64 = 10.0;
+
+/// 節點在 SVG 中的位置與大小
+struct NodeBox {
+    cx: f64,
+    cy: f64,
+    w: f64,
+    h: f64,
+}
+
+impl NodeBox {
+    /// 由中心往 `(x, y)` 的方向與外框的交點，作為邊的端點
+    fn boundary(&self, x: f64, y: f64) -> (f64, f64) {
+        let (dx, dy) = (x - self.cx, y - self.cy);
+        if dx == 0.0 && dy == 0.0 {
+            return (self.cx, self.cy);
+        }
+        let scale_x = if dx == 0.0 { f64::INFINITY } else { self.w / 2.0 / dx.abs() };
+        let scale_y = if dy == 0.0 { f64::INFINITY } else { self.h / 2.0 / dy.abs() };
+        let scale = scale_x.min(scale_y);
+        (self.cx + dx * scale, self.cy + dy * scale)
+    }
+}
+
+/// 分層配置：節點依最長路徑分層，層內以相鄰層的平均位置排序以減少交叉
+struct Layout<'g> {
+    boxes: BTreeMap<&'g str, NodeBox>,
+    width: f64,
+    height: f64,
+}
+
+impl<'g> Layout<'g> {
+    fn new(graph: &'g Graph) -> Self {
+        let keys: Vec<&str> = graph.nodes.keys().map(String::as_str).collect();
+        let index: BTreeMap<&str, usize> = keys.iter().enumerate().map(|(i, k)| (*k, i)).collect();
+        let edges: Vec<(usize, usize)> = graph
+            .edges
+            .iter()
+            .map(|(from, to)| (index[from.as_str()], index[to.as_str()]))
+            .collect();
+        let forward = acyclic_edges(keys.len(), &edges);
+
+        // 最長路徑分層；邊依起點的拓撲順序處理
+        let mut layer = vec![0usize; keys.len()];
+        let mut indegree = vec![0usize; keys.len()];
+        for &(_, to) in &forward {
+            indegree[to] += 1;
+        }
+        let mut queue: Vec<usize> = (0..keys.len()).filter(|&n| indegree[n] == 0).collect();
+        while let Some(node) = queue.pop() {
+            for &(from, to) in forward.iter().filter(|(from, _)| *from == node) {
+                layer[to] = layer[to].max(layer[from] + 1);
+                indegree[to] -= 1;
+                if indegree[to] == 0 {
+                    queue.push(to);
+                }
+            }
+        }
+        let depth = layer.iter().max().map_or(0, |max| max + 1);
+        let mut layers: Vec<Vec<usize>> = vec![Vec::new(); depth];
+        for (node, &l) in layer.iter().enumerate() {
+            layers[l].push(node);
+        }
+
+        // 重心排序：往下依前一層的鄰居、往上依後一層的鄰居
+        let mut position = vec![0.0; keys.len()];
+        let update = |layers: &Vec<Vec<usize>>, position: &mut Vec<f64>| {
+            for nodes in layers {
+                for (i, &node) in nodes.iter().enumerate() {
+                    position[node] = i as f64;
+                }
+            }
+        };
+        update(&layers, &mut position);
+        for sweep in 0..4 {
+            let downward = sweep % 2 == 0;
+            let order: Vec<usize> = if downward { (1..depth).collect() } else { (0..depth.saturating_sub(1)).rev().collect() };
+            for l in order {
+                let neighbor_layer = if downward { l - 1 } else { l + 1 };
+                let barycenter = |node: usize| {
+                    let neighbors: Vec<f64> = edges
+                        .iter()
+                        .filter_map(|&(from, to)| match (from == node, to == node) {
+                            (true, _) => Some(to),
+                            (_, true) => Some(from),
+                            _ => None,
+                        })
+                        .filter(|&other| layer[other] == neighbor_layer)
+                        .map(|other| position[other])
+                        .collect();
+                    if neighbors.is_empty() {
+                        position[node]
+                    } else {
+                        neighbors.iter().sum::<f64>() / neighbors.len() as f64
+                    }
+                };
+                let mut keyed: Vec<(f64, usize)> = layers[l].iter().map(|&n| (barycenter(n), n)).collect();
+                keyed.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+                layers[l] = keyed.into_iter().map(|(_, n)| n).collect();
+                update(&layers, &mut position);
+            }
+        }
+
+        let widths: Vec<f64> = keys
+            .iter()
+            .map(|key| {
+                let node = &graph.nodes[*key];
+                let extra = if node.shape == Shape::Hexagon { NODE_HEIGHT } else { 0.0 };
+                text_width(&node.label) + 24.0 + extra
+            })
+            .collect();
+        let vertical = graph.direction == "TD";
+        // 每層沿著層內方向的總長度，用於置中
+        let extent = |nodes: &Vec<usize>| -> f64 {
+            let sizes = nodes.iter().map(|&n| if vertical { widths[n] } else { NODE_HEIGHT });
+            sizes.sum::<f64>() + NODE_GAP * nodes.len().saturating_sub(1) as f64
+        };
+        let span = layers.iter().map(extent).fold(0.0, f64::max);
+        let layer_sizes: Vec<f64> = layers
+            .iter()
+            .map(|nodes| nodes.iter().map(|&n| if vertical { NODE_HEIGHT } else { widths[n] }).fold(0.0, f64::max))
+            .collect();
+
+        let mut boxes = BTreeMap::new();
+        let mut offset = MARGIN;
+        for (l, nodes) in layers.iter().enumerate() {
+            let mut along = MARGIN + (span - extent(nodes)) / 2.0;
+            for &node in nodes {
+                let (w, h) = (widths[node], NODE_HEIGHT);
+                let (cx, cy) = if vertical {
+                    (along + w / 2.0, offset + layer_sizes[l] / 2.0)
+                } else {
+                    (offset + layer_sizes[l] / 2.0, along + h / 2.0)
+                };
+                along += if vertical { w } else { h } + NODE_GAP;
+                boxes.insert(keys[node], NodeBox { cx, cy, w, h });
+            }
+            offset += layer_sizes[l] + LAYER_GAP;
+        }
+        let across = offset - LAYER_GAP + MARGIN;
+        let (width, height) = if vertical { (span + 2.0 * MARGIN, across) } else { (across, span + 2.0 * MARGIN) };
+        Self { boxes, width: width.max(0.0), height: height.max(0.0) }
+    }
+}
+
+/// 去除形成循環的邊（深度優先搜尋中指回搜尋路徑上節點的邊），其餘的邊用於分層
+fn acyclic_edges(count: usize, edges: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    // 0：未拜訪，1：在搜尋路徑上，2：已完成
+    let mut state = vec![0u8; count];
+    let mut forward = Vec::new();
+    for start in 0..count {
+        if state[start] != 0 {
+            continue;
+        }
+        let mut stack = vec![(start, 0usize)];
+        state[start] = 1;
+        while let Some(&mut (node, ref mut next)) = stack.last_mut() {
+            let outgoing: Vec<usize> = edges.iter().filter(|(from, _)| *from == node).map(|(_, to)| *to).collect();
+            if let Some(&to) = outgoing.get(*next) {
+                *next += 1;
+                match state[to] {
+                    0 => {
+                        forward.push((node, to));
+                        state[to] = 1;
+                        stack.push((to, 0));
+                    }
+                    2 => forward.push((node, to)),
+                    _ => {}
+                }
+            } else {
+                state[node] = 2;
+                stack.pop();
+            }
+        }
+    }
+    forward
+}
+
+/// 估計文字寬度：全形字元約為半形的兩倍
+fn text_width(text: &str) -> f64 {
+    text.chars()
+        .map(|c| if c.len_utf8() > 2 { SVG_FONT_SIZE } else { SVG_FONT_SIZE * 0.6 })
+        .sum()
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// 報告的所有架構圖
+pub struct Graphs {
+    pub module_tree: Graph,
+    pub module_dependencies: Graph,
+    pub crate_dependencies: Graph,
+    pub implementations: Graph,
+}
+
+/// 嵌入報告範本的 Mermaid 原始碼；沒有任何邊的圖為空字串
+#[derive(Serialize)]
+pub struct Diagrams {
+    pub module_tree: String,
+    pub module_dependencies: String,
+    pub crate_dependencies: String,
+    pub implementations: String,
+}
+
+impl Graphs {
+    fn all(&self) -> [(&'static str, &Graph); 4] {
+        [
+            ("module_tree", &self.module_tree),
+            ("module_dependencies", &self.module_dependencies),
+            ("crate_dependencies", &self.crate_dependencies),
+            ("implementations", &self.implementations),
+        ]
+    }
+
+    /// 各圖的 SVG，沒有任何邊的圖不列出
+    pub fn svg(&self) -> Vec<(&'static str, String)> {
+        self.all()
+            .into_iter()
+            .filter(|(_, graph)| !graph.is_empty())
+            .map(|(name, graph)| (name, graph.to_svg(name)))
+            .collect()
+    }
+
+    pub fn mermaid(&self) -> Diagrams {
+        let render = |graph: &Graph| if graph.is_empty() { String::new() } else { graph.to_mermaid() };
+        Diagrams {
+            module_tree: render(&self.module_tree),
+            module_dependencies: render(&self.module_dependencies),
+            crate_dependencies: render(&self.crate_dependencies),
+            implementations: render(&self.implementations),
+        }
+    }
+
+    /// 將非空的圖寫成 `<名稱>.dot`
+    pub fn write_dot(&self, dir: &Path) -> Result<()> {
+        std::fs::create_dir_all(dir)?;
+        for (name, graph) in self.all() {
+            if !graph.is_empty() {
+                std::fs::write(dir.join(format!("{}.dot", name)), graph.to_dot(name))?;
+            }
+        }
+        info!("Graphviz DOT 檔案已寫入 {}", dir.display());
+        Ok(())
+    }
+}
+
+/// 一個 crate 與其中的模組
+struct CrateModules {
+    name: String,
+    /// `use` 路徑中使用的名稱（`-` 換成 `_`）
+    ident: String,
+    path: String,
+    /// 模組路徑，crate 根為空的 Vec
+    modules: HashSet<Vec<String>>,
+}
+
+impl CrateModules {
+    /// 模組的顯示名稱與圖中的鍵
+    fn key(&self, module: &[String]) -> String {
+        std::iter::once(self.name.as_str())
+            .chain(module.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join("::")
+    }
+
+    /// `base` 之下 `rest` 所指向最深的已知模組
+    fn resolve(&self, base: &[String], rest: &[&str]) -> Vec<String> {
+        for len in (0..=rest.len()).rev() {
+            let mut candidate = base.to_vec();
+            candidate.extend(rest[..len].iter().map(|s| s.to_string()));
+            if self.modules.contains(&candidate) {
+                return candidate;
+            }
+        }
+        base.to_vec()
+    }
+}
+
+/// 檔案所屬 crate 的索引與模組路徑
+fn module_of(file_path: &str, crates: &[CrateModules]) -> Option<(usize, Vec<String>)> {
+    let (index, krate) = crates
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| c.path == "." || file_path.starts_with(&format!("{}/", c.path)))
+        .max_by_key(|(_, c)| c.path.len())?;
+    let relative = if krate.path == "." {
+        file_path
+    } else {
+        &file_path[krate.path.len() + 1..]
+    };
+    let rest = relative.strip_prefix("src/")?.strip_suffix(".rs")?;
+    let mut segments: Vec<String> = rest.split('/').map(str::to_string).collect();
+    if segments.first().is_some_and(|s| s == "bin") {
+        return None;
+    }
+    if segments.last().is_some_and(|s| s == "mod") {
+        segments.pop();
+    }
+    if segments.len() == 1 && (segments[0] == "main" || segments[0] == "lib") {
+        segments.clear();
+    }
+    Some((index, segments))
+}
+
+/// 去除型別或 trait 名稱的路徑、泛型與參照，例如 `&'a std::fmt::Display<T>` -> `Display`
+fn base_name(name: &str) -> &str {
+    let name = name.split('<').next().unwrap_or(name);
+    let name = name.rsplit("::").next().unwrap_or(name);
+    name.rsplit([' ', '&']).next().unwrap_or(name).trim()
+}
+
+/// 由報告計算所有架構圖
+pub fn build(report: &ProjectAnalysis) -> Graphs {
+    let mut crates: Vec<CrateModules> = report
+        .crates
+        .iter()
+        .map(|c| CrateModules {
+            name: c.name.clone(),
+            ident: c.name.replace('-', "_"),
+            path: c.path.clone(),
+            modules: HashSet::new(),
+        })
+        .collect();
+    if crates.is_empty() {
+        // 沒有 crate 資訊的舊報告視為單一 crate
+        crates.push(CrateModules {
+            name: "crate".to_string(),
+            ident: "crate".to_string(),
+            path: ".".to_string(),
+            modules: HashSet::new(),
+        });
+    }
+
+    // 每個檔案的模組，以及模組樹中所有的上層模組
+    let files: Vec<_> = report
+        .file_analyses
+        .iter()
+        .filter_map(|analysis| Some((analysis, module_of(&analysis.file_path, &crates)?)))
+        .collect();
+    for (_, (index, module)) in &files {
+        for len in 0..=module.len() {
+            crates[*index].modules.insert(module[..len].to_vec());
+        }
+    }
+
+    let mut module_tree = Graph::new("TD");
+    for krate in &crates {
+        let mut modules: Vec<&Vec<String>> = krate.modules.iter().collect();
+        modules.sort();
+        for module in modules {
+            let key = krate.key(module);
+            let label = module.last().unwrap_or(&krate.name);
+            let shape = if module.is_empty() { Shape::Round } else { Shape::Box };
+            module_tree.node(&key, label, shape, false);
+            if let Some((_, parent)) = module.split_last() {
+                module_tree.edge(&krate.key(parent), &key);
+            }
+        }
+    }
+
+    let mut module_dependencies = Graph::new("LR");
+    let mut crate_dependencies = Graph::new("LR");
+    for (analysis, (index, module)) in &files {
+        let krate = &crates[*index];
+        let from = krate.key(module);
+        for import in analysis.imports.iter().flatten() {
+            let segments: Vec<&str> = import.trim_start_matches("::").split("::").collect();
+            let target = match segments[0] {
+                "crate" => Some((*index, krate.resolve(&[], &segments[1..]))),
+                "self" => Some((*index, krate.resolve(module, &segments[1..]))),
+                "super" => {
+                    let supers = segments.iter().take_while(|s| **s == "super").count();
+                    let base = &module[..module.len().saturating_sub(supers)];
+                    Some((*index, krate.resolve(base, &segments[supers..])))
+                }
+                first => {
+                    let mut child = module.clone();
+                    child.push(first.to_string());
+                    if krate.modules.contains(&child) {
+                        Some((*index, krate.resolve(module, &segments)))
+                    } else {
+                        crates
+                            .iter()
+                            .position(|c| c.ident == first)
+                            .map(|other| (other, crates[other].resolve(&[], &segments[1..])))
+                    }
+                }
+            };
+            let Some((target_crate, target)) = target else {
+                continue;
+            };
+            let target_key = crates[target_crate].key(&target);
+            module_dependencies.node(&from, &from, Shape::Box, false);
+            module_dependencies.node(&target_key, &target_key, Shape::Box, false);
+            module_dependencies.edge(&from, &target_key);
+            if target_crate != *index {
+                let other = &crates[target_crate];
+                crate_dependencies.node(&krate.name, &krate.name, Shape::Round, false);
+                crate_dependencies.node(&other.name, &other.name, Shape::Round, false);
+                crate_dependencies.edge(&krate.name, &other.name);
+            }
+        }
+    }
+
+    // `Cargo.toml` 中的依賴：專案中的 crate 以實線表示，外部套件以虛線表示
+    let local: HashSet<&str> = report.crates.iter().map(|c| c.name.as_str()).collect();
+    for krate in &report.crates {
+        for dependency in &krate.dependencies {
+            crate_dependencies.node(&krate.name, &krate.name, Shape::Round, false);
+            crate_dependencies.node(dependency, dependency, Shape::Round, !local.contains(dependency.as_str()));
+            crate_dependencies.edge(&krate.name, dependency);
+        }
+    }
+
+    let items = report.file_analyses.iter().flat_map(|a| &a.items);
+    let types: HashSet<&str> = items
+        .clone()
+        .filter(|i| matches!(i.kind, ItemKind::Struct | ItemKind::Enum | ItemKind::Union))
+        .map(|i| i.name.as_str())
+        .collect();
+    let traits: HashSet<&str> = items
+        .clone()
+        .filter(|i| i.kind == ItemKind::Trait)
+        .map(|i| i.name.as_str())
+        .collect();
+    let mut implementations = Graph::new("LR");
+    for item in items.filter(|i| i.kind == ItemKind::Impl) {
+        let Some(trait_name) = &item.trait_name else {
+            continue;
+        };
+        let (type_name, trait_name) = (base_name(&item.name), base_name(trait_name));
+        let (local_type, local_trait) = (types.contains(type_name), traits.contains(trait_name));
+        if !local_type && !local_trait {
+            continue;
+        }
+        let type_key = format!("type:{}", type_name);
+        let trait_key = format!("trait:{}", trait_name);
+        implementations.node(&type_key, type_name, Shape::Box, !local_type);
+        implementations.node(&trait_key, trait_name, Shape::Hexagon, !local_trait);
+        implementations.edge(&type_key, &trait_key);
+    }
+
+    Graphs { module_tree, module_dependencies, crate_dependencies, implementations }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::tests::temp_project;
+    use crate::analysis::{FileFilter, analyze_files, discover_rust_files};
+
+    const WORKSPACE: &[(&str, &str)] = &[
+        ("Cargo.toml", include_str!("../tests/fixtures/diagrams/Cargo.toml")),
+        ("build.rs", include_str!("../tests/fixtures/diagrams/build.rs")),
+        ("src/main.rs", include_str!("../tests/fixtures/diagrams/src/main.rs")),
+        ("src/bin/tool.rs", include_str!("../tests/fixtures/diagrams/src/bin/tool.rs")),
+        ("src/net/mod.rs", include_str!("../tests/fixtures/diagrams/src/net/mod.rs")),
+        ("src/net/client.rs", include_str!("../tests/fixtures/diagrams/src/net/client.rs")),
+        ("src/net/server.rs", include_str!("../tests/fixtures/diagrams/src/net/server.rs")),
+        ("src/util/mod.rs", include_str!("../tests/fixtures/diagrams/src/util/mod.rs")),
+        ("src/util/fmt.rs", include_str!("../tests/fixtures/diagrams/src/util/fmt.rs")),
+        ("core/Cargo.toml", include_str!("../tests/fixtures/diagrams/core/Cargo.toml")),
+        ("core/src/lib.rs", include_str!("../tests/fixtures/diagrams/core/src/lib.rs")),
+    ];
+
+    /// 以本地分析（不呼叫 AI）建立 fixture 專案的報告並計算架構圖
+    async fn fixture_graphs() -> Graphs {
+        let dir = temp_project("diagrams", WORKSPACE);
+        let filter = FileFilter::new(&dir, &[], &[]).unwrap();
+        let mut report = crate::markdown::tests::sample_report();
+        report.file_analyses = analyze_files(&dir, &discover_rust_files(&dir, &filter), None, 1).await;
+        report.crates = crate::summary::summarize_project(&dir, &filter, &report.file_analyses, None, None, 1).await.crates;
+        std::fs::remove_dir_all(&dir).unwrap();
+        build(&report)
+    }
+
+    fn edges(graph: &Graph) -> Vec<(&str, &str)> {
+        graph.edges.iter().map(|(from, to)| (from.as_str(), to.as_str())).collect()
+    }
+
+    fn external(graph: &Graph) -> Vec<&str> {
+        graph.nodes.iter().filter(|(_, node)| node.external).map(|(key, _)| key.as_str()).collect()
+    }
+
+    fn graph(direction: &'static str, edges: &[(&str, &str)]) -> Graph {
+        let mut graph = Graph::new(direction);
+        for (from, to) in edges {
+            graph.node(from, from, Shape::Box, false);
+            graph.node(to, to, Shape::Hexagon, true);
+            graph.edge(from, to);
+        }
+        graph
+    }
+
+    fn assert_layout(graph: &Graph) {
+        let layout = Layout::new(graph);
+        assert_eq!(layout.boxes.len(), graph.nodes.len());
+        let boxes: Vec<&NodeBox> = layout.boxes.values().collect();
+        for (i, a) in boxes.iter().enumerate() {
+            assert!(a.cx - a.w / 2.0 >= 0.0 && a.cx + a.w / 2.0 <= layout.width);
+            assert!(a.cy - a.h / 2.0 >= 0.0 && a.cy + a.h / 2.0 <= layout.height);
+            for b in &boxes[i + 1..] {
+                let overlap = (a.cx - b.cx).abs() < (a.w + b.w) / 2.0 && (a.cy - b.cy).abs() < (a.h + b.h) / 2.0;
+                assert!(!overlap, "節點重疊");
+            }
+        }
+    }
+
+    #[test]
+    fn layers_follow_edges() {
+        let tree = graph("TD", &[("a", "a::b"), ("a", "a::c"), ("a::b", "a::b::d")]);
+        assert_layout(&tree);
+        let layout = Layout::new(&tree);
+        assert!(layout.boxes["a"].cy < layout.boxes["a::b"].cy);
+        assert!(layout.boxes["a::b"].cy < layout.boxes["a::b::d"].cy);
+        assert_eq!(layout.boxes["a::b"].cy, layout.boxes["a::c"].cy);
+
+        let chain = graph("LR", &[("x", "y"), ("y", "z")]);
+        let layout = Layout::new(&chain);
+        assert!(layout.boxes["x"].cx < layout.boxes["y"].cx && layout.boxes["y"].cx < layout.boxes["z"].cx);
+    }
+
+    #[test]
+    fn cycles_are_laid_out() {
+        let cycle = graph("LR", &[("a", "b"), ("b", "c"), ("c", "a"), ("c", "d")]);
+        assert_layout(&cycle);
+        let svg = cycle.to_svg("cycle");
+        assert_eq!(svg.matches("<line").count(), 4);
+        assert!(svg.contains("url(#arrow-cycle)"));
+        assert!(svg.contains("stroke-dasharray"));
+    }
+
+    #[test]
+    fn escapes_labels() {
+        let svg = graph("LR", &[("Vec<T>", "A & B")]).to_svg("g");
+        assert!(svg.contains("Vec&lt;T&gt;") && svg.contains("A &amp; B"));
+    }
+
+    #[tokio::test]
+    async fn module_tree_from_file_paths() {
+        let graphs = fixture_graphs().await;
+        // `src/bin/` 與 `build.rs` 不列入；沒有子模組的 crate 只有節點
+        assert_eq!(
+            edges(&graphs.module_tree),
+            [
+                ("app", "app::net"),
+                ("app", "app::util"),
+                ("app::net", "app::net::client"),
+                ("app::net", "app::net::server"),
+                ("app::util", "app::util::fmt"),
+            ]
+        );
+        assert!(graphs.module_tree.nodes.contains_key("core-lib"));
+        assert_eq!(graphs.module_tree.nodes["app::net::client"].label, "client");
+        assert!(graphs.module_tree.nodes["app"].shape == Shape::Round);
+    }
+
+    #[tokio::test]
+    async fn module_dependencies_from_use() {
+        let graphs = fixture_graphs().await;
+        // `crate::`、`self::`、`super::super::`、群組匯入、相對於目前模組的路徑與其他 crate；
+        // `std` 等外部套件不列入
+        assert_eq!(
+            edges(&graphs.module_dependencies),
+            [
+                ("app", "app::net::client"),
+                ("app", "app::util"),
+                ("app", "app::util::fmt"),
+                ("app", "core-lib"),
+                ("app::net", "app::net::server"),
+                ("app::net::client", "app::net"),
+                ("app::net::client", "app::net::server"),
+                ("app::net::client", "app::util"),
+                ("app::net::client", "core-lib"),
+                ("app::util::fmt", "app::util"),
+            ]
+        );
+        assert!(external(&graphs.module_dependencies).is_empty());
+    }
+
+    #[tokio::test]
+    async fn crate_dependencies_from_manifests() {
+        let graphs = fixture_graphs().await;
+        // 以 `package` 改名的依賴使用原本的套件名稱，`[target.*.dependencies]` 也列入
+        assert_eq!(
+            edges(&graphs.crate_dependencies),
+            [("app", "core-lib"), ("app", "serde"), ("core-lib", "libc")]
+        );
+        assert_eq!(external(&graphs.crate_dependencies), ["libc", "serde"]);
+    }
+
+    #[tokio::test]
+    async fn implementations_of_local_types_and_traits() {
+        let graphs = fixture_graphs().await;
+        assert_eq!(
+            edges(&graphs.implementations),
+            [
+                ("type:Client", "trait:Backend"),
+                ("type:Client", "trait:Display"),
+                ("type:Client", "trait:Handler"),
+                ("type:Server", "trait:Clone"),
+                ("type:Server", "trait:Handler"),
+            ]
+        );
+        assert_eq!(external(&graphs.implementations), ["trait:Clone", "trait:Display"]);
+        assert!(graphs.implementations.nodes["trait:Handler"].shape == Shape::Hexagon);
+
+        let mermaid = graphs.mermaid().implementations;
+        assert!(mermaid.starts_with("graph LR\n"));
+        assert_eq!(mermaid.matches(" --> ").count(), 5);
+        assert!(mermaid.contains("{{\"Handler\"}}"));
+        assert!(mermaid.contains("class n1,n2 external"));
+    }
+
+    #[test]
+    fn acyclic_edges_break_cycles() {
+        // 0 -> 1 -> 2 -> 0，2 -> 3，3 -> 1
+        let edges = [(0, 1), (1, 2), (2, 0), (2, 3), (3, 1)];
+        let forward = acyclic_edges(4, &edges);
+        assert_eq!(forward, [(0, 1), (1, 2), (2, 3)]);
+        // 保留的邊沒有循環：可以逐一移除沒有入邊的節點
+        let mut remaining: Vec<usize> = (0..4).collect();
+        while let Some(pos) = remaining
+            .iter()
+            .position(|&n| !forward.iter().any(|&(from, to)| to == n && remaining.contains(&from)))
+        {
+            remaining.remove(pos);
+        }
+        assert!(remaining.is_empty(), "保留的邊形成循環");
+
+        // 沒有循環時保留所有的邊
+        let dag = [(0, 1), (0, 2), (1, 2)];
+        assert_eq!(acyclic_edges(3, &dag).len(), dag.len());
+    }
+}
@@ -0,0 +1,304 @@
+//! Git 相關的輔助函式：取得目前的提交，以及某個版本之後變更的檔案。
+//!
+//! 專案目錄可以是倉庫中的子目錄。Git 回傳的路徑相對於倉庫的工作目錄，透過
+//! [`ProjectRepo`] 轉換為相對於專案目錄的路徑，專案目錄之外的變更一律略過。
+
+use anyhow::{Result, anyhow};
+use git2::{Delta, DiffDelta, DiffFindOptions, Oid, Repository, StatusOptions};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// 包含專案目錄的 Git 倉庫
+pub struct ProjectRepo {
+    pub repo: Repository,
+    /// 專案目錄相對於工作目錄的路徑；專案位於工作目錄的根目錄時為空
+    prefix: PathBuf,
+}
+
+impl ProjectRepo {
+    /// 由專案目錄向上尋找 Git 倉庫
+    pub fn discover(project_path: &Path) -> Result<Self> {
+        let repo = Repository::discover(project_path)
+            .map_err(|e| anyhow!("找不到包含 {} 的 Git 倉庫：{}", project_path.display(), e))?;
+        let workdir = repo
+            .workdir()
+            .ok_or_else(|| anyhow!("Git 倉庫沒有工作目錄：{}", repo.path().display()))?
+            .canonicalize()?;
+        let prefix = project_path
+            .canonicalize()?
+            .strip_prefix(&workdir)
+            .map(Path::to_path_buf)
+            .map_err(|_| anyhow!("{} 不在 Git 工作目錄 {} 之中", project_path.display(), workdir.display()))?;
+        Ok(Self { repo, prefix })
+    }
+
+    /// 相對於工作目錄的路徑轉為相對於專案目錄的路徑；不在專案目錄之中時回傳 `None`
+    fn to_project(&self, path: &Path) -> Option<String> {
+        let relative = path.strip_prefix(&self.prefix).ok()?;
+        Some(relative.to_string_lossy().replace('\\', "/"))
+    }
+
+    /// 相對於專案目錄的路徑轉為相對於工作目錄的路徑
+    fn to_workdir(&self, path: &str) -> PathBuf {
+        self.prefix.join(path)
+    }
+
+    /// 只列出專案目錄之中的狀態
+    fn status_options(&self) -> StatusOptions {
+        let mut options = StatusOptions::new();
+        options.include_untracked(true);
+        if !self.prefix.as_os_str().is_empty() {
+            options.pathspec(&self.prefix);
+        }
+        options
+    }
+}
+
+/// 某個版本之後的檔案變更，包含已提交與尚未提交的部分
+pub struct Changes {
+    /// 有變更的路徑；重新命名的檔案是否需要重新分析另外依內容判斷
+    pub changed: HashSet<String>,
+    /// 舊路徑 -> 新路徑
+    pub renamed: HashMap<String, String>,
+    /// 比較重新命名前內容時使用的 tree
+    base_tree: Option<Oid>,
+}
+
+impl Changes {
+    fn add_delta(&mut self, repo: &ProjectRepo, delta: &DiffDelta<'_>) {
+        let old = delta.old_file().path().and_then(|p| repo.to_project(p));
+        let new = delta.new_file().path().and_then(|p| repo.to_project(p));
+        match (delta.status(), old, new) {
+            (Delta::Renamed, Some(old), Some(new)) => {
+                self.renamed.insert(old, new);
+            }
+            (_, old, new) => self.changed.extend(old.into_iter().chain(new)),
+        }
+    }
+
+    /// 將連續的重新命名（a -> b -> c）收斂為 a -> c
+    fn resolve_renames(&mut self) {
+        let renamed = self.renamed.clone();
+        for new in self.renamed.values_mut() {
+            let mut seen = HashSet::new();
+            while let Some(next) = renamed.get(new.as_str()) {
+                if !seen.insert(next.clone()) {
+                    break;
+                }
+                *new = next.clone();
+            }
+        }
+    }
+
+    /// 檔案內容是否與基準版本中 `old_path`（相對於專案目錄）的內容相同
+    pub fn same_as_base(&self, repo: &ProjectRepo, old_path: &str, path: &Path) -> bool {
+        let base_blob = self.base_tree
+            .ok_or_else(|| git2::Error::from_str("沒有基準版本"))
+            .and_then(|id| repo.repo.find_tree(id))
+            .and_then(|tree| tree.get_path(&repo.to_workdir(old_path)))
+            .and_then(|entry| entry.to_object(&repo.repo))
+            .and_then(|object| object.peel_to_blob());
+        match (base_blob, std::fs::read(path)) {
+            (Ok(blob), Ok(content)) => blob.content() == content.as_slice(),
+            _ => false,
+        }
+    }
+}
+
+/// 目前 HEAD 指向的提交；不在 Git 倉庫之中或尚無提交時回傳 `None`
+pub fn head_commit(project_path: &Path) -> Option<String> {
+    let repo = ProjectRepo::discover(project_path).ok()?;
+    let commit = repo.repo.head().ok()?.peel_to_commit().ok()?;
+    Some(commit.id().to_string())
+}
+
+/// 專案目錄中是否有尚未提交的變更（包含未追蹤的檔案）；不在 Git 倉庫之中時回傳 `false`
+pub fn is_dirty(project_path: &Path) -> bool {
+    let Ok(repo) = ProjectRepo::discover(project_path) else {
+        return false;
+    };
+    let mut options = repo.status_options();
+    repo.repo.statuses(Some(&mut options)).is_ok_and(|statuses| !statuses.is_empty())
+}
+
+/// 取得 `since` 到 HEAD 之間已提交的變更，加上工作目錄中尚未提交的變更。
+/// 未指定 `since` 時只包含尚未提交的變更。路徑皆相對於專案目錄。
+pub fn changes_since(project: &ProjectRepo, since: Option<&str>) -> Result<Changes> {
+    let repo = &project.repo;
+    let head_tree = repo.head().and_then(|head| head.peel_to_tree()).ok();
+    let base_tree = match since {
+        Some(rev) => Some(
+            repo.revparse_single(rev)
+                .and_then(|object| object.peel_to_tree())
+                .map_err(|e| anyhow!("無法解析版本 {}：{}", rev, e))?,
+        ),
+        None => None,
+    };
+
+    let mut changes = Changes {
+        changed: HashSet::new(),
+        renamed: HashMap::new(),
+        base_tree: base_tree.as_ref().or(head_tree.as_ref()).map(|tree| tree.id()),
+    };
+
+    // 已提交的變更：基準版本與 HEAD 的 tree 比較
+    if let Some(base_tree) = &base_tree {
+        let mut diff = repo.diff_tree_to_tree(Some(base_tree), head_tree.as_ref(), None)?;
+        diff.find_similar(Some(DiffFindOptions::new().renames(true)))?;
+        for delta in diff.deltas() {
+            changes.add_delta(project, &delta);
+        }
+    }
+
+    // 尚未提交的變更
+    let mut options = project.status_options();
+    options
+        .recurse_untracked_dirs(true)
+        .renames_head_to_index(true)
+        .renames_index_to_workdir(true);
+    let statuses = repo.statuses(Some(&mut options))?;
+    for entry in statuses.iter() {
+        for delta in [entry.head_to_index(), entry.index_to_workdir()].into_iter().flatten() {
+            changes.add_delta(project, &delta);
+        }
+    }
+
+    changes.resolve_renames();
+    Ok(changes)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use git2::{IndexAddOption, Signature};
+
+    /// 在暫存目錄初始化 Git 倉庫並寫入檔案（尚未提交）
+    pub(crate) fn init_repo(name: &str, files: &[(&str, &str)]) -> (PathBuf, Repository) {
+        let dir = crate::analysis::tests::temp_project(name, files);
+        let repo = Repository::init(&dir).unwrap();
+        (dir, repo)
+    }
+
+    /// 提交工作目錄中的所有變更（包含新增與刪除），回傳提交的 id
+    pub(crate) fn commit_all(repo: &Repository, message: &str) -> String {
+        let mut index = repo.index().unwrap();
+        index.add_all(["*"], IndexAddOption::DEFAULT, None).unwrap();
+        index.update_all(["*"], None).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("rs2know", "rs2know@example.com").unwrap();
+        let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &signature, &signature, message, &tree, &parents).unwrap().to_string()
+    }
+
+    fn sorted(set: &HashSet<String>) -> Vec<&str> {
+        let mut paths: Vec<&str> = set.iter().map(String::as_str).collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn head_commit_and_dirty_state() {
+        let (dir, repo) = init_repo("git-head", &[("src/lib.rs", "pub fn a() {}\n")]);
+        // 尚無提交
+        assert_eq!(head_commit(&dir), None);
+        assert!(is_dirty(&dir));
+
+        let first = commit_all(&repo, "first");
+        assert_eq!(head_commit(&dir).as_deref(), Some(first.as_str()));
+        assert!(!is_dirty(&dir));
+
+        std::fs::write(dir.join("src/new.rs"), "pub fn b() {}\n").unwrap();
+        assert!(is_dirty(&dir));
+        let second = commit_all(&repo, "second");
+        assert_ne!(first, second);
+        assert_eq!(head_commit(&dir).as_deref(), Some(second.as_str()));
+        assert!(!is_dirty(&dir));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn outside_a_repository() {
+        let dir = crate::analysis::tests::temp_project("git-none", &[("src/lib.rs", "")]);
+        if Repository::discover(&dir).is_err() {
+            assert_eq!(head_commit(&dir), None);
+            assert!(!is_dirty(&dir));
+            assert!(ProjectRepo::discover(&dir).is_err());
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn dirty_state_is_limited_to_the_project() {
+        let (root, repo) = init_repo("git-dirty-subdir", &[("proj/src/lib.rs", ""), ("other/x.rs", "")]);
+        commit_all(&repo, "base");
+        std::fs::write(root.join("other/x.rs"), "pub fn x() {}\n").unwrap();
+        assert!(!is_dirty(&root.join("proj")));
+        assert!(is_dirty(&root));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn changes_since_includes_commits_and_working_tree() {
+        let (dir, repo) = init_repo("git-changes", &[
+            ("src/lib.rs", "pub fn a() {}\n"),
+            ("src/reverted.rs", "pub fn r() {}\n"),
+            ("src/stable.rs", "pub fn s() {}\n"),
+        ]);
+        let base = commit_all(&repo, "base");
+
+        // 提交之後的變更
+        std::fs::write(dir.join("src/lib.rs"), "pub fn a() -> u32 { 1 }\n").unwrap();
+        std::fs::write(dir.join("src/committed.rs"), "pub fn c() {}\n").unwrap();
+        std::fs::write(dir.join("src/reverted.rs"), "pub fn r() -> u32 { 1 }\n").unwrap();
+        commit_all(&repo, "change");
+        // 再改回基準版本的內容
+        std::fs::write(dir.join("src/reverted.rs"), "pub fn r() {}\n").unwrap();
+        commit_all(&repo, "revert");
+        // 尚未提交的變更
+        std::fs::write(dir.join("src/untracked.rs"), "pub fn u() {}\n").unwrap();
+        std::fs::write(dir.join("src/stable.rs"), "pub fn s() -> u32 { 1 }\n").unwrap();
+        std::fs::write(dir.join("src/stable.rs"), "pub fn s() {}\n").unwrap();
+
+        let project = ProjectRepo::discover(&dir).unwrap();
+        let changes = changes_since(&project, Some(&base)).unwrap();
+        assert_eq!(sorted(&changes.changed), ["src/committed.rs", "src/lib.rs", "src/untracked.rs"]);
+        assert!(changes.renamed.is_empty());
+        assert!(changes.same_as_base(&project, "src/reverted.rs", &dir.join("src/reverted.rs")));
+        assert!(!changes.same_as_base(&project, "src/lib.rs", &dir.join("src/lib.rs")));
+        assert!(!changes.same_as_base(&project, "src/committed.rs", &dir.join("src/committed.rs")));
+
+        // 未指定版本時只包含尚未提交的變更
+        let uncommitted = changes_since(&project, None).unwrap();
+        assert_eq!(sorted(&uncommitted.changed), ["src/untracked.rs"]);
+
+        assert!(changes_since(&project, Some("no-such-rev")).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn renames_are_chained_and_compared_with_the_base() {
+        let content: String = (0..20).map(|i| format!("pub fn f{}() {{}}\n", i)).collect();
+        let (dir, repo) = init_repo("git-renames", &[("src/a.rs", &content)]);
+        let base = commit_all(&repo, "base");
+        std::fs::rename(dir.join("src/a.rs"), dir.join("src/b.rs")).unwrap();
+        commit_all(&repo, "a -> b");
+        // 尚未提交的重新命名，並修改一行
+        std::fs::remove_file(dir.join("src/b.rs")).unwrap();
+        std::fs::write(dir.join("src/c.rs"), content.replace("f0()", "g0()")).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_all(["*"], IndexAddOption::DEFAULT, None).unwrap();
+        index.update_all(["*"], None).unwrap();
+        index.write().unwrap();
+
+        let project = ProjectRepo::discover(&dir).unwrap();
+        let changes = changes_since(&project, Some(&base)).unwrap();
+        assert_eq!(changes.renamed.get("src/a.rs").map(String::as_str), Some("src/c.rs"));
+        assert!(!changes.same_as_base(&project, "src/a.rs", &dir.join("src/c.rs")));
+
+        std::fs::write(dir.join("src/c.rs"), &content).unwrap();
+        assert!(changes.same_as_base(&project, "src/a.rs", &dir.join("src/c.rs")));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,567 @@
+//! 單一檔案、可離線開啟的 HTML 報告。
+//!
+//! 樣式與腳本內嵌在輸出的檔案中；搜尋索引以 JSON 嵌入頁面，由腳本在瀏覽器端搜尋。
+//! 介面文字與 `lang` 屬性取自語言目錄。
+//! 架構圖在產生報告時繪成 SVG 嵌入頁面，不需要載入任何外部資源。
+
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use tracing::info;
+use crate::diagrams;
+use crate::locale::Catalog;
+use crate::markdown::{worst_functions, WORST_FUNCTIONS_LIMIT};
+use crate::models::{ComplexityMetrics, FileAnalysis, ItemInfo, ItemKind, ProjectAnalysis};
+use crate::prompts::render;
+
+const STYLE: &str = include_str!("../assets/report.css");
+const SCRIPT: &str = include_str!("../assets/report.js");
+
+/// 搜尋索引中的一筆資料
+#[derive(Serialize)]
+struct SearchEntry<'a> {
+    name: String,
+    kind: &'a str,
+    file: &'a str,
+    line: usize,
+    anchor: String,
+    /// 文件註解與 AI 描述，供全文搜尋
+    text: String,
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn file_anchor(index: usize) -> String {
+    format!("file-{}", index)
+}
+
+fn item_anchor(file_index: usize, item_index: usize) -> String {
+    format!("item-{}-{}", file_index, item_index)
+}
+
+fn qualified_name(item: &ItemInfo) -> String {
+    match &item.parent {
+        Some(parent) => format!("{}::{}", parent, item.name),
+        None => item.name.clone(),
+    }
+}
+
+/// 語言目錄中的報告標籤；缺少時以名稱本身代替
+fn t<'a>(catalog: &'a Catalog, name: &'a str) -> &'a str {
+    catalog.report.get(name).map(String::as_str).unwrap_or(name)
+}
+
+fn directory(catalog: &Catalog, file_path: &str) -> String {
+    Path::new(file_path)
+        .parent()
+        .and_then(|p| p.to_str())
+        .filter(|p| !p.is_empty())
+        .unwrap_or(t(catalog, "root_dir"))
+        .to_string()
+}
+
+/// 側邊欄的目錄樹
+#[derive(Default)]
+struct DirTree<'a> {
+    dirs: BTreeMap<&'a str, DirTree<'a>>,
+    files: Vec<(usize, &'a FileAnalysis)>,
+}
+
+impl<'a> DirTree<'a> {
+    fn new(analyses: &'a [FileAnalysis]) -> Self {
+        let mut root = DirTree::default();
+        for (index, analysis) in analyses.iter().enumerate() {
+            let mut node = &mut root;
+            if let Some((dir, _)) = analysis.file_path.rsplit_once('/') {
+                for component in dir.split('/') {
+                    node = node.dirs.entry(component).or_default();
+                }
+            }
+            node.files.push((index, analysis));
+        }
+        root
+    }
+
+    fn push(&self, html: &mut String) {
+        html.push_str("<ul>\n");
+        for (name, dir) in &self.dirs {
+            html.push_str(&format!("<li><details open>\n<summary>{}</summary>\n", escape(name)));
+            dir.push(html);
+            html.push_str("</details></li>\n");
+        }
+        for (index, analysis) in &self.files {
+            let file_name = Path::new(&analysis.file_path)
+                .file_name()
+                .and_then(|f| f.to_str())
+                .unwrap_or(&analysis.file_path);
+            html.push_str(&format!(
+                "<li><a href=\"#{}\">{}</a></li>\n",
+                file_anchor(*index),
+                escape(file_name)
+            ));
+        }
+        html.push_str("</ul>\n");
+    }
+}
+
+fn search_index(analyses: &[FileAnalysis]) -> String {
+    let mut entries = Vec::new();
+    for (file_index, analysis) in analyses.iter().enumerate() {
+        let ai = analysis.ai_analysis.as_ref();
+        for (item_index, item) in analysis.items.iter().enumerate() {
+            if item.kind == ItemKind::Impl {
+                continue;
+            }
+            let mut text = item.doc.clone().unwrap_or_default();
+            let described = ai.and_then(|ai| {
+                ai.functions_details.iter().find(|d| d.name == item.name).map(|d| &d.description)
+                    .or_else(|| ai.core_structs.iter().find(|s| s.name == item.name).map(|s| &s.description))
+            });
+            if let Some(description) = described {
+                text.push(' ');
+                text.push_str(description);
+            }
+            entries.push(SearchEntry {
+                name: qualified_name(item),
+                kind: item.kind.as_str(),
+                file: &analysis.file_path,
+                line: item.start_line,
+                anchor: item_anchor(file_index, item_index),
+                text,
+            });
+        }
+    }
+    // 避免內容中的 `</script>` 提前結束 script 元素
+    serde_json::to_string(&entries)
+        .unwrap_or_else(|_| "[]".to_string())
+        .replace("</", "<\\/")
+}
+
+fn push_list(html: &mut String, title: &str, values: &[String]) {
+    if values.is_empty() {
+        return;
+    }
+    html.push_str(&format!("<h3>{}</h3>\n<ul>\n", escape(title)));
+    for value in values {
+        html.push_str(&format!("<li>{}</li>\n", escape(value)));
+    }
+    html.push_str("</ul>\n");
+}
+
+fn push_sidebar(html: &mut String, catalog: &Catalog, tree: &DirTree, has_diagrams: bool, has_worst: bool) {
+    html.push_str(&format!(
+        "<nav>\n<input id=\"search\" type=\"search\" placeholder=\"{}\" data-more=\"{}\" data-none=\"{}\" autocomplete=\"off\">\n",
+        escape(t(catalog, "search_placeholder")),
+        escape(t(catalog, "search_more")),
+        escape(t(catalog, "search_none")),
+    ));
+    html.push_str("<ul id=\"search-results\"></ul>\n");
+    html.push_str(&format!("<a href=\"#summary\">{}</a><br>\n", escape(t(catalog, "project_summary"))));
+    if has_diagrams {
+        html.push_str(&format!("<a href=\"#diagrams\">{}</a><br>\n", escape(t(catalog, "diagrams"))));
+    }
+    if has_worst {
+        html.push_str(&format!("<a href=\"#worst\">{}</a>\n", escape(t(catalog, "worst_functions"))));
+    }
+    html.push_str("<div class=\"tree\">\n");
+    tree.push(html);
+    html.push_str("</div>\n</nav>\n");
+}
+
+fn push_summary(html: &mut String, catalog: &Catalog, report: &ProjectAnalysis) {
+    let summary = &report.summary;
+    let metadata = &report.metadata;
+    let colon = t(catalog, "colon");
+    html.push_str(&format!("<h1>{}</h1>\n<p class=\"meta\">", escape(t(catalog, "title"))));
+    let mut meta = vec![format!("rs2know {}", escape(&metadata.generator_version))];
+    if let (Some(provider), Some(model)) = (&metadata.provider, &metadata.model) {
+        meta.push(format!("{} / {}", escape(provider), escape(model)));
+    }
+    if let Some(commit) = &metadata.git_commit {
+        let dirty = if metadata.git_dirty { t(catalog, "dirty") } else { "" };
+        meta.push(format!(
+            "{} <code>{}</code>{}",
+            escape(t(catalog, "commit")),
+            escape(&commit[..commit.len().min(12)]),
+            escape(dirty)
+        ));
+    }
+    if let Some(finished) = metadata.finished_at {
+        meta.push(finished.format("%Y-%m-%d %H:%M:%S UTC").to_string());
+    }
+    html.push_str(&meta.join(" · "));
+    html.push_str("</p>\n");
+
+    html.push_str(&format!("<h2 id=\"summary\">{}</h2>\n", escape(t(catalog, "project_summary"))));
+    html.push_str(&format!(
+        "<p>{}{}{}{}{}{}{}</p>\n",
+        escape(t(catalog, "total_files")),
+        escape(colon),
+        summary.total_files,
+        escape(t(catalog, "list_separator")),
+        escape(t(catalog, "total_loc")),
+        escape(colon),
+        summary.total_loc
+    ));
+    if !summary.code_architecture.is_empty() {
+        html.push_str(&format!(
+            "<h3>{}</h3>\n<p>{}</p>\n",
+            escape(t(catalog, "code_architecture")),
+            escape(&summary.code_architecture)
+        ));
+    }
+    push_list(html, t(catalog, "main_features"), &summary.main_features);
+    push_list(html, t(catalog, "key_components"), &summary.key_components);
+    push_list(html, t(catalog, "tech_stack"), &summary.tech_stack);
+    push_list(html, t(catalog, "recommendations"), &summary.recommendations);
+
+    if report.crates.is_empty() {
+        return;
+    }
+    html.push_str(&format!("<h2 id=\"architecture\">{}</h2>\n", escape(t(catalog, "module_architecture"))));
+    for krate in &report.crates {
+        html.push_str(&format!(
+            "<h3>{}</h3>\n<p class=\"meta\"><code>{}</code> · {}{}{} · {}{}{}</p>\n",
+            escape(&krate.name),
+            escape(&krate.path),
+            escape(t(catalog, "files")),
+            escape(colon),
+            krate.total_files,
+            escape(t(catalog, "loc")),
+            escape(colon),
+            krate.total_loc
+        ));
+        if !krate.overview.purpose.is_empty() {
+            html.push_str(&format!("<p>{}</p>\n", escape(&krate.overview.purpose)));
+        }
+        if !krate.overview.code_architecture.is_empty() {
+            html.push_str(&format!(
+                "<p><strong>{}</strong>{}{}</p>\n",
+                escape(t(catalog, "architecture")),
+                escape(colon),
+                escape(&krate.overview.code_architecture)
+            ));
+        }
+        html.push_str(&format!(
+            "<table class=\"sortable\">\n<thead><tr><th>{}</th><th class=\"num\">{}</th><th class=\"num\">{}</th><th>{}</th><th>{}</th></tr></thead>\n<tbody>\n",
+            escape(t(catalog, "module")),
+            escape(t(catalog, "files")),
+            escape(t(catalog, "lines")),
+            escape(t(catalog, "purpose")),
+            escape(t(catalog, "responsibilities")),
+        ));
+        for module in report.modules.iter().filter(|m| m.crate_name == krate.name) {
+            html.push_str(&format!(
+                "<tr><td><code>{}</code></td><td class=\"num\">{}</td><td class=\"num\">{}</td><td>{}</td><td>{}</td></tr>\n",
+                escape(&module.path),
+                module.total_files,
+                module.total_loc,
+                escape(&module.overview.purpose),
+                escape(&module.overview.responsibilities.join(t(catalog, "list_separator"))),
+            ));
+        }
+        html.push_str("</tbody>\n</table>\n");
+    }
+}
+
+fn push_diagrams(html: &mut String, catalog: &Catalog, diagrams: &[(&str, String)]) {
+    html.push_str(&format!("<h2 id=\"diagrams\">{}</h2>\n", escape(t(catalog, "diagrams"))));
+    for (name, svg) in diagrams {
+        html.push_str(&format!(
+            "<h3>{}</h3>\n<figure class=\"diagram\">\n{}</figure>\n",
+            escape(t(catalog, name)),
+            svg
+        ));
+    }
+}
+
+fn push_complexity_table(
+    html: &mut String,
+    catalog: &Catalog,
+    functions: &[(&FileAnalysis, &ItemInfo, ComplexityMetrics)],
+    anchors: &dyn Fn(&FileAnalysis, &ItemInfo) -> String,
+    with_file: bool,
+) {
+    html.push_str(&format!("<table class=\"sortable\">\n<thead><tr><th>{}</th>", escape(t(catalog, "function"))));
+    if with_file {
+        html.push_str(&format!("<th>{}</th>", escape(t(catalog, "file"))));
+    }
+    for name in ["line", "cognitive", "cyclomatic", "max_nesting", "parameter_count"] {
+        html.push_str(&format!("<th class=\"num\">{}</th>", escape(t(catalog, name))));
+    }
+    html.push_str("</tr></thead>\n<tbody>\n");
+    for (analysis, item, metrics) in functions {
+        html.push_str(&format!(
+            "<tr><td><a href=\"#{}\"><code>{}</code></a></td>",
+            anchors(analysis, item),
+            escape(&qualified_name(item))
+        ));
+        if with_file {
+            html.push_str(&format!("<td>{}</td>", escape(&analysis.file_path)));
+        }
+        html.push_str(&format!(
+            "<td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>\n",
+            item.start_line, metrics.cognitive, metrics.cyclomatic, metrics.max_nesting, metrics.parameters
+        ));
+    }
+    html.push_str("</tbody>\n</table>\n");
+}
+
+fn push_file(html: &mut String, catalog: &Catalog, index: usize, analysis: &FileAnalysis) {
+    html.push_str(&format!(
+        "<section class=\"file\" id=\"{}\">\n<h3>{}</h3>\n",
+        file_anchor(index),
+        escape(&analysis.file_path)
+    ));
+    let stats = [
+        ("loc", analysis.loc),
+        ("code", analysis.code_lines),
+        ("comment", analysis.comment_lines),
+        ("doc", analysis.doc_comment_lines + analysis.inner_doc_lines),
+        ("blank", analysis.blank_lines),
+    ]
+    .map(|(name, count)| (name, count.to_string()));
+    let vars: Vec<(&str, &str)> = stats.iter().map(|(name, count)| (*name, count.as_str())).collect();
+    html.push_str(&format!("<p class=\"meta\">{}</p>\n", escape(&render(t(catalog, "file_stats"), &vars))));
+
+    if !analysis.items.is_empty() {
+        html.push_str(&format!(
+            "<table class=\"sortable\">\n<thead><tr><th>{}</th><th>{}</th><th>{}</th><th class=\"num\">{}</th><th>{}</th></tr></thead>\n<tbody>\n",
+            escape(t(catalog, "kind")),
+            escape(t(catalog, "name")),
+            escape(t(catalog, "visibility")),
+            escape(t(catalog, "line")),
+            escape(t(catalog, "signature")),
+        ));
+        for (item_index, item) in analysis.items.iter().enumerate() {
+            html.push_str(&format!(
+                "<tr id=\"{}\"><td>{}</td><td><code>{}</code></td><td>{}</td><td class=\"num\">{}</td><td><code>{}</code></td></tr>\n",
+                item_anchor(index, item_index),
+                item.kind.as_str(),
+                escape(&qualified_name(item)),
+                escape(&item.visibility),
+                item.start_line,
+                escape(&item.signature),
+            ));
+        }
+        html.push_str("</tbody>\n</table>\n");
+    }
+
+    let functions = worst_functions(std::slice::from_ref(analysis), usize::MAX);
+    if !functions.is_empty() {
+        html.push_str(&format!("<details>\n<summary>{}</summary>\n", escape(t(catalog, "function_complexity"))));
+        let anchor = |analysis: &FileAnalysis, item: &ItemInfo| {
+            let item_index = analysis.items.iter().position(|i| std::ptr::eq(i, item)).unwrap_or_default();
+            item_anchor(index, item_index)
+        };
+        push_complexity_table(html, catalog, &functions, &anchor, false);
+        html.push_str("</details>\n");
+    }
+
+    if let Some(ai) = &analysis.ai_analysis {
+        let colon = escape(t(catalog, "colon"));
+        html.push_str(&format!("<details>\n<summary>{}</summary>\n", escape(t(catalog, "ai_analysis"))));
+        if analysis.ai_stale {
+            html.push_str(&format!("<p class=\"meta\">{}</p>\n", escape(t(catalog, "ai_stale"))));
+        }
+        push_list(html, t(catalog, "main_functions"), &ai.main_functions);
+        if !ai.core_structs.is_empty() {
+            html.push_str(&format!("<h3>{}</h3>\n<ul>\n", escape(t(catalog, "core_structs"))));
+            for core_struct in &ai.core_structs {
+                html.push_str(&format!(
+                    "<li><strong>{}</strong>{}{}</li>\n",
+                    escape(&core_struct.name),
+                    colon,
+                    escape(&core_struct.description)
+                ));
+            }
+            html.push_str("</ul>\n");
+        }
+        push_list(html, t(catalog, "error_types"), &ai.error_types);
+        if !ai.functions_details.is_empty() {
+            html.push_str(&format!("<h3>{}</h3>\n<dl>\n", escape(t(catalog, "functions_details"))));
+            for detail in &ai.functions_details {
+                html.push_str(&format!(
+                    "<dt><code>{}</code> → <code>{}</code></dt>\n<dd>{}",
+                    escape(&detail.name),
+                    escape(&detail.return_type),
+                    escape(&detail.description)
+                ));
+                if !detail.parameters.is_empty() {
+                    html.push_str(&format!(
+                        "<br>{}{}{}",
+                        escape(t(catalog, "parameters")),
+                        colon,
+                        escape(&detail.parameters.join(t(catalog, "list_separator")))
+                    ));
+                }
+                if !detail.complexity.is_empty() {
+                    html.push_str(&format!(
+                        "<br>{}{}{}",
+                        escape(t(catalog, "complexity")),
+                        colon,
+                        escape(&detail.complexity)
+                    ));
+                }
+                html.push_str("</dd>\n");
+            }
+            html.push_str("</dl>\n");
+        }
+        if !ai.code_complexity.is_empty() {
+            html.push_str(&format!(
+                "<h3>{}</h3>\n<p>{}</p>\n",
+                escape(t(catalog, "code_complexity")),
+                escape(&ai.code_complexity)
+            ));
+        }
+        html.push_str("</details>\n");
+    }
+    html.push_str("</section>\n");
+}
+
+fn render_html(report: &ProjectAnalysis, catalog: &Catalog) -> String {
+    let analyses = &report.file_analyses;
+    let mut dirs: BTreeMap<String, Vec<(usize, &FileAnalysis)>> = BTreeMap::new();
+    for (index, analysis) in analyses.iter().enumerate() {
+        dirs.entry(directory(catalog, &analysis.file_path)).or_default().push((index, analysis));
+    }
+
+    let mut html = String::new();
+    html.push_str(&format!(
+        "<!DOCTYPE html>\n<html lang=\"{}\">\n<head>\n<meta charset=\"utf-8\">\n",
+        escape(&catalog.code)
+    ));
+    html.push_str("<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n");
+    html.push_str(&format!("<title>{}</title>\n", escape(t(catalog, "title"))));
+    html.push_str(&format!("<style>\n{}</style>\n</head>\n<body>\n", STYLE));
+    let diagrams = diagrams::build(report).svg();
+    let has_diagrams = !diagrams.is_empty();
+    let worst = worst_functions(analyses, WORST_FUNCTIONS_LIMIT);
+    push_sidebar(&mut html, catalog, &DirTree::new(analyses), has_diagrams, !worst.is_empty());
+
+    html.push_str("<main>\n");
+    push_summary(&mut html, catalog, report);
+    if has_diagrams {
+        push_diagrams(&mut html, catalog, &diagrams);
+    }
+
+    if !worst.is_empty() {
+        html.push_str(&format!("<h2 id=\"worst\">{}</h2>\n", escape(t(catalog, "worst_functions"))));
+        let anchor = |analysis: &FileAnalysis, item: &ItemInfo| {
+            let file_index = analyses.iter().position(|a| std::ptr::eq(a, analysis)).unwrap_or_default();
+            let item_index = analysis.items.iter().position(|i| std::ptr::eq(i, item)).unwrap_or_default();
+            item_anchor(file_index, item_index)
+        };
+        push_complexity_table(&mut html, catalog, &worst, &anchor, true);
+    }
+
+    for (dir, files) in &dirs {
+        html.push_str(&format!("<h2>{}</h2>\n", escape(dir)));
+        for (index, analysis) in files {
+            push_file(&mut html, catalog, *index, analysis);
+        }
+    }
+    html.push_str("</main>\n");
+
+    html.push_str(&format!(
+        "<script type=\"application/json\" id=\"search-index\">{}</script>\n",
+        search_index(analyses)
+    ));
+    html.push_str(&format!("<script>\n{}</script>\n</body>\n</html>\n", SCRIPT));
+    html
+}
+
+pub async fn generate_html_report(report: &ProjectAnalysis, catalog: &Catalog, output_path: &str) -> Result<()> {
+    std::fs::write(output_path, render_html(report, catalog))?;
+    info!("HTML 報告已生成並寫入 {}", output_path);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown::tests::sample_report;
+
+    #[test]
+    fn ui_follows_catalog() {
+        let report = sample_report();
+        let html = render_html(&report, &Catalog::load("en").unwrap());
+        assert!(html.contains("<html lang=\"en\">"));
+        assert!(html.contains("placeholder=\"Search functions, structs…\""));
+        for hardcoded in ["zh-Hant", "搜尋函數", "沒有符合", "專案總結", "架構圖", "複雜度最高的函數", "程式碼分析報告"] {
+            assert!(!html.contains(hardcoded), "{}", hardcoded);
+        }
+
+        let html = render_html(&report, &Catalog::load("zh-TW").unwrap());
+        assert!(html.contains("<html lang=\"zh-TW\">"));
+        assert!(html.contains("搜尋函數"));
+    }
+
+    #[test]
+    fn sidebar_nests_directories() {
+        let report = sample_report();
+        let html = render_html(&report, &Catalog::load("en").unwrap());
+        let nav = &html[html.find("<div class=\"tree\">").unwrap()..html.find("</nav>").unwrap()];
+        // 每個目錄一個可收合的節點，巢狀於上層目錄之中
+        let mut dirs: Vec<String> = report
+            .file_analyses
+            .iter()
+            .filter_map(|a| a.file_path.rsplit_once('/').map(|(dir, _)| dir.to_string()))
+            .collect();
+        let ancestors: Vec<String> = dirs
+            .iter()
+            .flat_map(|dir| dir.match_indices('/').map(move |(i, _)| dir[..i].to_string()))
+            .collect();
+        dirs.extend(ancestors);
+        dirs.sort();
+        dirs.dedup();
+        assert_eq!(nav.matches("<details open>").count(), dirs.len());
+        assert!(nav.find("<summary>a</summary>").unwrap() > nav.find("<summary>src</summary>").unwrap());
+        for index in 0..report.file_analyses.len() {
+            assert!(nav.contains(&format!("href=\"#{}\"", file_anchor(index))));
+        }
+    }
+
+    #[test]
+    fn sidebar_links_resolve() {
+        let mut report = sample_report();
+        let catalog = Catalog::load("en").unwrap();
+        assert!(render_html(&report, &catalog).contains("<a href=\"#worst\">"));
+
+        for analysis in &mut report.file_analyses {
+            analysis.items.retain(|item| item.kind != ItemKind::Function);
+        }
+        let html = render_html(&report, &catalog);
+        assert!(!html.contains("id=\"worst\""));
+        // 側邊欄的每個連結都有對應的錨點
+        let nav = &html[..html.find("</nav>").unwrap()];
+        for link in nav.split("href=\"#").skip(1) {
+            let id = &link[..link.find('"').unwrap()];
+            assert!(html.contains(&format!("id=\"{}\"", id)), "{}", id);
+        }
+    }
+
+    #[test]
+    fn tables_are_sortable() {
+        let html = render_html(&sample_report(), &Catalog::load("en").unwrap());
+        let tables = html.matches("<table").count();
+        assert!(tables > 0);
+        // report.js 依 class 掛上點擊排序，數字欄位以 num 標記
+        assert_eq!(html.matches("<table class=\"sortable\">").count(), tables);
+        assert!(html.contains("<th class=\"num\">Cyclomatic</th>"));
+    }
+}
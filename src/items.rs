@@ -0,0 +1,446 @@
+//! 以 `syn` 解析原始碼，擷取每個項目（函數、結構體、列舉、trait、impl 等）的清單。
+//!
+//! 擷取完全在本地進行，不依賴 AI，因此在 `--skip-ai` 時同樣可用。
+
+use anyhow::{Result, anyhow};
+use proc_macro2::{LineColumn, Span};
+use syn::spanned::Spanned;
+use syn::{Attribute, Expr, Ident, ImplItem, Item, Lit, Meta, TraitItem, UseTree, Visibility};
+use crate::complexity;
+use crate::models::{ItemInfo, ItemKind};
+
+/// 原始碼文字，用於依照 span 取回對應的片段
+pub(crate) struct Source<'a> {
+    lines: Vec<&'a str>,
+}
+
+impl<'a> Source<'a> {
+    pub(crate) fn new(content: &'a str) -> Self {
+        Self { lines: content.lines().collect() }
+    }
+
+    /// 取出 `start` 至 `end` 之間的原始碼，並將連續空白壓縮為單一空格
+    pub(crate) fn slice(&self, start: LineColumn, end: LineColumn) -> String {
+        if start.line == 0 || end.line < start.line {
+            return String::new();
+        }
+        let mut text = String::new();
+        for line_no in start.line..=end.line.min(self.lines.len()) {
+            let line = self.lines[line_no - 1];
+            let from = if line_no == start.line { start.column } else { 0 };
+            let to = if line_no == end.line { end.column } else { usize::MAX };
+            text.extend(line.chars().skip(from).take(to.saturating_sub(from)));
+            text.push(' ');
+        }
+        text.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
+    fn between(&self, start: Span, end: Span) -> String {
+        self.slice(start.start(), end.end())
+    }
+}
+
+struct Extractor<'a> {
+    source: Source<'a>,
+    items: Vec<ItemInfo>,
+}
+
+/// 一個檔案的項目清單與 `use` 宣告
+#[derive(Default)]
+pub struct Inventory {
+    pub items: Vec<ItemInfo>,
+    /// 檔案最外層 `use` 宣告展開後的路徑，例如 `crate::models::FileAnalysis`
+    pub imports: Vec<String>,
+}
+
+/// 擷取檔案中的所有項目（包含 impl、trait 與內嵌模組中的成員）與 `use` 宣告
+pub fn extract_inventory(content: &str) -> Result<Inventory> {
+    let file = syn::parse_file(content)
+        .map_err(|e| anyhow!("無法解析 Rust 原始碼（第 {} 行）：{}", e.span().start().line, e))?;
+    let mut extractor = Extractor {
+        source: Source::new(content),
+        items: Vec::new(),
+    };
+    extractor.visit_items(&file.items, None);
+
+    // 內嵌模組中的 `use` 以該模組為基準，無法直接與檔案的模組對應，因此只收集最外層的宣告
+    let mut imports = Vec::new();
+    for item in &file.items {
+        if let Item::Use(u) = item {
+            let prefix = if u.leading_colon.is_some() { "::" } else { "" };
+            flatten_use(&u.tree, prefix.to_string(), &mut imports);
+        }
+    }
+    imports.sort();
+    imports.dedup();
+    Ok(Inventory { items: extractor.items, imports })
+}
+
+/// 將 `use a::{self, b, c::*}` 展開為 `a`、`a::b`、`a::c::*`；重新命名的匯入記錄原本的名稱
+fn flatten_use(tree: &UseTree, prefix: String, imports: &mut Vec<String>) {
+    let name = |ident: &Ident| {
+        if ident == "self" && !prefix.is_empty() {
+            prefix.trim_end_matches("::").to_string()
+        } else {
+            format!("{}{}", prefix, ident)
+        }
+    };
+    match tree {
+        UseTree::Path(path) => flatten_use(&path.tree, format!("{}{}::", prefix, path.ident), imports),
+        UseTree::Name(use_name) => imports.push(name(&use_name.ident)),
+        UseTree::Rename(rename) => imports.push(name(&rename.ident)),
+        UseTree::Glob(_) => imports.push(format!("{}*", prefix)),
+        UseTree::Group(group) => {
+            for tree in &group.items {
+                flatten_use(tree, prefix.clone(), imports);
+            }
+        }
+    }
+}
+
+/// 合併 `#[doc = "..."]` 屬性為單一字串
+pub(crate) fn doc_comment(attrs: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(nv) => match &nv.value {
+                Expr::Lit(expr) => match &expr.lit {
+                    Lit::Str(s) => Some(s.value()),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        })
+        .flat_map(|doc| doc.lines().map(|l| l.strip_prefix(' ').unwrap_or(l).trim_end().to_string()).collect::<Vec<_>>())
+        .collect();
+    let doc = lines.join("\n").trim().to_string();
+    if doc.is_empty() { None } else { Some(doc) }
+}
+
+impl Extractor<'_> {
+    fn visibility(&self, vis: &Visibility) -> String {
+        match vis {
+            Visibility::Inherited => "private".to_string(),
+            _ => self.source.between(vis.span(), vis.span()),
+        }
+    }
+
+    /// 項目的起始 span：有可見性修飾時從可見性開始，否則從關鍵字開始
+    fn start_span(vis: &Visibility, keyword: Span) -> Span {
+        match vis {
+            Visibility::Inherited => keyword,
+            _ => vis.span(),
+        }
+    }
+
+    fn generics(&self, generics: &syn::Generics) -> String {
+        match (&generics.lt_token, &generics.gt_token) {
+            (Some(lt), Some(gt)) => self.source.between(lt.span, gt.span),
+            _ => String::new(),
+        }
+    }
+
+    /// 泛型宣告的結尾 span（where 子句、`>` 或名稱），用於截取項目標頭
+    fn header_end(generics: &syn::Generics, fallback: Span) -> Span {
+        if let Some(where_clause) = &generics.where_clause {
+            where_clause.span()
+        } else if let Some(gt) = &generics.gt_token {
+            gt.span
+        } else {
+            fallback
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn push(
+        &mut self,
+        kind: ItemKind,
+        name: String,
+        parent: Option<&str>,
+        vis: &Visibility,
+        attrs: &[Attribute],
+        start: Span,
+        header_end: Span,
+        whole: Span,
+        generics: Option<&syn::Generics>,
+    ) {
+        let signature = self.source.between(start, header_end);
+        self.items.push(ItemInfo {
+            kind,
+            name,
+            parent: parent.map(str::to_string),
+            visibility: self.visibility(vis),
+            signature,
+            generics: generics.map(|g| self.generics(g)).unwrap_or_default(),
+            start_line: start.start().line,
+            end_line: whole.end().line.max(start.start().line),
+            doc: doc_comment(attrs),
+            trait_name: None,
+            complexity: None,
+        });
+    }
+
+    /// 為剛加入的函數附上複雜度指標
+    fn measure_last(&mut self, sig: &syn::Signature, block: &syn::Block) {
+        if let Some(last) = self.items.last_mut() {
+            last.complexity = Some(complexity::measure(sig, block));
+        }
+    }
+
+    fn visit_items(&mut self, items: &[Item], parent: Option<&str>) {
+        for item in items {
+            self.visit_item(item, parent);
+        }
+    }
+
+    fn visit_item(&mut self, item: &Item, parent: Option<&str>) {
+        match item {
+            Item::Fn(f) => {
+                let start = Self::start_span(&f.vis, f.sig.span());
+                self.push(ItemKind::Function, f.sig.ident.to_string(), parent, &f.vis, &f.attrs,
+                    start, f.sig.span(), f.block.span(), Some(&f.sig.generics));
+                self.measure_last(&f.sig, &f.block);
+            }
+            Item::Struct(s) => {
+                let start = Self::start_span(&s.vis, s.struct_token.span);
+                let end = Self::header_end(&s.generics, s.ident.span());
+                self.push(ItemKind::Struct, s.ident.to_string(), parent, &s.vis, &s.attrs,
+                    start, end, item.span(), Some(&s.generics));
+            }
+            Item::Enum(e) => {
+                let start = Self::start_span(&e.vis, e.enum_token.span);
+                let end = Self::header_end(&e.generics, e.ident.span());
+                self.push(ItemKind::Enum, e.ident.to_string(), parent, &e.vis, &e.attrs,
+                    start, end, item.span(), Some(&e.generics));
+            }
+            Item::Union(u) => {
+                let start = Self::start_span(&u.vis, u.union_token.span);
+                let end = Self::header_end(&u.generics, u.ident.span());
+                self.push(ItemKind::Union, u.ident.to_string(), parent, &u.vis, &u.attrs,
+                    start, end, item.span(), Some(&u.generics));
+            }
+            Item::Trait(t) => {
+                let keyword = t.unsafety.map(|u| u.span)
+                    .or(t.auto_token.map(|a| a.span))
+                    .unwrap_or(t.trait_token.span);
+                let start = Self::start_span(&t.vis, keyword);
+                let fallback = t.supertraits.last().map(|b| b.span()).unwrap_or(t.ident.span());
+                let end = if t.generics.where_clause.is_some() {
+                    Self::header_end(&t.generics, fallback)
+                } else {
+                    fallback
+                };
+                let name = t.ident.to_string();
+                self.push(ItemKind::Trait, name.clone(), parent, &t.vis, &t.attrs,
+                    start, end, item.span(), Some(&t.generics));
+                self.visit_trait_items(&t.items, &name, &t.vis);
+            }
+            Item::Impl(i) => {
+                let start = i.unsafety.map(|u| u.span)
+                    .or(i.defaultness.map(|d| d.span))
+                    .unwrap_or(i.impl_token.span);
+                let end = match &i.generics.where_clause {
+                    Some(where_clause) => where_clause.span(),
+                    None => i.self_ty.span(),
+                };
+                let self_ty = self.source.between(i.self_ty.span(), i.self_ty.span());
+                let trait_name = i.trait_.as_ref()
+                    .map(|(_, path, _)| self.source.between(path.span(), path.span()));
+                self.push(ItemKind::Impl, self_ty.clone(), parent, &Visibility::Inherited, &i.attrs,
+                    start, end, item.span(), Some(&i.generics));
+                if let Some(last) = self.items.last_mut() {
+                    last.trait_name = trait_name;
+                }
+                self.visit_impl_items(&i.items, &self_ty);
+            }
+            Item::Const(c) => {
+                let start = Self::start_span(&c.vis, c.const_token.span);
+                self.push(ItemKind::Const, c.ident.to_string(), parent, &c.vis, &c.attrs,
+                    start, c.ty.span(), item.span(), Some(&c.generics));
+            }
+            Item::Static(s) => {
+                let start = Self::start_span(&s.vis, s.static_token.span);
+                self.push(ItemKind::Static, s.ident.to_string(), parent, &s.vis, &s.attrs,
+                    start, s.ty.span(), item.span(), None);
+            }
+            Item::Type(t) => {
+                let start = Self::start_span(&t.vis, t.type_token.span);
+                self.push(ItemKind::Type, t.ident.to_string(), parent, &t.vis, &t.attrs,
+                    start, t.ty.span(), item.span(), Some(&t.generics));
+            }
+            Item::Macro(m) if m.mac.path.is_ident("macro_rules") => {
+                if let Some(ident) = &m.ident {
+                    let start = m.mac.path.span();
+                    self.push(ItemKind::Macro, ident.to_string(), parent, &Visibility::Inherited, &m.attrs,
+                        start, ident.span(), item.span(), None);
+                    // `#[macro_export]` 的巨集可以從 crate 外部以 `crate_name::name!` 使用
+                    if m.attrs.iter().any(|attr| attr.path().is_ident("macro_export")) {
+                        if let Some(last) = self.items.last_mut() {
+                            last.visibility = "pub".to_string();
+                        }
+                    }
+                }
+            }
+            Item::Mod(m) => {
+                if let Some((_, items)) = &m.content {
+                    let path = match parent {
+                        Some(parent) => format!("{}::{}", parent, m.ident),
+                        None => m.ident.to_string(),
+                    };
+                    self.visit_items(items, Some(&path));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// trait 的成員沒有自己的可見性，沿用 trait 的可見性
+    fn visit_trait_items(&mut self, items: &[TraitItem], parent: &str, vis: &Visibility) {
+        for item in items {
+            match item {
+                TraitItem::Fn(f) => {
+                    self.push(ItemKind::Function, f.sig.ident.to_string(), Some(parent), vis, &f.attrs,
+                        f.sig.span(), f.sig.span(), item.span(), Some(&f.sig.generics));
+                    if let Some(block) = &f.default {
+                        self.measure_last(&f.sig, block);
+                    }
+                }
+                TraitItem::Const(c) => {
+                    self.push(ItemKind::Const, c.ident.to_string(), Some(parent), vis, &c.attrs,
+                        c.const_token.span, c.ty.span(), item.span(), Some(&c.generics));
+                }
+                TraitItem::Type(t) => {
+                    let end = t.bounds.last().map(|b| b.span())
+                        .unwrap_or_else(|| Self::header_end(&t.generics, t.ident.span()));
+                    self.push(ItemKind::Type, t.ident.to_string(), Some(parent), vis, &t.attrs,
+                        t.type_token.span, end, item.span(), Some(&t.generics));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn visit_impl_items(&mut self, items: &[ImplItem], parent: &str) {
+        for item in items {
+            match item {
+                ImplItem::Fn(f) => {
+                    let start = Self::start_span(&f.vis, f.sig.span());
+                    self.push(ItemKind::Function, f.sig.ident.to_string(), Some(parent), &f.vis, &f.attrs,
+                        start, f.sig.span(), f.block.span(), Some(&f.sig.generics));
+                    self.measure_last(&f.sig, &f.block);
+                }
+                ImplItem::Const(c) => {
+                    let start = Self::start_span(&c.vis, c.const_token.span);
+                    self.push(ItemKind::Const, c.ident.to_string(), Some(parent), &c.vis, &c.attrs,
+                        start, c.ty.span(), item.span(), Some(&c.generics));
+                }
+                ImplItem::Type(t) => {
+                    let start = Self::start_span(&t.vis, t.type_token.span);
+                    self.push(ItemKind::Type, t.ident.to_string(), Some(parent), &t.vis, &t.attrs,
+                        start, t.ty.span(), item.span(), Some(&t.generics));
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = include_str!("../tests/fixtures/items/inventory.rs");
+
+    /// (種類, 所屬, 名稱, 可見性, 起始行)
+    fn summary(items: &[ItemInfo]) -> Vec<(&str, Option<&str>, &str, &str, usize)> {
+        items
+            .iter()
+            .map(|item| (item.kind.as_str(), item.parent.as_deref(), item.name.as_str(), item.visibility.as_str(), item.start_line))
+            .collect()
+    }
+
+    #[test]
+    fn fixture_items() {
+        let inventory = extract_inventory(FIXTURE).unwrap();
+        let expected = vec![
+            ("trait", None, "Shape", "pub", 6),
+            ("const", Some("Shape"), "SIDES", "pub", 7),
+            ("type", Some("Shape"), "Unit", "pub", 8),
+            ("fn", Some("Shape"), "area", "pub", 11),
+            ("fn", Some("Shape"), "describe", "pub", 13),
+            ("trait", None, "Internal", "pub(crate)", 22),
+            ("fn", Some("Internal"), "hidden", "pub(crate)", 23),
+            ("trait", None, "Private", "private", 26),
+            ("fn", Some("Private"), "secret", "private", 27),
+            ("struct", None, "Square", "pub", 30),
+            ("impl", None, "Square<T>", "private", 34),
+            ("const", Some("Square<T>"), "NAME", "pub", 35),
+            ("fn", Some("Square<T>"), "new", "pub", 37),
+            ("fn", Some("Square<T>"), "helper", "private", 41),
+            ("impl", None, "Square<f64>", "private", 44),
+            ("const", Some("Square<f64>"), "SIDES", "private", 45),
+            ("type", Some("Square<f64>"), "Unit", "private", 46),
+            ("fn", Some("Square<f64>"), "area", "private", 48),
+            ("impl", None, "Square<f64>", "private", 53),
+            ("fn", Some("Square<f64>"), "fmt", "private", 54),
+            ("fn", Some("outer::inner"), "deep", "pub", 61),
+            ("struct", Some("outer::inner"), "Hidden", "pub(super)", 63),
+            ("fn", Some("outer"), "shallow", "private", 66),
+            ("macro_rules!", None, "square", "pub", 71),
+            ("macro_rules!", None, "local", "private", 77),
+        ];
+        assert_eq!(summary(&inventory.items), expected);
+    }
+
+    #[test]
+    fn fixture_details() {
+        let inventory = extract_inventory(FIXTURE).unwrap();
+        let find = |parent: Option<&str>, name: &str| {
+            inventory.items.iter().find(|i| i.parent.as_deref() == parent && i.name == name).unwrap()
+        };
+
+        let shape = find(None, "Shape");
+        assert_eq!(shape.doc.as_deref(), Some("A shape with an area."));
+        assert_eq!((shape.start_line, shape.end_line), (6, 20));
+        // 沒有預設實作的 trait 方法沒有複雜度
+        assert!(find(Some("Shape"), "area").complexity.is_none());
+        assert_eq!(find(Some("Shape"), "describe").complexity.unwrap().cyclomatic, 2);
+
+        let display = inventory.items.iter().find(|i| i.trait_name.as_deref() == Some("Display")).unwrap();
+        assert_eq!(display.signature, "impl Display for Square<f64>");
+        assert_eq!(find(Some("Square<T>"), "new").signature, "pub fn new(side: T) -> Self");
+        assert_eq!(find(None, "square").doc.as_deref(), Some("Builds a square."));
+
+        assert_eq!(inventory.imports, ["std::fmt", "std::fmt::Display"]);
+    }
+
+    #[test]
+    fn flattens_use_trees() {
+        let source = "\
+use std::io::{self, Read as _, prelude::*};
+use super::{self as parent, sibling::{self, Item}};
+use crate::a::{b::{c, d}, e};
+use self::inner::Thing as Other;
+use ::serde::Serialize;
+";
+        let imports = extract_inventory(source).unwrap().imports;
+        assert_eq!(
+            imports,
+            [
+                "::serde::Serialize",
+                "crate::a::b::c",
+                "crate::a::b::d",
+                "crate::a::e",
+                "self::inner::Thing",
+                "std::io",
+                "std::io::Read",
+                "std::io::prelude::*",
+                "super",
+                "super::sibling",
+                "super::sibling::Item",
+            ]
+        );
+    }
+}
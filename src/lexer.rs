@@ -0,0 +1,392 @@
+//! 以詞法層級掃描 Rust 原始碼，將每一行歸類為程式碼、屬性、註解或空白。
+//!
+//! 這裡不依賴完整的語法解析，因此即使檔案無法通過 `syn` 解析（例如巨集中的
+//! 非標準語法），行數統計依然準確。掃描器會正確處理巢狀區塊註解、字串中的
+//! `/*`、原始字串、字元常值與生命週期，以及程式碼後方的行尾註解。
+
+/// 單行的分類結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineKind {
+    Blank,
+    Code,
+    /// 只包含 `#[...]` 或 `#![...]` 屬性（可能跨多行）
+    Attribute,
+    /// 一般註解
+    Comment,
+    /// 外部文件註解：`///` 或 `/** */`
+    OuterDoc,
+    /// 內部文件註解：`//!` 或 `/*! */`
+    InnerDoc,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct LineFlags {
+    code: bool,
+    attribute: bool,
+    comment: bool,
+    outer_doc: bool,
+    inner_doc: bool,
+}
+
+impl LineFlags {
+    fn kind(&self) -> LineKind {
+        if self.code {
+            LineKind::Code
+        } else if self.attribute {
+            LineKind::Attribute
+        } else if self.outer_doc {
+            LineKind::OuterDoc
+        } else if self.inner_doc {
+            LineKind::InnerDoc
+        } else if self.comment {
+            LineKind::Comment
+        } else {
+            LineKind::Blank
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CommentKind {
+    Plain,
+    OuterDoc,
+    InnerDoc,
+}
+
+struct Scanner<'a> {
+    chars: &'a [char],
+    pos: usize,
+    line: usize,
+    lines: Vec<LineFlags>,
+    /// 目前所在屬性的方括號深度，0 表示不在屬性內
+    attr_depth: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn flags(&mut self) -> &mut LineFlags {
+        if self.line >= self.lines.len() {
+            self.lines.resize(self.line + 1, LineFlags::default());
+        }
+        &mut self.lines[self.line]
+    }
+
+    /// 前進一個字元並維護行號
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek(0)?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    /// 將目前行標記為程式碼（在屬性內則標記為屬性）
+    fn mark_code(&mut self) {
+        let in_attr = self.attr_depth > 0;
+        let flags = self.flags();
+        if in_attr {
+            flags.attribute = true;
+        } else {
+            flags.code = true;
+        }
+    }
+
+    fn mark_comment(&mut self, kind: CommentKind) {
+        let flags = self.flags();
+        match kind {
+            CommentKind::Plain => flags.comment = true,
+            CommentKind::OuterDoc => flags.outer_doc = true,
+            CommentKind::InnerDoc => flags.inner_doc = true,
+        }
+    }
+
+    fn run(mut self) -> Vec<LineFlags> {
+        while let Some(c) = self.peek(0) {
+            match c {
+                '/' if self.peek(1) == Some('/') => self.line_comment(),
+                '/' if self.peek(1) == Some('*') => self.block_comment(),
+                '"' => {
+                    self.mark_code();
+                    self.bump();
+                    self.quoted('"');
+                }
+                '\'' => self.quote_or_lifetime(),
+                'r' | 'b' | 'c' if self.raw_or_prefixed_string() => {}
+                '#' if self.attribute_start() => {}
+                '[' if self.attr_depth > 0 => {
+                    self.attr_depth += 1;
+                    self.mark_code();
+                    self.bump();
+                }
+                ']' if self.attr_depth > 0 => {
+                    self.mark_code();
+                    self.attr_depth -= 1;
+                    self.bump();
+                }
+                c if c.is_whitespace() => {
+                    self.bump();
+                }
+                c if c.is_alphanumeric() || c == '_' => {
+                    self.mark_code();
+                    while self.peek(0).is_some_and(|c| c.is_alphanumeric() || c == '_') {
+                        self.bump();
+                    }
+                }
+                _ => {
+                    self.mark_code();
+                    self.bump();
+                }
+            }
+        }
+        self.lines
+    }
+
+    fn line_comment(&mut self) {
+        let kind = match (self.peek(2), self.peek(3)) {
+            (Some('/'), Some('/')) => CommentKind::Plain,
+            (Some('/'), _) => CommentKind::OuterDoc,
+            (Some('!'), _) => CommentKind::InnerDoc,
+            _ => CommentKind::Plain,
+        };
+        self.mark_comment(kind);
+        while let Some(c) = self.peek(0) {
+            if c == '\n' {
+                break;
+            }
+            self.bump();
+        }
+    }
+
+    fn block_comment(&mut self) {
+        let kind = match (self.peek(2), self.peek(3)) {
+            // `/**/` 與 `/***` 都不是文件註解
+            (Some('*'), Some('/')) | (Some('*'), Some('*')) => CommentKind::Plain,
+            (Some('*'), _) => CommentKind::OuterDoc,
+            (Some('!'), _) => CommentKind::InnerDoc,
+            _ => CommentKind::Plain,
+        };
+        self.mark_comment(kind);
+        self.bump();
+        self.bump();
+        let mut depth = 1_usize;
+        while depth > 0 {
+            match self.peek(0) {
+                None => break,
+                Some('/') if self.peek(1) == Some('*') => {
+                    depth += 1;
+                    self.bump();
+                    self.bump();
+                }
+                Some('*') if self.peek(1) == Some('/') => {
+                    depth -= 1;
+                    self.mark_comment(kind);
+                    self.bump();
+                    self.bump();
+                }
+                Some(c) => {
+                    if !c.is_whitespace() {
+                        self.mark_comment(kind);
+                    }
+                    self.bump();
+                }
+            }
+        }
+    }
+
+    /// 掃描跳脫字串直到結束引號（開頭引號已被消耗）
+    fn quoted(&mut self, end: char) {
+        while let Some(c) = self.peek(0) {
+            if !c.is_whitespace() {
+                self.mark_code();
+            }
+            self.bump();
+            if c == '\\' {
+                if self.peek(0).is_some_and(|c| !c.is_whitespace()) {
+                    self.mark_code();
+                }
+                self.bump();
+            } else if c == end {
+                break;
+            }
+        }
+    }
+
+    /// 區分字元常值（`'a'`、`'\n'`）與生命週期或標籤（`'a`）
+    fn quote_or_lifetime(&mut self) {
+        self.mark_code();
+        self.bump();
+        match (self.peek(0), self.peek(1)) {
+            (Some('\\'), _) => self.quoted('\''),
+            (Some(_), Some('\'')) => {
+                self.bump();
+                self.bump();
+            }
+            _ => {}
+        }
+    }
+
+    /// 處理 `r"..."`、`r#"..."#`、`b"..."`、`br"..."`、`b'x'`、`c"..."` 等前綴字串。
+    /// 若目前位置不是字串則回傳 `false`，交由一般識別字處理。
+    fn raw_or_prefixed_string(&mut self) -> bool {
+        // 前綴必須是識別字的開頭
+        if self.pos > 0 {
+            let prev = self.chars[self.pos - 1];
+            if prev.is_alphanumeric() || prev == '_' {
+                return false;
+            }
+        }
+        let mut offset = 0;
+        if matches!(self.peek(0), Some('b') | Some('c')) {
+            offset += 1;
+        }
+        let raw = self.peek(offset) == Some('r');
+        if raw {
+            offset += 1;
+        }
+        if raw {
+            let mut hashes = 0;
+            while self.peek(offset + hashes) == Some('#') {
+                hashes += 1;
+            }
+            if self.peek(offset + hashes) != Some('"') {
+                return false;
+            }
+            self.mark_code();
+            for _ in 0..offset + hashes + 1 {
+                self.bump();
+            }
+            self.raw_body(hashes);
+            return true;
+        }
+        if offset == 0 {
+            return false;
+        }
+        match self.peek(offset) {
+            Some('"') => {
+                self.mark_code();
+                self.bump();
+                self.bump();
+                self.quoted('"');
+                true
+            }
+            Some('\'') if self.peek(0) == Some('b') => {
+                self.mark_code();
+                self.bump();
+                self.bump();
+                self.quoted('\'');
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn raw_body(&mut self, hashes: usize) {
+        while let Some(c) = self.peek(0) {
+            if !c.is_whitespace() {
+                self.mark_code();
+            }
+            self.bump();
+            if c == '"' && (0..hashes).all(|i| self.peek(i) == Some('#')) {
+                for _ in 0..hashes {
+                    self.bump();
+                }
+                break;
+            }
+        }
+    }
+
+    /// 偵測 `#[` 或 `#![` 並進入屬性模式
+    fn attribute_start(&mut self) -> bool {
+        let mut offset = 1;
+        if self.peek(offset) == Some('!') {
+            offset += 1;
+        }
+        while self.peek(offset).is_some_and(|c| c.is_whitespace() && c != '\n') {
+            offset += 1;
+        }
+        if self.peek(offset) != Some('[') {
+            return false;
+        }
+        self.attr_depth += 1;
+        self.mark_code();
+        for _ in 0..=offset {
+            self.bump();
+        }
+        true
+    }
+}
+
+/// 將原始碼逐行分類，回傳的向量長度等於 `content.lines().count()`
+pub fn classify_lines(content: &str) -> Vec<LineKind> {
+    let chars: Vec<char> = content.chars().collect();
+    let line_count = content.lines().count();
+    let scanner = Scanner {
+        chars: &chars,
+        pos: 0,
+        line: 0,
+        lines: vec![LineFlags::default(); line_count],
+        attr_depth: 0,
+    };
+    let mut lines = scanner.run();
+    lines.truncate(line_count);
+    lines.iter().map(LineFlags::kind).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 各分類的行數：空白、程式碼、屬性、註解、外部文件註解、內部文件註解
+    type Counts = [usize; 6];
+
+    const FIXTURES: &[(&str, &str, Counts)] = &[
+        ("raw_strings.rs", include_str!("../tests/fixtures/lexer/raw_strings.rs"), [0, 13, 0, 0, 0, 0]),
+        ("nested_comments.rs", include_str!("../tests/fixtures/lexer/nested_comments.rs"), [1, 2, 0, 8, 2, 2]),
+        ("docs_and_attributes.rs", include_str!("../tests/fixtures/lexer/docs_and_attributes.rs"), [2, 3, 8, 1, 1, 2]),
+        ("no_trailing_newline.rs", include_str!("../tests/fixtures/lexer/no_trailing_newline.rs"), [1, 1, 0, 1, 0, 0]),
+    ];
+
+    fn counts(content: &str) -> Counts {
+        let mut counts = [0; 6];
+        for kind in classify_lines(content) {
+            let index = match kind {
+                LineKind::Blank => 0,
+                LineKind::Code => 1,
+                LineKind::Attribute => 2,
+                LineKind::Comment => 3,
+                LineKind::OuterDoc => 4,
+                LineKind::InnerDoc => 5,
+            };
+            counts[index] += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn fixtures() {
+        for (name, content, expected) in FIXTURES {
+            assert_eq!(counts(content), *expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn crlf_line_endings() {
+        for (name, content, expected) in FIXTURES {
+            assert_eq!(counts(&content.replace('\n', "\r\n")), *expected, "{}（CRLF）", name);
+        }
+    }
+
+    #[test]
+    fn line_kinds() {
+        let content = "#[test]\nfn a() {} // x\n\n/* a\n b */ let x = 1;\n";
+        assert_eq!(
+            classify_lines(content),
+            [LineKind::Attribute, LineKind::Code, LineKind::Blank, LineKind::Comment, LineKind::Code]
+        );
+        assert!(classify_lines("").is_empty());
+    }
+}
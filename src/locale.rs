@@ -0,0 +1,176 @@
+//! 語言目錄：AI 提示詞與摘要標籤、要求模型回答的語言，以及 Markdown 與 HTML 報告的標題。
+//!
+//! 內建的目錄位於 `locales/`。使用者可以在 `~/.config/rs2know/locales/<語言代碼>.yml`
+//! 新增目錄或覆寫內建的目錄；目錄以 `base` 指定一個內建的語言（預設為 `en`），
+//! 未填寫的欄位沿用該語言的內容。
+
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+use serde_yaml::Value;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use crate::prompts::{render, DigestLabels, Prompts};
+
+/// 未設定語言時使用的語言
+pub const DEFAULT_LANGUAGE: &str = "zh-TW";
+
+/// 使用者目錄未指定 `base` 時沿用的語言
+const FALLBACK_LANGUAGE: &str = "en";
+
+const BUNDLED: &[(&str, &str)] = &[
+    ("zh-TW", include_str!("../locales/zh-TW.yml")),
+    ("en", include_str!("../locales/en.yml")),
+];
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Catalog {
+    /// 語言代碼，例如 `zh-TW`
+    #[serde(skip)]
+    pub code: String,
+    /// 語言的顯示名稱
+    pub name: String,
+    /// 要求模型回答時使用的語言，代入提示詞中的 `{language}`
+    pub language: String,
+    pub prompts: Prompts,
+    /// 總結請求中摘要的標題與標籤
+    pub digest: DigestLabels,
+    /// Markdown 與 HTML 報告的標題與標籤
+    pub report: BTreeMap<String, String>,
+}
+
+impl Catalog {
+    /// 載入語言目錄；使用者目錄優先於內建目錄
+    pub fn load(code: &str) -> Result<Self> {
+        let value = match user_catalog(code)? {
+            Some(mut user) => {
+                let base = user
+                    .get("base")
+                    .and_then(Value::as_str)
+                    .unwrap_or(FALLBACK_LANGUAGE)
+                    .to_string();
+                // 覆寫內建目錄時，以同一語言的內建內容為基礎
+                let base = if bundled(code).is_some() { code.to_string() } else { base };
+                let mut value = bundled(&base)
+                    .ok_or_else(|| anyhow!("語言目錄 {} 的 base 不是內建的語言：{}", code, base))??;
+                if let Some(user) = user.as_mapping_mut() {
+                    user.remove("base");
+                }
+                merge(&mut value, user);
+                value
+            }
+            None => bundled(code).ok_or_else(|| {
+                anyhow!("不支援的語言：{}（可用的語言：{}）", code, available().join("、"))
+            })??,
+        };
+        let mut catalog: Catalog = serde_yaml::from_value(value)
+            .map_err(|e| anyhow!("語言目錄 {} 格式錯誤：{}", code, e))?;
+        catalog.code = code.to_string();
+        Ok(catalog)
+    }
+
+    /// 代入提示詞範本的變數，並以 `{language}` 代入回答的語言
+    pub fn prompt(&self, template: &str, vars: &[(&str, &str)]) -> String {
+        let mut vars = vars.to_vec();
+        vars.push(("language", &self.language));
+        render(template, &vars)
+    }
+
+    /// 所有提示詞範本、摘要標籤與回答語言的 SHA-256
+    pub fn prompt_hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        let templates = self.prompts.all().into_iter().chain(self.digest.all());
+        for template in templates.chain([self.language.as_str()]) {
+            hasher.update(template.as_bytes());
+            hasher.update([0]);
+        }
+        format!("{:x}", hasher.finalize())
+    }
+}
+
+/// 內建與使用者目錄中所有可用的語言代碼
+pub fn available() -> Vec<String> {
+    let mut codes: Vec<String> = BUNDLED.iter().map(|(code, _)| code.to_string()).collect();
+    let user_codes = user_dir()
+        .and_then(|dir| std::fs::read_dir(dir).ok())
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            match path.extension()?.to_str()? {
+                "yml" | "yaml" => Some(path.file_stem()?.to_str()?.to_string()),
+                _ => None,
+            }
+        });
+    for code in user_codes {
+        if !codes.contains(&code) {
+            codes.push(code);
+        }
+    }
+    codes
+}
+
+fn bundled(code: &str) -> Option<Result<Value>> {
+    BUNDLED
+        .iter()
+        .find(|(bundled, _)| bundled.eq_ignore_ascii_case(code))
+        .map(|(_, content)| Ok(serde_yaml::from_str(content)?))
+}
+
+fn user_dir() -> Option<PathBuf> {
+    Some(dirs::home_dir()?.join(crate::config::GLOBAL_CONFIG_DIR).join("locales"))
+}
+
+fn user_catalog(code: &str) -> Result<Option<Value>> {
+    let Some(dir) = user_dir() else {
+        return Ok(None);
+    };
+    for extension in ["yml", "yaml"] {
+        let path = dir.join(format!("{}.{}", code, extension));
+        if path.exists() {
+            let content = std::fs::read_to_string(&path)?;
+            let value = serde_yaml::from_str(&content)
+                .map_err(|e| anyhow!("無法解析語言目錄：{} - {}", path.display(), e))?;
+            return Ok(Some(value));
+        }
+    }
+    Ok(None)
+}
+
+/// 以 `overlay` 的欄位覆寫 `base`，巢狀的對應表逐欄合併
+fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Mapping(base), Value::Mapping(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_catalogs_define_every_field() {
+        for (code, _) in BUNDLED {
+            let catalog = Catalog::load(code).unwrap();
+            assert!(catalog.digest.all().iter().all(|label| !label.is_empty()), "{}", code);
+        }
+    }
+
+    #[test]
+    fn digest_labels_change_prompt_hash() {
+        let catalog = Catalog::load("en").unwrap();
+        let mut relabeled = catalog.clone();
+        relabeled.digest.purpose = "Goal".to_string();
+        assert_ne!(catalog.prompt_hash(), relabeled.prompt_hash());
+    }
+}
@@ -1,39 +1,96 @@
+mod ai;
 mod analysis;
-mod openai;
+mod book;
+mod cache;
+mod chunking;
+mod complexity;
+mod html;
+mod git;
+mod items;
+mod lexer;
+mod locale;
 mod models;
+mod prompts;
+mod provider;
+mod ratelimit;
+mod report;
+mod slug;
+mod structured;
+mod summary;
+mod tokens;
 mod markdown;
 mod config;
+mod diagrams;
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use anyhow::{Result, anyhow};
 use tracing::{info, error};
 use tracing_subscriber::{EnvFilter, fmt::format::FmtSpan};
 use std::path::PathBuf;
 
-#[derive(Parser, Debug)]
+#[derive(Parser)]
 #[command(author, version, about = "一個用於分析 Rust 程式碼並進行 AI 分析的命令列工具")]
 struct Args {
     /// Rust 專案的路徑
     #[arg(short, long, default_value = ".")]
     path: String,
+    /// AI 服務提供者
+    #[arg(long, value_enum)]
+    provider: Option<provider::ProviderKind>,
+    /// 使用配置檔中的 profile（預設：配置檔的 default_profile）
+    #[arg(long, global = true)]
+    profile: Option<String>,
     /// OpenAI（或其他 GPT 服務）端點
     #[arg(long)]
     api_url: Option<String>,
-    /// OpenAI API 金鑰或 GPT 令牌
-    #[arg(long)]
+    /// OpenAI API 金鑰或 GPT 令牌；`-` 表示從標準輸入讀取。直接寫在命令列的金鑰會留在 shell 歷史中，已不建議使用
+    #[arg(long, value_name = "KEY")]
     api_key: Option<String>,
     /// GPT 模型名稱
     #[arg(long)]
     model: Option<String>,
+    /// 同時進行 AI 分析的檔案數量（預設：4）
+    #[arg(short, long)]
+    jobs: Option<usize>,
+    /// 每分鐘請求數上限
+    #[arg(long)]
+    rpm: Option<u32>,
+    /// 每分鐘 token 數上限
+    #[arg(long)]
+    tpm: Option<u32>,
+    /// 單次請求中程式碼的估計 token 上限，超過時切塊分析（預設：12000）
+    #[arg(long)]
+    max_input_tokens: Option<usize>,
+    /// 不讀取也不寫入 AI 分析快取
+    #[arg(long)]
+    no_cache: bool,
     /// 是否跳過 AI 分析
     #[arg(long)]
     skip_ai: bool,
-    /// 僅輸出 JSON 格式（無 markdown）
+    /// 僅輸出 JSON 格式（無 markdown），等同 `--format json`
     #[arg(long)]
     json: bool,
-    /// 輸出檔案路徑（預設：analysis_report.{json|md}）
+    /// 報告格式（預設依輸出檔案的副檔名決定，否則為 markdown）
+    #[arg(long, value_enum)]
+    format: Option<OutputFormat>,
+    /// 輸出檔案路徑（預設：analysis_report.{json|md|html}，mdbook 為 analysis_book 目錄）
     #[arg(short, long)]
     output: Option<String>,
+    /// 單一檔案的 Markdown 報告使用的 Tera 範本（預設：內建範本）
+    #[arg(long)]
+    template: Option<String>,
+    /// 提示詞、AI 回答與 Markdown 報告的語言，例如 zh-TW、en（預設：zh-TW）
+    #[arg(long)]
+    lang: Option<String>,
+    /// 只分析符合樣式的檔案，樣式語法同 `.gitignore`，可重複指定
+    #[arg(long, value_name = "PATTERN", global = true)]
+    include: Vec<String>,
+    /// 排除符合樣式的檔案或目錄，樣式語法同 `.gitignore`，可重複指定；指定時取代配置中的清單（預設：vendor/）
+    #[arg(long, value_name = "PATTERN", global = true)]
+    exclude: Vec<String>,
+    /// 另外將架構圖寫成 Graphviz DOT 檔案到指定的目錄
+    #[arg(long, value_name = "DIR")]
+    dot: Option<String>,
     /// 日誌級別 (trace, debug, info, warn, error)
     #[arg(long, default_value = "info")]
     log_level: String,
@@ -42,36 +99,169 @@ struct Args {
     command: Option<Commands>,
 }
 
+impl std::fmt::Debug for Args {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Args")
+            .field("path", &self.path)
+            .field("provider", &self.provider)
+            .field("profile", &self.profile)
+            .field("api_url", &self.api_url)
+            .field("api_key", &self.api_key.as_deref().map(config::mask_secret))
+            .field("model", &self.model)
+            .field("jobs", &self.jobs)
+            .field("rpm", &self.rpm)
+            .field("tpm", &self.tpm)
+            .field("max_input_tokens", &self.max_input_tokens)
+            .field("no_cache", &self.no_cache)
+            .field("skip_ai", &self.skip_ai)
+            .field("json", &self.json)
+            .field("format", &self.format)
+            .field("output", &self.output)
+            .field("template", &self.template)
+            .field("lang", &self.lang)
+            .field("include", &self.include)
+            .field("exclude", &self.exclude)
+            .field("dot", &self.dot)
+            .field("log_level", &self.log_level)
+            .field("command", &self.command)
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Markdown,
+    Json,
+    Html,
+    /// mdBook 目錄，每個原始碼檔案一頁
+    Mdbook,
+}
+
+impl OutputFormat {
+    /// 未指定輸出路徑時使用的預設值
+    fn default_output(&self) -> &'static str {
+        match self {
+            OutputFormat::Markdown => "analysis_report.md",
+            OutputFormat::Json => "analysis_report.json",
+            OutputFormat::Html => "analysis_report.html",
+            OutputFormat::Mdbook => "analysis_book",
+        }
+    }
+}
+
 #[derive(Subcommand, Debug)]
 enum Commands {
     /// 初始化專案配置
     Init,
-    /// 配置設定
+    /// 配置設定；未指定子命令時開啟互動式設定
     Config {
         /// 使用全局配置
-        #[arg(short, long)]
+        #[arg(short, long, global = true)]
         global: bool,
+        #[command(subcommand)]
+        action: Option<ConfigAction>,
     },
     /// 更新現有的 JSON 報告，只分析修改過的文件
     Update {
         /// 現有的 JSON 報告路徑
         #[arg(short, long)]
         report: String,
+        /// 比對的基準版本（預設：報告記錄的提交）
+        #[arg(long)]
+        since: Option<String>,
+    },
+    /// 列出會被分析的 Rust 檔案，不進行分析
+    Files,
+    /// 輸出 JSON 報告格式的 JSON Schema
+    Schema {
+        /// 輸出檔案路徑（預設：標準輸出）
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// 管理 AI 分析快取
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
     },
     /// 從 JSON 生成 Markdown 報告
     GenerateMd {
         /// JSON 報告路徑
         #[arg(short, long)]
         report: String,
-        /// 輸出的 Markdown 檔案路徑
+        /// 使用的 Tera 範本（預設：內建範本）
+        #[arg(long)]
+        template: Option<String>,
+        /// 輸出的 Markdown 檔案路徑；使用 `--book` 時為輸出目錄
         #[arg(short, long)]
         output: Option<String>,
+        /// 輸出 mdBook 目錄，每個原始碼檔案一頁
+        #[arg(long, conflicts_with = "template")]
+        book: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// 列出合併所有來源後的有效設定
+    Show {
+        /// 同時列出各設定值的來源
+        #[arg(long)]
+        origin: bool,
+    },
+    /// 輸出一個設定的有效值
+    Get {
+        /// 欄位名稱，例如 model
+        key: String,
+    },
+    /// 設定專案（或 `--global` 全局）配置中的一個欄位；`--profile` 設定其中的 profile
+    Set {
+        /// 欄位名稱，例如 model 或 profiles.final.model
+        key: String,
+        /// 欄位的值；`-` 表示從標準輸入讀取
+        #[arg(allow_negative_numbers = true)]
+        value: String,
+    },
+    /// 移除專案（或 `--global` 全局）配置中的一個欄位
+    Unset {
+        /// 欄位名稱
+        key: String,
+    },
+    /// 列出專案（或 `--global` 全局）配置中有設定的欄位
+    List,
+}
+
+/// `config set api_key` 的值同樣是金鑰，輸出時遮蔽
+impl std::fmt::Debug for ConfigAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigAction::Show { origin } => f.debug_struct("Show").field("origin", origin).finish(),
+            ConfigAction::Get { key } => f.debug_struct("Get").field("key", key).finish(),
+            ConfigAction::Set { key, value } => {
+                let value = if key.ends_with("api_key") { config::mask_secret(value) } else { value.clone() };
+                f.debug_struct("Set").field("key", key).field("value", &value).finish()
+            }
+            ConfigAction::Unset { key } => f.debug_struct("Unset").field("key", key).finish(),
+            ConfigAction::List => f.write_str("List"),
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum CacheAction {
+    /// 刪除過期或提示詞版本不符的快取項目
+    Prune {
+        /// 刪除超過指定天數的項目
+        #[arg(long, default_value_t = 30)]
+        older_than_days: u64,
+        /// 刪除所有快取項目
+        #[arg(long)]
+        all: bool,
     },
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let args = Args::parse();
+    let mut args = Args::parse();
     
     // 設置日誌級別
     let filter = EnvFilter::try_from_default_env()
@@ -83,6 +273,10 @@ async fn main() -> Result<()> {
         .with_span_events(FmtSpan::CLOSE)
         .init();
 
+    if let Some(key) = &args.api_key {
+        args.api_key = Some(config::cli_api_key(key)?);
+    }
+
     let project_path = PathBuf::from(&args.path);
     if !project_path.exists() {
         error!("指定的路徑不存在：{}", project_path.display());
@@ -93,173 +287,165 @@ async fn main() -> Result<()> {
         Some(Commands::Init) => {
             config::init_project(&project_path)?;
         }
-        Some(Commands::Config { global }) => {
-            config::configure_interactive(&project_path, *global).await?;
+        Some(Commands::Config { global, action: None }) => {
+            config::configure_interactive(&project_path, *global, args.profile.as_deref()).await?;
+        }
+        Some(Commands::Config { action: Some(ConfigAction::Show { origin }), .. }) => {
+            let config = config::get_effective_config(&project_path, &args)?;
+            config::show_config(&config, *origin);
+        }
+        Some(Commands::Config { action: Some(ConfigAction::Get { key }), .. }) => {
+            let config = config::get_effective_config(&project_path, &args)?;
+            config::print_value(&config, key)?;
+        }
+        Some(Commands::Config { global, action: Some(ConfigAction::Set { key, value }) }) => {
+            config::set_value(&project_path, *global, args.profile.as_deref(), key, value)?;
         }
-        Some(Commands::Update { report }) => {
-            analysis::update_report(report, &args.path, &args).await?;
+        Some(Commands::Config { global, action: Some(ConfigAction::Unset { key }) }) => {
+            config::unset_value(&project_path, *global, args.profile.as_deref(), key)?;
         }
-        Some(Commands::GenerateMd { report, output }) => {
-            markdown::generate_md_from_json(report, output.as_deref()).await?;
+        Some(Commands::Config { global, action: Some(ConfigAction::List) }) => {
+            config::list_values(&project_path, *global)?;
+        }
+        Some(Commands::Update { report, since }) => {
+            analysis::update_report(report, &args.path, since.as_deref(), &args).await?;
+        }
+        Some(Commands::Files) => {
+            let config = config::get_effective_config(&project_path, &args)?;
+            let filter = analysis::FileFilter::new(&project_path, &config.include, &config.exclude)?;
+            let files = analysis::discover_rust_files(&project_path, &filter);
+            for file in &files {
+                println!("{}", file.strip_prefix(&project_path).unwrap_or(file).display());
+            }
+            info!("共 {} 個檔案", files.len());
+        }
+        Some(Commands::Schema { output }) => {
+            let schema = serde_json::to_string_pretty(&report::json_schema())?;
+            match output {
+                Some(path) => {
+                    std::fs::write(path, schema)?;
+                    info!("JSON Schema 已寫入 {}", path);
+                }
+                None => println!("{}", schema),
+            }
+        }
+        Some(Commands::Cache { action: CacheAction::Prune { older_than_days, all } }) => {
+            let older_than = std::time::Duration::from_secs(older_than_days * 24 * 60 * 60);
+            cache::prune(ai::PROMPT_VERSION, older_than, *all)?;
+        }
+        Some(Commands::GenerateMd { report, template, output, book }) => {
+            let config = config::get_effective_config(&project_path, &args)?;
+            let catalog = locale::Catalog::load(&config.language)?;
+            let project_analysis = report::load(report)?;
+            if *book {
+                let output = output.as_deref().unwrap_or(OutputFormat::Mdbook.default_output());
+                book::generate_book(&project_analysis, &catalog, output).await?;
+            } else {
+                let output = output.as_deref().unwrap_or(OutputFormat::Markdown.default_output());
+                markdown::generate_markdown_report(&project_analysis, &catalog, template.as_deref(), output).await?;
+            }
+            if let Some(dir) = &args.dot {
+                diagrams::build(&project_analysis).write_dot(std::path::Path::new(dir))?;
+            }
         }
         None => {
             info!("開始分析路徑：{}", project_path.display());
+            let started_at = chrono::Utc::now();
             
-            // 載入配置
-            let config = config::get_effective_config(&project_path)?;
-            
-            // 命令行參數優先於配置文件
-            let api_url = args.api_url.unwrap_or(config.api_url);
-            let api_key = args.api_key.unwrap_or(config.api_key);
-            let model = args.model.unwrap_or(config.model);
-            
-            let mut analyses = Vec::new();
-            let mut total_files = 0_usize;
-            let mut total_loc = 0_usize;
-            
-            // 遞迴掃描目錄
-            for entry in walkdir::WalkDir::new(&project_path)
-                .into_iter()
-                .filter_entry(|e| {
-                    let skip = e.path()
-                        .components()
-                        .any(|c| c.as_os_str() == "target");
-                    if skip {
-                        tracing::debug!("跳過目錄：{}", e.path().display());
-                    }
-                    !skip
-                })
-                .filter_map(|e| e.ok())
-            {
-                let path = entry.path();
-                if path.is_file() && path.extension().map_or(false, |ext| ext == "rs") {
-                    total_files += 1;
-                    tracing::debug!("分析檔案：{}", path.display());
-                    
-                    let code_str = match std::fs::read_to_string(path) {
-                        Ok(content) => content,
-                        Err(e) => {
-                            error!("無法讀取檔案：{} - {}", path.display(), e);
-                            continue;
-                        }
-                    };
-                    
-                    let stats = analysis::analyze_code(&code_str);
-                    total_loc += stats.loc;
-                    
-                    // 計算相對路徑
-                    let relative_path = path.strip_prefix(&project_path)
-                        .unwrap_or(path)
-                        .to_string_lossy()
-                        .to_string();
-                    
-                    let ai_analysis = if !args.skip_ai && !api_key.is_empty() {
-                        info!("開始對檔案進行 AI 分析：{}", relative_path);
-                        match openai::do_ai_analysis_with_retry(&api_url, &api_key, &model, &code_str, &relative_path).await {
-                            Ok(Some(ai_result)) => {
-                                info!("AI 分析成功：{}", relative_path);
-                                Some(ai_result)
-                            }
-                            Ok(None) => None,
-                            Err(e) => {
-                                error!("AI 分析系統錯誤：{} - {}", relative_path, e);
-                                None
-                            }
-                        }
-                    } else {
-                        if args.skip_ai {
-                            tracing::debug!("跳過 AI 分析（已設定 skip_ai）");
-                        } else if api_key.is_empty() {
-                            tracing::warn!("跳過 AI 分析（API key 為空）");
-                        }
-                        None
-                    };
-                    
-                    analyses.push(models::FileAnalysis {
-                        file_path: relative_path,
-                        loc: stats.loc,
-                        blank_lines: stats.blank_lines,
-                        comment_lines: stats.comment_lines,
-                        code_lines: stats.code_lines,
-                        ai_analysis,
-                    });
-                }
+            // 載入配置，命令行參數優先於配置文件
+            let mut config = config::get_effective_config(&project_path, &args)?;
+            if !args.skip_ai {
+                config.resolve_api_key()?;
+            }
+            let use_ai = !args.skip_ai && !config.missing_api_key();
+            let catalog = locale::Catalog::load(&config.language)?;
+            let analyzer = ai::Analyzer::new(&config, catalog.clone());
+            let provider = analyzer.provider();
+            if use_ai {
+                info!("AI 服務提供者：{}，模型：{}，並行數：{}", provider.kind(), provider.model(), config.jobs);
+            } else if args.skip_ai {
+                tracing::debug!("跳過 AI 分析（已設定 skip_ai）");
+            } else {
+                tracing::warn!("跳過 AI 分析（{} 需要 API key，但 API key 為空）", config.provider);
             }
             
+            // 遞迴掃描目錄並分析檔案
+            let filter = analysis::FileFilter::new(&project_path, &config.include, &config.exclude)?;
+            let files = analysis::discover_rust_files(&project_path, &filter);
+            let ai_analyzer = if use_ai { Some(&analyzer) } else { None };
+            let analyses = analysis::analyze_files(&project_path, &files, ai_analyzer, config.jobs).await;
+            analyzer.log_cache_stats();
+            let total_files = analyses.len();
+            let total_loc: usize = analyses.iter().map(|a| a.loc).sum();
+            
             info!("偵測到的 Rust 檔案數：{}", total_files);
             info!("程式碼總行數：{}", total_loc);
             
             // 決定輸出路徑和格式
-            let output_path = if let Some(path) = args.output {
-                path
-            } else if args.json {
-                "analysis_report.json".to_string()
+            let output = args.output.as_deref().unwrap_or_default();
+            let format = args.format.unwrap_or(if args.json || output.ends_with(".json") {
+                OutputFormat::Json
+            } else if output.ends_with(".html") {
+                OutputFormat::Html
             } else {
-                "analysis_report.md".to_string()
-            };
+                OutputFormat::Markdown
+            });
+            let output_path = args.output
+                .clone()
+                .unwrap_or_else(|| format.default_output().to_string());
             
-            let project_summary = if !args.skip_ai && !api_key.is_empty() {
-                match openai::generate_project_summary_with_retry(&analyses, &api_url, &api_key, &model).await {
-                    Ok(Some(summary)) => Some(summary),
-                    Ok(None) => Some(models::ProjectSummary {
-                        total_files,
-                        total_loc,
-                        main_features: vec![],
-                        code_architecture: String::new(),
-                        key_components: vec![],
-                        tech_stack: vec![],
-                        recommendations: vec![],
-                    }),
-                    Err(e) => {
-                        error!("專案總結系統錯誤：{}", e);
-                        Some(models::ProjectSummary {
-                            total_files,
-                            total_loc,
-                            main_features: vec![],
-                            code_architecture: String::new(),
-                            key_components: vec![],
-                            tech_stack: vec![],
-                            recommendations: vec![],
-                        })
-                    }
-                }
-            } else {
-                Some(models::ProjectSummary {
-                    total_files,
-                    total_loc,
-                    main_features: vec![],
-                    code_architecture: String::new(),
-                    key_components: vec![],
-                    tech_stack: vec![],
-                    recommendations: vec![],
-                })
+            let summaries = summary::summarize_project(&project_path, &filter, &analyses, ai_analyzer, None, config.jobs).await;
+            let mut project_analysis = models::ProjectAnalysis {
+                schema_version: report::SCHEMA_VERSION,
+                metadata: analysis::report_metadata(&project_path, ai_analyzer, started_at),
+                summary: summaries.summary,
+                crates: summaries.crates,
+                modules: summaries.modules,
+                file_analyses: analyses,
             };
 
-            if args.json || output_path.ends_with(".json") {
-                // 生成 JSON 輸出
-                let project_analysis = models::ProjectAnalysis {
-                    summary: project_summary.unwrap_or(models::ProjectSummary {
-                        total_files,
-                        total_loc,
-                        main_features: vec![],
-                        code_architecture: String::new(),
-                        key_components: vec![],
-                        tech_stack: vec![],
-                        recommendations: vec![],
-                    }),
-                    file_analyses: analyses,
-                };
-                
-                let json_report = serde_json::to_string_pretty(&project_analysis)?;
-                std::fs::write(&output_path, &json_report)?;
-                info!("分析完成！JSON 報告已寫入 {}", output_path);
-            } else {
-                // 生成 Markdown 報告
-                markdown::generate_markdown_report(Some(analyses), project_summary, &output_path).await?;
-                info!("分析完成！Markdown 報告已寫入 {}", output_path);
+            match format {
+                OutputFormat::Json => {
+                    report::save(&output_path, &mut project_analysis)?;
+                    info!("分析完成！JSON 報告已寫入 {}", output_path);
+                }
+                OutputFormat::Html => {
+                    html::generate_html_report(&project_analysis, &catalog, &output_path).await?;
+                    info!("分析完成！HTML 報告已寫入 {}", output_path);
+                }
+                OutputFormat::Mdbook => {
+                    book::generate_book(&project_analysis, &catalog, &output_path).await?;
+                    info!("分析完成！mdBook 已寫入 {}", output_path);
+                }
+                OutputFormat::Markdown => {
+                    markdown::generate_markdown_report(&project_analysis, &catalog, args.template.as_deref(), &output_path).await?;
+                    info!("分析完成！Markdown 報告已寫入 {}", output_path);
+                }
+            }
+            if let Some(dir) = &args.dot {
+                diagrams::build(&project_analysis).write_dot(std::path::Path::new(dir))?;
             }
         }
     }
     
     Ok(())
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_masks_api_keys() {
+        let secret = "sk-proj-0123456789abcdef";
+        let args = Args::parse_from(["rs2know", "--api-key", secret]);
+        let debug = format!("{:?}", args);
+        assert!(!debug.contains(secret), "{}", debug);
+        assert!(debug.contains(&config::mask_secret(secret)), "{}", debug);
+
+        let args = Args::parse_from(["rs2know", "config", "set", "profiles.cloud.api_key", secret]);
+        let debug = format!("{:?}", args);
+        assert!(!debug.contains(secret), "{}", debug);
+        let args = Args::parse_from(["rs2know", "config", "set", "model", "gpt-4o"]);
+        assert!(format!("{:?}", args).contains("gpt-4o"));
+    }
+}
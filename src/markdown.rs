@@ -1,196 +1,382 @@
-use anyhow::Result;
-use tracing::info;
-use std::collections::HashMap;
+use anyhow::{Result, anyhow};
+use serde::Serialize;
+use serde_json::Value;
+use tera::{Context, Tera};
+use tracing::{info, warn};
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
-use crate::models::{ProjectAnalysis, ProjectSummary, FileAnalysis};
+use crate::locale::Catalog;
+use crate::{diagrams, slug};
+use crate::models::{ComplexityMetrics, ProjectAnalysis, FileAnalysis, ItemInfo, ItemKind, ModuleSummary};
 
+/// 報告開頭列出的高複雜度函數數量
+pub const WORST_FUNCTIONS_LIMIT: usize = 20;
+
+/// 預設的報告範本
+const DEFAULT_TEMPLATE: &str = include_str!("../templates/report.md.tera");
+/// 各範本共用的巨集
+const MACROS: &str = include_str!("../templates/macros.md.tera");
+
+/// 以 Tera 範本產生 Markdown 報告。未指定 `template` 時使用內建的預設範本，
+/// 標題與標籤來自 `catalog`。
 pub async fn generate_markdown_report(
-    analyses: Option<Vec<FileAnalysis>>, 
-    project_summary: Option<ProjectSummary>,
-    output_path: &str
+    report: &ProjectAnalysis,
+    catalog: &Catalog,
+    template: Option<&str>,
+    output_path: &str,
 ) -> Result<()> {
-    let mut md_content = String::new();
-    md_content.push_str("# Rust 程式碼分析報告\n\n");
-    
-    // Add project summary if available
-    if let Some(summary) = project_summary {
-        md_content.push_str("## 專案總結\n\n");
-        md_content.push_str("### 基本資訊\n\n");
-        md_content.push_str(&format!("- 總檔案數：{}\n", summary.total_files));
-        md_content.push_str(&format!("- 總程式碼行數：{}\n\n", summary.total_loc));
-        
-        if !summary.main_features.is_empty() {
-            md_content.push_str("### 主要功能\n\n");
-            for feature in &summary.main_features {
-                md_content.push_str(&format!("- {}\n", feature));
-            }
-            md_content.push_str("\n");
-        }
-        
-        if !summary.code_architecture.is_empty() {
-            md_content.push_str("### 程式架構\n\n");
-            md_content.push_str(&format!("{}\n\n", summary.code_architecture));
-        }
-        
-        if !summary.key_components.is_empty() {
-            md_content.push_str("### 關鍵元件\n\n");
-            for component in &summary.key_components {
-                md_content.push_str(&format!("- {}\n", component));
-            }
-            md_content.push_str("\n");
-        }
-        
-        if !summary.tech_stack.is_empty() {
-            md_content.push_str("### 技術堆疊\n\n");
-            for tech in &summary.tech_stack {
-                md_content.push_str(&format!("- {}\n", tech));
-            }
-            md_content.push_str("\n");
-        }
-        
-        if !summary.recommendations.is_empty() {
-            md_content.push_str("### 改進建議\n\n");
-            for rec in &summary.recommendations {
-                md_content.push_str(&format!("- {}\n", rec));
-            }
-            md_content.push_str("\n");
-        }
-        
-        md_content.push_str("---\n\n");
+    let source = match template {
+        Some(path) => std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("無法讀取範本：{} - {}", path, e))?,
+        None => DEFAULT_TEMPLATE.to_string(),
+    };
+    let md_content = render_report(report, catalog, &source)
+        .map_err(|e| anyhow!("無法套用範本 {}：{}", template.unwrap_or("（預設）"), error_chain(&e)))?;
+    for anchor in slug::broken_links(&md_content) {
+        warn!("報告中的連結 #{} 找不到對應的標題", anchor);
     }
 
-    // Add file analyses if available
-    if let Some(analyses) = analyses {
-        // 按目錄組織文件
-        let mut dir_files: HashMap<String, Vec<&FileAnalysis>> = HashMap::new();
-        
-        for analysis in &analyses {
-            let parent = Path::new(&analysis.file_path)
-                .parent()
-                .and_then(|p| p.to_str())
-                .unwrap_or("root")
-                .to_string();
-            dir_files.entry(parent.clone()).or_default().push(analysis);
+    // Write to file
+    std::fs::write(output_path, md_content)?;
+    info!("Markdown 報告已生成並寫入 {}", output_path);
+
+    Ok(())
+}
+
+/// 載入共用的巨集與 `templates` 中的範本，並註冊報告使用的篩選器
+pub(crate) fn engine(templates: &[(&str, &str)]) -> tera::Result<Tera> {
+    let mut tera = Tera::default();
+    tera.register_filter("escape_cell", escape_cell_filter);
+    tera.register_filter("item_kind", item_kind_filter);
+    tera.register_filter("file_name", file_name_filter);
+    tera.add_raw_template("macros.md.tera", MACROS)?;
+    tera.add_raw_templates(templates.iter().copied())?;
+    Ok(tera)
+}
+
+/// 範本共用的變數：完整的報告、語言與複雜度最高的函數
+pub(crate) fn base_context(report: &ProjectAnalysis, catalog: &Catalog) -> tera::Result<Context> {
+    let mut context = Context::from_serialize(report)?;
+    context.insert("lang", &catalog.code);
+    context.insert("t", &catalog.report);
+    let worst = worst_functions(&report.file_analyses, WORST_FUNCTIONS_LIMIT);
+    context.insert("worst_functions", &function_rows(&worst));
+    context.insert("diagrams", &diagrams::build(report).mermaid());
+    Ok(context)
+}
+
+fn render_report(report: &ProjectAnalysis, catalog: &Catalog, source: &str) -> tera::Result<String> {
+    let tera = engine(&[("report.md", source)])?;
+    let mut context = base_context(report, catalog)?;
+    let mut directories = directories(report, catalog);
+    context.insert("directories", &directories);
+
+    // 標題的錨點取決於整份文件中先出現的同名標題，因此先產生一次文件，
+    // 取得各目錄與檔案標題實際的錨點後再重新產生
+    let draft = tera.render("report.md", &context)?;
+    resolve_anchors(&mut directories, &draft);
+    context.insert("directories", &directories);
+    tera.render("report.md", &context)
+}
+
+/// 依文件順序找出各目錄與檔案的標題，以其實際的錨點取代預設值。
+/// 找不到標題（例如自訂範本使用不同的標題文字）時保留預設值。
+fn resolve_anchors(directories: &mut [DirectoryContext<'_>], markdown: &str) {
+    let headings = slug::headings(markdown);
+    let mut position = 0;
+    let mut resolve = |text: &str, anchor: &mut String| {
+        if let Some(offset) = headings[position..].iter().position(|h| h.text == text) {
+            *anchor = headings[position + offset].id.clone();
+            position += offset + 1;
         }
-        
-        // 生成目錄
-        md_content.push_str("## 目錄\n\n");
-        
-        // 為每個目錄生成目錄項
-        let mut sorted_dirs: Vec<String> = dir_files.keys().cloned().collect();
-        sorted_dirs.sort();
-        
-        for dir in &sorted_dirs {
-            let safe_dir = if dir.as_str() == "root" {
-                "根目錄".to_string()
-            } else {
-                dir.replace('/', "-")
-            };
-            md_content.push_str(&format!("- [{}](#{})\n", dir, safe_dir));
-            
-            if let Some(files) = dir_files.get(dir) {
-                for analysis in files {
-                    let file_name = Path::new(&analysis.file_path)
-                        .file_name()
-                        .and_then(|f| f.to_str())
-                        .unwrap_or(&analysis.file_path);
-                    let safe_file = file_name.replace('.', "-");
-                    md_content.push_str(&format!("  - [{}](#{})\n", file_name, safe_file));
-                }
-            }
+    };
+    for dir in directories {
+        resolve(&dir.name, &mut dir.anchor);
+        for file in &mut dir.files {
+            resolve(file.name, &mut file.anchor);
         }
-        md_content.push_str("\n---\n\n");
-        
-        // 按目錄生成內容
-        for dir in &sorted_dirs {
-            if let Some(files) = dir_files.get(dir) {
-                let display_dir = if dir.as_str() == "root" {
-                    "根目錄".to_string()
-                } else {
-                    dir.clone()
-                };
-                md_content.push_str(&format!("## {}\n\n", display_dir));
-                
-                for analysis in files {
-                    let file_name = Path::new(&analysis.file_path)
+    }
+}
+
+/// Tera 的錯誤訊息分散在 `source()` 中，逐層串接以便找出範本的問題
+pub(crate) fn error_chain(error: &tera::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = std::error::Error::source(error);
+    while let Some(e) = source {
+        message.push_str(&format!("：{}", e));
+        source = e.source();
+    }
+    message
+}
+
+/// 範本中 `directories` 的一個目錄
+#[derive(Serialize)]
+pub(crate) struct DirectoryContext<'a> {
+    /// 原始的目錄路徑，專案根目錄為空字串
+    pub key: String,
+    /// 顯示用的名稱
+    pub name: String,
+    /// 標題的錨點，與 GitHub 產生的相同
+    anchor: String,
+    /// 這個目錄的模組總結
+    module: Option<&'a ModuleSummary>,
+    pub files: Vec<FileContext<'a>>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct FileContext<'a> {
+    pub name: &'a str,
+    anchor: String,
+    analysis: &'a FileAnalysis,
+    functions: Vec<FunctionRow<'a>>,
+}
+
+/// 複雜度表格的一列
+#[derive(Serialize)]
+struct FunctionRow<'a> {
+    name: String,
+    file: &'a str,
+    line: usize,
+    cognitive: u32,
+    cyclomatic: u32,
+    max_nesting: u32,
+    parameters: u32,
+}
+
+/// 按目錄組織文件，目錄依名稱排序，目錄內保持報告中的順序
+pub(crate) fn directories<'a>(report: &'a ProjectAnalysis, catalog: &Catalog) -> Vec<DirectoryContext<'a>> {
+    let root_dir = catalog.report.get("root_dir").map_or("root", String::as_str);
+    let mut dir_files: BTreeMap<String, Vec<&FileAnalysis>> = BTreeMap::new();
+    for analysis in &report.file_analyses {
+        let parent = Path::new(&analysis.file_path)
+            .parent()
+            .and_then(|p| p.to_str())
+            .unwrap_or_default()
+            .to_string();
+        dir_files.entry(parent).or_default().push(analysis);
+    }
+
+    dir_files
+        .into_iter()
+        .map(|(dir, files)| {
+            let name = if dir.is_empty() { root_dir.to_string() } else { dir.clone() };
+            let anchor = slug::slug(&name);
+            // 模組總結以 `.` 表示專案根目錄
+            let module_path = if dir.is_empty() { "." } else { dir.as_str() };
+            let module = report.modules.iter().find(|m| m.path == module_path);
+            let files = files
+                .into_iter()
+                .map(|analysis| {
+                    let name = Path::new(&analysis.file_path)
                         .file_name()
                         .and_then(|f| f.to_str())
                         .unwrap_or(&analysis.file_path);
-                    
-                    md_content.push_str(&format!("### {}\n\n", file_name));
-                    
-                    if let Some(ai) = &analysis.ai_analysis {
-                        if !ai.main_functions.is_empty() {
-                            md_content.push_str("#### 主要函數\n\n");
-                            for func in &ai.main_functions {
-                                md_content.push_str(&format!("- {}\n", func));
-                            }
-                            md_content.push_str("\n");
-                        }
-                        
-                        if !ai.core_structs.is_empty() {
-                            md_content.push_str("#### 核心結構體\n\n");
-                            for struct_info in &ai.core_structs {
-                                md_content.push_str(&format!("- **{}**：{}\n", struct_info.name, struct_info.description));
-                            }
-                            md_content.push_str("\n");
-                        }
-                        
-                        if !ai.error_types.is_empty() {
-                            md_content.push_str("#### 錯誤類型\n\n");
-                            for error in &ai.error_types {
-                                md_content.push_str(&format!("- {}\n", error));
-                            }
-                            md_content.push_str("\n");
-                        }
-                        
-                        if !ai.functions_details.is_empty() {
-                            md_content.push_str("#### 函數詳情\n\n");
-                            for func in &ai.functions_details {
-                                md_content.push_str(&format!("##### {}\n\n", func.name));
-                                md_content.push_str(&format!("- 說明：{}\n", func.description));
-                                if !func.parameters.is_empty() {
-                                    md_content.push_str("- 參數：\n");
-                                    for param in &func.parameters {
-                                        md_content.push_str(&format!("  - {}\n", param));
-                                    }
-                                }
-                                md_content.push_str(&format!("- 返回類型：{}\n", func.return_type));
-                                md_content.push_str(&format!("- 複雜度：{}\n\n", func.complexity));
-                            }
-                        }
-                        
-                        md_content.push_str("#### 程式碼複雜度\n\n");
-                        md_content.push_str(&format!("{}\n\n", ai.code_complexity));
+                    FileContext {
+                        name,
+                        anchor: slug::slug(name),
+                        analysis,
+                        functions: function_rows(&worst_functions(std::slice::from_ref(analysis), usize::MAX)),
                     }
-                    
-                    md_content.push_str("---\n\n");
-                }
-            }
+                })
+                .collect();
+            DirectoryContext { key: dir, name, anchor, module, files }
+        })
+        .collect()
+}
+
+fn function_rows<'a>(functions: &[(&'a FileAnalysis, &'a ItemInfo, ComplexityMetrics)]) -> Vec<FunctionRow<'a>> {
+    functions
+        .iter()
+        .map(|(analysis, item, metrics)| FunctionRow {
+            name: match &item.parent {
+                Some(parent) => format!("{}::{}", parent, item.name),
+                None => item.name.clone(),
+            },
+            file: &analysis.file_path,
+            line: item.start_line,
+            cognitive: metrics.cognitive,
+            cyclomatic: metrics.cyclomatic,
+            max_nesting: metrics.max_nesting,
+            parameters: metrics.parameters,
+        })
+        .collect()
+}
+
+/// 收集具有複雜度指標的函數，依認知複雜度、循環複雜度由高至低排序
+pub fn worst_functions(analyses: &[FileAnalysis], limit: usize) -> Vec<(&FileAnalysis, &ItemInfo, ComplexityMetrics)> {
+    let mut functions: Vec<_> = analyses
+        .iter()
+        .flat_map(|analysis| {
+            analysis.items.iter().filter_map(move |item| item.complexity.map(|c| (analysis, item, c)))
+        })
+        .collect();
+    functions.sort_by(|a, b| {
+        b.2.cognitive.cmp(&a.2.cognitive)
+            .then(b.2.cyclomatic.cmp(&a.2.cyclomatic))
+            .then(a.0.file_path.cmp(&b.0.file_path))
+            .then(a.1.start_line.cmp(&b.1.start_line))
+    });
+    functions.truncate(limit);
+    functions
+}
+
+/// 跳脫 Markdown 表格儲存格中的 `|`
+fn escape_cell_filter(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
+    let text = tera::try_get_value!("escape_cell", "value", String, value);
+    Ok(Value::String(text.replace('|', "\\|")))
+}
+
+/// 項目種類的顯示名稱，例如 `function` -> `fn`
+fn item_kind_filter(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
+    let kind = tera::try_get_value!("item_kind", "value", ItemKind, value);
+    Ok(Value::String(kind.as_str().to_string()))
+}
+
+/// 路徑的檔名部分
+fn file_name_filter(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
+    let path = tera::try_get_value!("file_name", "value", String, value);
+    let name = Path::new(&path).file_name().and_then(|f| f.to_str()).unwrap_or(&path);
+    Ok(Value::String(name.to_string()))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::models::{CrateOverview, CrateSummary, ModuleOverview, ProjectSummary, ReportMetadata};
+
+    fn file(path: &str) -> FileAnalysis {
+        FileAnalysis {
+            file_path: path.to_string(),
+            loc: 3,
+            blank_lines: 0,
+            comment_lines: 0,
+            code_lines: 3,
+            doc_comment_lines: 0,
+            inner_doc_lines: 0,
+            attribute_lines: 0,
+            items: vec![ItemInfo {
+                kind: ItemKind::Function,
+                name: "run".to_string(),
+                parent: None,
+                visibility: "pub".to_string(),
+                signature: "pub fn run()".to_string(),
+                generics: String::new(),
+                start_line: 1,
+                end_line: 3,
+                doc: None,
+                trait_name: None,
+                complexity: Some(ComplexityMetrics { cyclomatic: 2, cognitive: 1, max_nesting: 1, parameters: 0 }),
+            }],
+            imports: Some(Vec::new()),
+            ai_analysis: None,
+            ai_stale: false,
+            analyzed_at: None,
+            model: None,
         }
     }
 
-    // Write to file
-    std::fs::write(output_path, md_content)?;
-    info!("Markdown 報告已生成並寫入 {}", output_path);
+    fn module(path: &str) -> ModuleSummary {
+        ModuleSummary {
+            path: path.to_string(),
+            crate_name: "contents".to_string(),
+            total_files: 1,
+            total_loc: 3,
+            overview: ModuleOverview { purpose: format!("{} 的用途", path), ..Default::default() },
+        }
+    }
 
-    Ok(())
-}
+    /// 重複的檔名、與章節標題同名的目錄與 crate、中日韓文字、標點符號與多層巢狀的模組
+    pub(crate) fn sample_report() -> ProjectAnalysis {
+        let paths = [
+            "main.rs",
+            "contents/mod.rs",
+            "src/main.rs",
+            "src/a/mod.rs",
+            "src/a/b/mod.rs",
+            "src/a/b/c/mod.rs",
+            "src/b/mod.rs",
+            "src/模組/檔案.rs",
+            "src/模組/子模組/檔案.rs",
+            "src/a.b (c)/x-y_z.rs",
+            "src/a.b (c)/main.rs",
+        ];
+        ProjectAnalysis {
+            schema_version: crate::report::SCHEMA_VERSION,
+            metadata: ReportMetadata::default(),
+            summary: ProjectSummary { total_files: paths.len(), total_loc: 3 * paths.len(), ..Default::default() },
+            crates: vec![CrateSummary {
+                name: "contents".to_string(),
+                path: ".".to_string(),
+                dependencies: Vec::new(),
+                total_files: paths.len(),
+                total_loc: 3 * paths.len(),
+                overview: CrateOverview::default(),
+            }],
+            modules: [".", "src/a", "src/a/b", "src/a/b/c", "src/模組"].into_iter().map(module).collect(),
+            file_analyses: paths.into_iter().map(file).collect(),
+        }
+    }
+
+    /// 目錄中的 `- [名稱](#錨點)` 連結
+    fn toc_links<'m>(markdown: &'m str, contents: &str) -> Vec<(&'m str, &'m str)> {
+        let start = markdown.find(&format!("## {}\n", contents)).expect("找不到目錄");
+        markdown[start..]
+            .lines()
+            .skip(1)
+            .take_while(|line| *line != "---")
+            .filter_map(|line| {
+                let rest = line.trim_start().strip_prefix("- [")?;
+                let (name, rest) = rest.split_once("](#")?;
+                Some((name, rest.strip_suffix(')')?))
+            })
+            .collect()
+    }
 
-pub async fn generate_md_from_json(report_path: &str, output_path: Option<&str>) -> Result<()> {
-    // 讀取 JSON 報告
-    let report_content = std::fs::read_to_string(report_path)?;
-    let project_analysis: ProjectAnalysis = serde_json::from_str(&report_content)?;
+    fn assert_links_resolve(language: &str) {
+        let catalog = Catalog::load(language).unwrap();
+        let report = sample_report();
+        let markdown = render_report(&report, &catalog, DEFAULT_TEMPLATE).unwrap();
+        assert!(slug::broken_links(&markdown).is_empty(), "{:?}", slug::broken_links(&markdown));
 
-    // 決定輸出路徑
-    let output = match output_path {
-        Some(path) => path.to_string(),
-        None => "analysis_report.md".to_string(),
-    };
+        let headings = slug::headings(&markdown);
+        let ids: Vec<&str> = headings.iter().map(|h| h.id.as_str()).collect();
+        let mut unique = ids.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), ids.len(), "錨點重複：{:?}", ids);
+
+        let links = toc_links(&markdown, &catalog.report["contents"]);
+        assert_eq!(links.len(), directories(&report, &catalog).len() + report.file_analyses.len());
+        // 每個連結都指向同名的標題，且依目錄的順序指向各自目錄下的標題
+        let mut last = 0;
+        for (name, anchor) in links {
+            let index = headings.iter().position(|h| h.id == anchor).unwrap_or_else(|| panic!("#{} 不存在", anchor));
+            assert_eq!(headings[index].text, name, "#{}", anchor);
+            assert!(index > last, "#{} 指向目錄之前的標題", anchor);
+            last = index;
+        }
+    }
 
-    generate_markdown_report(
-        Some(project_analysis.file_analyses),
-        Some(project_analysis.summary),
-        &output
-    ).await
+    #[test]
+    fn toc_links_resolve_in_every_language() {
+        for language in crate::locale::available() {
+            assert_links_resolve(&language);
+        }
+    }
+
+    #[test]
+    fn nested_modules_get_their_own_sections() {
+        let catalog = Catalog::load("en").unwrap();
+        let report = sample_report();
+        let dirs = directories(&report, &catalog);
+        let keys: Vec<&str> = dirs.iter().map(|d| d.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            ["", "contents", "src", "src/a", "src/a.b (c)", "src/a/b", "src/a/b/c", "src/b", "src/模組", "src/模組/子模組"]
+        );
+        let module = |key: &str| dirs.iter().find(|d| d.key == key).and_then(|d| d.module).map(|m| m.path.as_str());
+        assert_eq!(module(""), Some("."));
+        assert_eq!(module("src/a/b/c"), Some("src/a/b/c"));
+        assert_eq!(module("src/b"), None);
+    }
 }
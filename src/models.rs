@@ -1,48 +1,152 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+/// 單一檔案的行數統計。
+///
+/// `loc = blank_lines + comment_lines + code_lines`；文件註解行數包含在
+/// `comment_lines` 中，屬性行數則包含在 `code_lines` 中。
 #[derive(Debug)]
 pub struct CodeStats {
     pub loc: usize,
     pub blank_lines: usize,
     pub comment_lines: usize,
     pub code_lines: usize,
+    /// `///` 與 `/** */` 外部文件註解行數
+    pub doc_comment_lines: usize,
+    /// `//!` 與 `/*! */` 內部文件註解行數
+    pub inner_doc_lines: usize,
+    /// 僅包含 `#[...]` / `#![...]` 屬性的行數
+    pub attribute_lines: usize,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct FileAnalysis {
     pub file_path: String,
     pub loc: usize,
     pub blank_lines: usize,
     pub comment_lines: usize,
     pub code_lines: usize,
+    #[serde(default)]
+    pub doc_comment_lines: usize,
+    #[serde(default)]
+    pub inner_doc_lines: usize,
+    #[serde(default)]
+    pub attribute_lines: usize,
+    /// 本地擷取的項目清單，不依賴 AI
+    #[serde(default)]
+    pub items: Vec<ItemInfo>,
+    /// 最外層 `use` 宣告展開後的路徑，用於計算模組之間的依賴；
+    /// `None` 表示報告由未記錄此欄位的版本產生，`update` 時會重新擷取
+    #[serde(default)]
+    pub imports: Option<Vec<String>>,
     pub ai_analysis: Option<AIAnalysis>,
+    /// `ai_analysis` 是檔案修改前的分析：`update` 時未進行 AI 分析或重新分析失敗
+    #[serde(default)]
+    pub ai_stale: bool,
+    /// 分析此檔案的時間
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub analyzed_at: Option<DateTime<Utc>>,
+    /// 產生 `ai_analysis` 的模型
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemKind {
+    Function,
+    Struct,
+    Enum,
+    Union,
+    Trait,
+    Impl,
+    Const,
+    Static,
+    Type,
+    Macro,
+}
+
+impl ItemKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ItemKind::Function => "fn",
+            ItemKind::Struct => "struct",
+            ItemKind::Enum => "enum",
+            ItemKind::Union => "union",
+            ItemKind::Trait => "trait",
+            ItemKind::Impl => "impl",
+            ItemKind::Const => "const",
+            ItemKind::Static => "static",
+            ItemKind::Type => "type",
+            ItemKind::Macro => "macro_rules!",
+        }
+    }
+}
+
+/// 原始碼中的單一項目
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ItemInfo {
+    pub kind: ItemKind,
+    /// 項目名稱；impl 區塊為實作的型別
+    pub name: String,
+    /// 所屬的 impl 型別、trait 或內嵌模組路徑
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    /// `pub`、`pub(crate)` 等，未標示時為 `private`；trait 的成員沿用 trait 的可見性，
+    /// `#[macro_export]` 的巨集為 `pub`
+    pub visibility: String,
+    pub signature: String,
+    #[serde(default)]
+    pub generics: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub doc: Option<String>,
+    /// impl 區塊所實作的 trait
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trait_name: Option<String>,
+    /// 具有函數本體的函數才會有複雜度指標
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub complexity: Option<ComplexityMetrics>,
+}
+
+/// 由語法樹計算的函數複雜度指標
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+pub struct ComplexityMetrics {
+    pub cyclomatic: u32,
+    pub cognitive: u32,
+    pub max_nesting: u32,
+    pub parameters: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct AIAnalysis {
     pub main_functions: Vec<String>,
     pub core_structs: Vec<CoreStruct>,
     pub error_types: Vec<String>,
     pub functions_details: Vec<FunctionDetail>,
+    /// AI 對整體複雜度的文字評估；數值指標見 `ItemInfo::complexity`
+    #[serde(default)]
     pub code_complexity: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct CoreStruct {
     pub name: String,
     pub description: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct FunctionDetail {
     pub name: String,
     pub description: String,
     pub parameters: Vec<String>,
     pub return_type: String,
+    #[serde(default)]
     pub complexity: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct ProjectSummary {
     pub total_files: usize,
     pub total_loc: usize,
@@ -53,8 +157,92 @@ pub struct ProjectSummary {
     pub recommendations: Vec<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// AI 對單一模組（目錄）的總結
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ModuleOverview {
+    pub purpose: String,
+    pub responsibilities: Vec<String>,
+    pub key_components: Vec<String>,
+}
+
+/// 單一目錄的總結；未進行 AI 分析時只有統計數字
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ModuleSummary {
+    /// 相對於專案根目錄的目錄路徑，根目錄為 `.`
+    pub path: String,
+    /// 所屬 crate 的名稱
+    pub crate_name: String,
+    pub total_files: usize,
+    pub total_loc: usize,
+    #[serde(flatten)]
+    pub overview: ModuleOverview,
+}
+
+/// AI 對單一 crate 的總結
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct CrateOverview {
+    pub purpose: String,
+    pub main_features: Vec<String>,
+    pub code_architecture: String,
+    pub key_components: Vec<String>,
+}
+
+/// 單一 crate 的總結；未進行 AI 分析時只有統計數字
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CrateSummary {
+    pub name: String,
+    /// `Cargo.toml` 所在目錄，相對於專案根目錄，根目錄為 `.`
+    pub path: String,
+    /// `[dependencies]`（包含各平台的 `[target.*.dependencies]`）中的套件名稱，依名稱排序
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+    pub total_files: usize,
+    pub total_loc: usize,
+    #[serde(flatten)]
+    pub overview: CrateOverview,
+}
+
+/// 報告的產生資訊
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ReportMetadata {
+    /// 產生報告的 rs2know 版本
+    #[serde(default)]
+    pub generator_version: String,
+    /// 未進行 AI 分析時為空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// 所有提示詞範本的 SHA-256
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_hash: Option<String>,
+    /// AI 分析使用的語言目錄，例如 `zh-TW`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// 產生報告時 HEAD 指向的提交，`update` 以此為比對基準
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_commit: Option<String>,
+    /// 產生報告時工作目錄是否有尚未提交的變更
+    #[serde(default)]
+    pub git_dirty: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ProjectAnalysis {
+    /// 報告格式版本，見 `report::SCHEMA_VERSION`
+    #[serde(default)]
+    pub schema_version: u32,
+    #[serde(default)]
+    pub metadata: ReportMetadata,
+    /// 工作區層級的總結
     pub summary: ProjectSummary,
+    #[serde(default)]
+    pub crates: Vec<CrateSummary>,
+    #[serde(default)]
+    pub modules: Vec<ModuleSummary>,
     pub file_analyses: Vec<FileAnalysis>,
 }
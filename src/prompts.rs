@@ -0,0 +1,151 @@
+//! AI 請求使用的提示詞範本。
+//!
+//! 範本的內容來自語言目錄（見 [`crate::locale`]）。範本中的 `{name}` 由 [`render`] 代入；
+//! 其餘的大括號（例如 JSON 範例）保持原樣。報告會記錄所有範本的雜湊值，方便辨識不同
+//! 版本的提示詞所產生的結果。
+
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Prompts {
+    pub analysis_system: String,
+    /// 檔案分析，變數：`{context}`、`{code}`
+    pub analysis: String,
+    /// 切塊分析時說明目前的區塊，變數：`{file}`、`{index}`、`{count}`、`{start}`、`{end}`
+    pub chunk_context: String,
+    pub summary_system: String,
+    /// 變數：`{crate}`、`{path}`、`{part}`、`{digest}`
+    pub module_summary: String,
+    /// 變數：`{crate}`、`{part}`、`{digest}`
+    pub crate_summary: String,
+    /// 變數：`{part}`、`{digest}`
+    pub workspace_summary: String,
+    /// 分批總結時說明目前的部分，變數：`{index}`、`{count}`
+    pub part_note: String,
+    /// 回應無效時重新請求，變數：`{prompt}`、`{feedback}`
+    pub feedback: String,
+}
+
+impl Prompts {
+    /// 所有範本，依固定順序排列以計算雜湊值
+    pub fn all(&self) -> [&str; 9] {
+        [
+            &self.analysis_system,
+            &self.analysis,
+            &self.chunk_context,
+            &self.summary_system,
+            &self.module_summary,
+            &self.crate_summary,
+            &self.workspace_summary,
+            &self.part_note,
+            &self.feedback,
+        ]
+    }
+}
+
+/// 總結請求中，檔案、模組與 crate 摘要所用的標題與標籤
+#[derive(Debug, Clone, Deserialize)]
+pub struct DigestLabels {
+    /// 標籤與內容之間的分隔
+    pub colon: String,
+    /// 清單項目之間的分隔
+    pub list_separator: String,
+    /// 檔案標題，變數：`{path}`、`{loc}`
+    pub file: String,
+    /// 模組標題，變數：`{path}`、`{files}`、`{loc}`
+    pub module: String,
+    /// crate 標題，變數：`{name}`、`{path}`、`{files}`、`{loc}`
+    #[serde(rename = "crate")]
+    pub krate: String,
+    /// 變數：`{name}`、`{description}`
+    pub core_struct: String,
+    pub main_functions: String,
+    pub error_types: String,
+    pub purpose: String,
+    pub responsibilities: String,
+    pub key_components: String,
+    pub architecture: String,
+    pub main_features: String,
+    pub tech_stack: String,
+    pub recommendations: String,
+}
+
+impl DigestLabels {
+    /// 所有標籤，依固定順序排列以計算雜湊值
+    pub fn all(&self) -> [&str; 15] {
+        [
+            &self.colon,
+            &self.list_separator,
+            &self.file,
+            &self.module,
+            &self.krate,
+            &self.core_struct,
+            &self.main_functions,
+            &self.error_types,
+            &self.purpose,
+            &self.responsibilities,
+            &self.key_components,
+            &self.architecture,
+            &self.main_features,
+            &self.tech_stack,
+            &self.recommendations,
+        ]
+    }
+}
+
+/// 代入範本中的 `{name}`。只掃描一次，代入的內容中即使含有 `{name}` 也不會再被取代。
+pub fn render(template: &str, vars: &[(&str, &str)]) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let value = after.find('}').and_then(|end| {
+            let name = &after[..end];
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| (*value, end))
+        });
+        match value {
+            Some((value, end)) => {
+                result.push_str(value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                result.push('{');
+                rest = after;
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_placeholders_are_kept() {
+        assert_eq!(render("{name} {missing} {", &[("name", "x")]), "x {missing} {");
+        assert_eq!(render("{}", &[("", "empty")]), "empty");
+        assert_eq!(render("no placeholders", &[("name", "x")]), "no placeholders");
+    }
+
+    #[test]
+    fn json_braces_are_untouched() {
+        let template = "回傳 JSON：{\"file\": \"{file_path}\", \"items\": [{\"name\": \"...\"}]}";
+        assert_eq!(
+            render(template, &[("file_path", "src/lib.rs")]),
+            "回傳 JSON：{\"file\": \"src/lib.rs\", \"items\": [{\"name\": \"...\"}]}"
+        );
+        assert_eq!(render("{{code}}", &[("code", "fn f() {}")]), "{fn f() {}}");
+    }
+
+    #[test]
+    fn substituted_values_are_not_rendered_again() {
+        let vars = [("code", "let s = \"{language}\";"), ("language", "en")];
+        assert_eq!(render("{language}: {code}", &vars), "en: let s = \"{language}\";");
+        assert_eq!(render("{code}{code}", &[("code", "{code}")]), "{code}{code}");
+    }
+}
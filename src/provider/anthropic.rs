@@ -0,0 +1,213 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+use std::time::Duration;
+use tracing::{debug, error, info};
+use super::{endpoint, ChatRequest, LlmProvider, ProviderKind};
+
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+/// Messages API 要求必須指定回應的最大 token 數
+const MAX_TOKENS: u32 = 8192;
+
+/// Anthropic Messages API
+pub struct AnthropicProvider {
+    client: Client,
+    api_url: String,
+    api_key: String,
+    model: String,
+}
+
+#[derive(Deserialize)]
+struct MessagesResponse {
+    content: Vec<ContentBlock>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ContentBlock {
+    Text { text: String },
+    ToolUse { input: serde_json::Value },
+    #[serde(other)]
+    Other,
+}
+
+/// Messages API 的錯誤回應，例如 `{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}`
+#[derive(Deserialize)]
+struct ErrorResponse {
+    error: ApiError,
+}
+
+#[derive(Deserialize)]
+struct ApiError {
+    #[serde(rename = "type")]
+    kind: String,
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModelResponse {
+    data: Vec<Model>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Model {
+    id: String,
+}
+
+impl AnthropicProvider {
+    pub fn new(api_url: &str, api_key: &str, model: &str) -> Self {
+        Self {
+            client: Client::new(),
+            api_url: api_url.to_string(),
+            api_key: api_key.to_string(),
+            model: model.to_string(),
+        }
+    }
+
+    fn request(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        builder
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+    }
+}
+
+/// 錯誤回應中的類型與說明；無法解析時回傳原始內容
+fn describe_error(text: &str) -> String {
+    match serde_json::from_str::<ErrorResponse>(text) {
+        Ok(response) => format!("{}：{}", response.error.kind, response.error.message),
+        Err(_) => text.to_string(),
+    }
+}
+
+/// 取出回應內容：工具呼叫的輸入即為結構化結果，優先於文字內容
+fn parse_content(response_text: &str) -> Result<String> {
+    if let Ok(response) = serde_json::from_str::<ErrorResponse>(response_text) {
+        return Err(anyhow!("AI 回應錯誤：{}：{}", response.error.kind, response.error.message));
+    }
+    let messages_resp: MessagesResponse = serde_json::from_str(response_text)
+        .map_err(|e| anyhow!("無法解析 AI 回應的 JSON：{} - 回應：{}", e, response_text))?;
+
+    let mut content = String::new();
+    for block in messages_resp.content {
+        match block {
+            ContentBlock::ToolUse { input } => return Ok(input.to_string()),
+            ContentBlock::Text { text } => content.push_str(&text),
+            ContentBlock::Other => {}
+        }
+    }
+    if content.is_empty() {
+        return Err(anyhow!("AI 未返回任何文字內容"));
+    }
+    Ok(content)
+}
+
+#[async_trait]
+impl LlmProvider for AnthropicProvider {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::Anthropic
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn complete(&self, request: &ChatRequest) -> Result<String> {
+        let endpoint = endpoint(&self.api_url, "messages");
+        info!("發送 API 請求至：{}", endpoint);
+
+        let mut body = serde_json::json!({
+            "model": self.model,
+            "max_tokens": MAX_TOKENS,
+            "system": request.system,
+            "messages": [
+                {
+                    "role": "user",
+                    "content": [
+                        {
+                            "type": "text",
+                            "text": request.prompt
+                        }
+                    ]
+                }
+            ]
+        });
+        if let Some(temperature) = request.temperature {
+            body["temperature"] = serde_json::json!(temperature);
+        }
+        // Messages API 沒有 response_format，以強制呼叫單一工具取得結構化輸出
+        if let Some(schema) = &request.response_schema {
+            body["tools"] = serde_json::json!([{
+                "name": schema.name,
+                "description": "以結構化格式回報分析結果",
+                "input_schema": schema.schema
+            }]);
+            body["tool_choice"] = serde_json::json!({ "type": "tool", "name": schema.name });
+        }
+
+        let mut builder = self.request(self.client.post(&endpoint))
+            .header("Content-Type", "application/json")
+            .json(&body);
+        if let Some(timeout) = request.timeout {
+            builder = builder.timeout(timeout);
+        }
+        let resp = builder.send().await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            error!("API 錯誤回應：{}", text);
+            return Err(anyhow!("AI 回應錯誤：{} - {}", status, describe_error(&text)));
+        }
+
+        let response_text = resp.text().await?;
+        debug!("API 回應：{}", response_text);
+
+        parse_content(&response_text)
+    }
+
+    async fn list_models(&self) -> Result<Vec<String>> {
+        let endpoint = endpoint(&self.api_url, "models");
+        debug!("獲取可用模型列表：{}", endpoint);
+
+        let resp = self.request(self.client.get(&endpoint))
+            .timeout(Duration::from_secs(10))
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            return Err(anyhow!("獲取模型列表失敗：{}", resp.status()));
+        }
+
+        let model_resp: ModelResponse = resp.json().await
+            .map_err(|e| anyhow!("解析模型列表失敗：{}", e))?;
+        Ok(model_resp.data.into_iter().map(|m| m.id).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefers_tool_use_input() {
+        let response = r#"{"content":[{"type":"text","text":"以下是結果"},{"type":"tool_use","id":"t1","name":"analysis","input":{"main_functions":["a"]}}],"stop_reason":"tool_use"}"#;
+        assert_eq!(parse_content(response).unwrap(), r#"{"main_functions":["a"]}"#);
+    }
+
+    #[test]
+    fn joins_text_blocks() {
+        let response = r#"{"content":[{"type":"text","text":"{\"a\":"},{"type":"thinking","thinking":"…"},{"type":"text","text":"1}"}]}"#;
+        assert_eq!(parse_content(response).unwrap(), r#"{"a":1}"#);
+        assert!(parse_content(r#"{"content":[]}"#).is_err());
+    }
+
+    #[test]
+    fn reports_error_bodies() {
+        let body = r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#;
+        let error = parse_content(body).unwrap_err().to_string();
+        assert!(error.contains("overloaded_error：Overloaded"), "{}", error);
+        assert_eq!(describe_error(body), "overloaded_error：Overloaded");
+        assert_eq!(describe_error("Bad Gateway"), "Bad Gateway");
+    }
+}
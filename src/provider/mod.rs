@@ -0,0 +1,201 @@
+//! LLM 服務提供者抽象層。
+//!
+//! `ai` 模組中的分析與總結只透過 [`LlmProvider`] 與模型溝通，各家 API 的
+//! 請求格式、驗證方式與回應解析都封裝在對應的實作中。
+
+mod anthropic;
+mod ollama;
+mod openai;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::warn;
+use crate::config::Config;
+use crate::ratelimit::{RateLimitedProvider, RateLimiter};
+use crate::structured::ResponseSchema;
+
+pub use anthropic::AnthropicProvider;
+pub use ollama::OllamaProvider;
+pub use openai::OpenAiProvider;
+
+/// 支援的服務提供者
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderKind {
+    /// OpenAI 或任何相容 `/chat/completions` 的服務
+    #[default]
+    #[serde(rename = "openai")]
+    #[value(name = "openai")]
+    OpenAi,
+    /// Anthropic Messages API
+    Anthropic,
+    /// 本地 Ollama 伺服器
+    Ollama,
+}
+
+impl ProviderKind {
+    pub const ALL: &'static [ProviderKind] = &[ProviderKind::OpenAi, ProviderKind::Anthropic, ProviderKind::Ollama];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProviderKind::OpenAi => "openai",
+            ProviderKind::Anthropic => "anthropic",
+            ProviderKind::Ollama => "ollama",
+        }
+    }
+
+    pub fn default_url(&self) -> &'static str {
+        match self {
+            ProviderKind::OpenAi => "https://api.openai.com/v1/",
+            ProviderKind::Anthropic => "https://api.anthropic.com/v1/",
+            ProviderKind::Ollama => "http://localhost:11434/",
+        }
+    }
+
+    /// 切換到此提供者且未指定模型時使用的模型
+    pub fn default_model(&self) -> &'static str {
+        match self {
+            ProviderKind::OpenAi => "gpt-4o-mini",
+            ProviderKind::Anthropic => "claude-3-5-haiku-latest",
+            ProviderKind::Ollama => "llama3.1",
+        }
+    }
+
+    /// 無法從 API 取得模型列表時使用的預設清單
+    pub fn default_models(&self) -> &'static [&'static str] {
+        match self {
+            ProviderKind::OpenAi => &["gpt-4o", "gpt-4o-mini", "gpt-4.1", "gpt-4.1-mini"],
+            ProviderKind::Anthropic => &["claude-3-5-sonnet-latest", "claude-3-5-haiku-latest", "claude-3-7-sonnet-latest"],
+            ProviderKind::Ollama => &["llama3.1", "qwen2.5-coder", "deepseek-coder-v2"],
+        }
+    }
+
+    /// 本地服務不需要 API 金鑰
+    pub fn requires_api_key(&self) -> bool {
+        !matches!(self, ProviderKind::Ollama)
+    }
+}
+
+impl std::fmt::Display for ProviderKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 單次對話請求
+#[derive(Debug, Clone)]
+pub struct ChatRequest {
+    pub system: String,
+    pub prompt: String,
+    pub temperature: Option<f32>,
+    pub timeout: Option<Duration>,
+    /// 要求模型輸出符合此 schema 的 JSON
+    pub response_schema: Option<ResponseSchema>,
+}
+
+#[async_trait]
+pub trait LlmProvider: Send + Sync {
+    fn kind(&self) -> ProviderKind;
+
+    fn model(&self) -> &str;
+
+    /// 送出對話並回傳模型的文字回應
+    async fn complete(&self, request: &ChatRequest) -> Result<String>;
+
+    /// 從 API 取得可用的模型列表
+    async fn list_models(&self) -> Result<Vec<String>>;
+}
+
+/// 依設定建立對應的服務提供者，設定了速率限制時加上限速包裝
+pub fn from_config(config: &Config) -> Box<dyn LlmProvider> {
+    let provider: Box<dyn LlmProvider> = match config.provider {
+        ProviderKind::OpenAi => Box::new(OpenAiProvider::new(&config.api_url, &config.api_key, &config.model)),
+        ProviderKind::Anthropic => Box::new(AnthropicProvider::new(&config.api_url, &config.api_key, &config.model)),
+        ProviderKind::Ollama => Box::new(OllamaProvider::new(&config.api_url, &config.model)),
+    };
+    match RateLimiter::new(config.requests_per_minute, config.tokens_per_minute) {
+        Some(limiter) => Box::new(RateLimitedProvider::new(provider, limiter)),
+        None => provider,
+    }
+}
+
+/// 取得可用的模型列表，失敗或為空時回退到預設清單
+pub async fn available_models(provider: &dyn LlmProvider) -> Vec<String> {
+    let defaults = || provider.kind().default_models().iter().map(|&s| s.to_string()).collect();
+    match provider.list_models().await {
+        Ok(models) if !models.is_empty() => models,
+        Ok(_) => {
+            warn!("未找到可用的模型，使用預設列表");
+            defaults()
+        }
+        Err(e) => {
+            warn!("無法獲取模型列表：{}", e);
+            defaults()
+        }
+    }
+}
+
+/// 將基底 URL 與端點路徑組合
+fn endpoint(api_url: &str, path: &str) -> String {
+    format!("{}/{}", api_url.trim_end_matches('/'), path)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use std::sync::{Arc, Mutex};
+
+    /// 測試用的服務提供者：依 schema 名稱回傳固定的結果，並記錄每次請求
+    #[derive(Clone, Default)]
+    pub(crate) struct FakeProvider {
+        /// 每次請求的 schema 名稱與提示詞
+        pub calls: Arc<Mutex<Vec<(String, String)>>>,
+        /// 回傳錯誤而不是結果
+        pub fail: bool,
+        /// 依提示詞決定回應前等待的時間
+        pub delay: Option<fn(&str) -> Duration>,
+    }
+
+    impl FakeProvider {
+        /// 已收到請求的 schema 名稱
+        pub fn schemas(&self) -> Vec<String> {
+            self.calls.lock().unwrap().iter().map(|(schema, _)| schema.clone()).collect()
+        }
+    }
+
+    #[async_trait]
+    impl LlmProvider for FakeProvider {
+        fn kind(&self) -> ProviderKind {
+            ProviderKind::OpenAi
+        }
+
+        fn model(&self) -> &str {
+            "fake"
+        }
+
+        async fn complete(&self, request: &ChatRequest) -> Result<String> {
+            let schema = request.response_schema.as_ref().map(|schema| schema.name).unwrap_or_default();
+            self.calls.lock().unwrap().push((schema.to_string(), request.prompt.clone()));
+            if let Some(delay) = self.delay {
+                tokio::time::sleep(delay(&request.prompt)).await;
+            }
+            if self.fail {
+                return Err(anyhow!("fake provider failure"));
+            }
+            let response = match schema {
+                "ai_analysis" => r#"{"main_functions":["fake"],"core_structs":[],"error_types":[],"functions_details":[],"code_complexity":""}"#,
+                "module_overview" => r#"{"purpose":"fake module","responsibilities":[],"key_components":[]}"#,
+                "crate_overview" => r#"{"purpose":"fake crate","main_features":[],"code_architecture":"fake","key_components":[]}"#,
+                _ => r#"{"total_files":0,"total_loc":0,"main_features":[],"code_architecture":"fake project","key_components":[],"tech_stack":[],"recommendations":[]}"#,
+            };
+            Ok(response.to_string())
+        }
+
+        async fn list_models(&self) -> Result<Vec<String>> {
+            Ok(Vec::new())
+        }
+    }
+}
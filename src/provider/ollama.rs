@@ -0,0 +1,217 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use reqwest::{Client, Response, StatusCode};
+use serde::Deserialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+use super::{endpoint, ChatRequest, LlmProvider, ProviderKind};
+
+/// 本地 Ollama 伺服器，不需要 API 金鑰，程式碼不會離開本機
+///
+/// 以 JSON Schema 作為 `format` 需要 Ollama 0.5 以上；較舊的版本拒絕請求時，
+/// 改以 `"format": "json"` 重送，之後的請求也不再附上 schema。
+pub struct OllamaProvider {
+    client: Client,
+    api_url: String,
+    model: String,
+    schema_unsupported: AtomicBool,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    message: ChatMessage,
+}
+
+#[derive(Deserialize)]
+struct ChatMessage {
+    content: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TagsResponse {
+    models: Vec<LocalModel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LocalModel {
+    name: String,
+}
+
+impl OllamaProvider {
+    pub fn new(api_url: &str, model: &str) -> Self {
+        Self {
+            client: Client::new(),
+            api_url: api_url.to_string(),
+            model: model.to_string(),
+            schema_unsupported: AtomicBool::new(false),
+        }
+    }
+
+    async fn send(&self, endpoint: &str, body: &serde_json::Value) -> Result<Response> {
+        // 本地模型推論時間取決於硬體，不套用請求逾時
+        Ok(self.client
+            .post(endpoint)
+            .header("Content-Type", "application/json")
+            .json(body)
+            .send()
+            .await?)
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OllamaProvider {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::Ollama
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn complete(&self, request: &ChatRequest) -> Result<String> {
+        let endpoint = endpoint(&self.api_url, "api/chat");
+        info!("發送 API 請求至：{}", endpoint);
+
+        let mut body = serde_json::json!({
+            "model": self.model,
+            "stream": false,
+            "messages": [
+                {
+                    "role": "system",
+                    "content": request.system
+                },
+                {
+                    "role": "user",
+                    "content": request.prompt
+                }
+            ]
+        });
+        if let Some(schema) = &request.response_schema {
+            body["format"] = if self.schema_unsupported.load(Ordering::Relaxed) {
+                serde_json::json!("json")
+            } else {
+                schema.schema.clone()
+            };
+        }
+        if let Some(temperature) = request.temperature {
+            body["options"] = serde_json::json!({ "temperature": temperature });
+        }
+
+        let mut resp = self.send(&endpoint, &body).await?;
+        if resp.status() == StatusCode::BAD_REQUEST && body["format"].is_object() {
+            let text = resp.text().await.unwrap_or_default();
+            warn!("Ollama 不接受以 JSON Schema 作為 format（需要 0.5 以上版本），改用 \"json\" 重新請求：{}", text);
+            self.schema_unsupported.store(true, Ordering::Relaxed);
+            body["format"] = serde_json::json!("json");
+            resp = self.send(&endpoint, &body).await?;
+        }
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            error!("API 錯誤回應：{}", text);
+            return Err(anyhow!("AI 回應錯誤：{} - {}", status, text));
+        }
+
+        let response_text = resp.text().await?;
+        debug!("API 回應：{}", response_text);
+
+        let chat_resp: ChatResponse = serde_json::from_str(&response_text)
+            .map_err(|e| anyhow!("無法解析 AI 回應的 JSON：{} - 回應：{}", e, response_text))?;
+
+        Ok(chat_resp.message.content)
+    }
+
+    async fn list_models(&self) -> Result<Vec<String>> {
+        let endpoint = endpoint(&self.api_url, "api/tags");
+        debug!("獲取可用模型列表：{}", endpoint);
+
+        let resp = self.client
+            .get(&endpoint)
+            .timeout(Duration::from_secs(10))
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            return Err(anyhow!("獲取模型列表失敗：{}", resp.status()));
+        }
+
+        let tags: TagsResponse = resp.json().await
+            .map_err(|e| anyhow!("解析模型列表失敗：{}", e))?;
+        Ok(tags.models.into_iter().map(|m| m.name).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structured::ResponseSchema;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// 模擬 Ollama 0.5 以前的伺服器：`format` 只接受字串，並記錄每次收到的 `format`
+    async fn legacy_server() -> (String, Arc<Mutex<Vec<serde_json::Value>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let formats = Arc::new(Mutex::new(Vec::new()));
+        let seen = formats.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buffer = [0; 4096];
+                let body = loop {
+                    let read = socket.read(&mut buffer).await.unwrap();
+                    request.extend_from_slice(&buffer[..read]);
+                    let text = String::from_utf8_lossy(&request);
+                    if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                        let length: usize = head
+                            .lines()
+                            .find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse().unwrap()))
+                            .unwrap_or(0);
+                        if body.len() >= length {
+                            break body.to_string();
+                        }
+                    }
+                };
+                let format = serde_json::from_str::<serde_json::Value>(&body).unwrap()["format"].clone();
+                let (status, reply) = if format.is_object() {
+                    ("400 Bad Request", r#"{"error":"json: cannot unmarshal object into Go struct field ChatRequest.format of type string"}"#)
+                } else {
+                    ("200 OK", r#"{"message":{"role":"assistant","content":"{}"}}"#)
+                };
+                seen.lock().unwrap().push(format);
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    reply.len(),
+                    reply
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        (url, formats)
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_json_format() {
+        let (url, formats) = legacy_server().await;
+        let provider = OllamaProvider::new(&url, "m");
+        let request = ChatRequest {
+            system: String::new(),
+            prompt: String::new(),
+            temperature: None,
+            timeout: None,
+            response_schema: Some(ResponseSchema { name: "t", schema: serde_json::json!({ "type": "object" }) }),
+        };
+        assert_eq!(provider.complete(&request).await.unwrap(), "{}");
+        // 之後的請求直接使用 "json"
+        assert_eq!(provider.complete(&request).await.unwrap(), "{}");
+        assert_eq!(
+            *formats.lock().unwrap(),
+            [serde_json::json!({ "type": "object" }), serde_json::json!("json"), serde_json::json!("json")]
+        );
+    }
+}
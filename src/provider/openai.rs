@@ -0,0 +1,184 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+use std::time::Duration;
+use tracing::{debug, error, info};
+use super::{endpoint, ChatRequest, LlmProvider, ProviderKind};
+
+/// OpenAI 以及相容 `/chat/completions` 格式的服務
+pub struct OpenAiProvider {
+    client: Client,
+    api_url: String,
+    api_key: String,
+    model: String,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    choices: Vec<Choice>,
+}
+
+#[derive(Deserialize)]
+struct Choice {
+    message: ChoiceMessage,
+    #[serde(default)]
+    finish_reason: Option<String>,
+}
+
+/// 模型拒絕回答或被內容過濾時 `content` 為 `null`，拒絕原因放在 `refusal`
+#[derive(Deserialize)]
+struct ChoiceMessage {
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default)]
+    refusal: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModelResponse {
+    data: Vec<Model>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Model {
+    id: String,
+}
+
+impl OpenAiProvider {
+    pub fn new(api_url: &str, api_key: &str, model: &str) -> Self {
+        Self {
+            client: Client::new(),
+            api_url: api_url.to_string(),
+            api_key: api_key.to_string(),
+            model: model.to_string(),
+        }
+    }
+}
+
+/// 取出第一個選項的回應內容；模型拒絕回答或沒有內容時回傳錯誤
+fn parse_content(response_text: &str) -> Result<String> {
+    let chat_resp: ChatResponse = serde_json::from_str(response_text)
+        .map_err(|e| anyhow!("無法解析 AI 回應的 JSON：{} - 回應：{}", e, response_text))?;
+
+    let choice = chat_resp.choices.into_iter().next()
+        .ok_or_else(|| anyhow!("AI 未返回任何選項"))?;
+    if let Some(refusal) = choice.message.refusal {
+        return Err(anyhow!("模型拒絕回應：{}", refusal));
+    }
+    choice.message.content.ok_or_else(|| {
+        anyhow!("AI 回應沒有內容（finish_reason：{}）", choice.finish_reason.as_deref().unwrap_or("未知"))
+    })
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiProvider {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::OpenAi
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn complete(&self, request: &ChatRequest) -> Result<String> {
+        let endpoint = endpoint(&self.api_url, "chat/completions");
+        info!("發送 API 請求至：{}", endpoint);
+
+        let mut body = serde_json::json!({
+            "model": self.model,
+            "messages": [
+                {
+                    "role": "system",
+                    "content": request.system
+                },
+                {
+                    "role": "user",
+                    "content": request.prompt
+                }
+            ]
+        });
+        if let Some(temperature) = request.temperature {
+            body["temperature"] = serde_json::json!(temperature);
+        }
+        if let Some(schema) = &request.response_schema {
+            body["response_format"] = serde_json::json!({
+                "type": "json_schema",
+                "json_schema": {
+                    "name": schema.name,
+                    "schema": schema.schema,
+                    "strict": false
+                }
+            });
+        }
+
+        let mut builder = self.client
+            .post(&endpoint)
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .header("Content-Type", "application/json")
+            .json(&body);
+        if let Some(timeout) = request.timeout {
+            builder = builder.timeout(timeout);
+        }
+        let resp = builder.send().await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            error!("API 錯誤回應：{}", text);
+            return Err(anyhow!("AI 回應錯誤：{} - {}", status, text));
+        }
+
+        let response_text = resp.text().await?;
+        debug!("API 回應：{}", response_text);
+
+        parse_content(&response_text)
+    }
+
+    async fn list_models(&self) -> Result<Vec<String>> {
+        let endpoint = endpoint(&self.api_url, "models");
+        debug!("獲取可用模型列表：{}", endpoint);
+
+        let resp = self.client
+            .get(&endpoint)
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .timeout(Duration::from_secs(10))
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            return Err(anyhow!("獲取模型列表失敗：{}", resp.status()));
+        }
+
+        let model_resp: ModelResponse = resp.json().await
+            .map_err(|e| anyhow!("解析模型列表失敗：{}", e))?;
+        Ok(model_resp.data.into_iter().map(|m| m.id).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn returns_message_content() {
+        let response = r#"{"choices":[{"message":{"role":"assistant","content":"{}"},"finish_reason":"stop"}]}"#;
+        assert_eq!(parse_content(response).unwrap(), "{}");
+    }
+
+    #[test]
+    fn surfaces_refusal() {
+        let response = r#"{"choices":[{"message":{"role":"assistant","content":null,"refusal":"I can't help with that."},"finish_reason":"stop"}]}"#;
+        let error = parse_content(response).unwrap_err().to_string();
+        assert!(error.contains("模型拒絕回應"), "{}", error);
+        assert!(error.contains("I can't help with that."), "{}", error);
+    }
+
+    #[test]
+    fn null_content_is_an_error() {
+        let response = r#"{"choices":[{"message":{"role":"assistant","content":null},"finish_reason":"content_filter"}]}"#;
+        let error = parse_content(response).unwrap_err().to_string();
+        assert!(error.contains("content_filter"), "{}", error);
+        assert!(parse_content(r#"{"choices":[]}"#).is_err());
+    }
+}
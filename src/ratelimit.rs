@@ -0,0 +1,199 @@
+//! 每分鐘請求數（RPM）與每分鐘 token 數（TPM）的令牌桶限速。
+//!
+//! 限速以 [`RateLimitedProvider`] 包裝任意 [`LlmProvider`]，因此分析與總結的
+//! 所有請求都會經過同一組令牌桶，並行數再高也不會超出服務的配額。
+
+use anyhow::Result;
+use async_trait::async_trait;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+use tracing::debug;
+use crate::provider::{ChatRequest, LlmProvider, ProviderKind};
+use crate::tokens::estimate_tokens;
+
+struct TokenBucket {
+    capacity: f64,
+    /// 可為負值，代表超額使用後尚未補回的量
+    available: f64,
+    per_sec: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn per_minute(limit: u32) -> Self {
+        let capacity = f64::from(limit.max(1));
+        Self {
+            capacity,
+            available: capacity,
+            per_sec: capacity / 60.0,
+            updated: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+        self.available = (self.available + elapsed * self.per_sec).min(self.capacity);
+        self.updated = now;
+    }
+
+    /// 嘗試取用 `amount`，不足時回傳需要等待的時間
+    fn try_take(&mut self, amount: f64) -> Option<Duration> {
+        self.refill();
+        // 單次用量超過容量時，只要求桶滿即可，避免永遠等待
+        let amount = amount.min(self.capacity);
+        if self.available >= amount {
+            self.available -= amount;
+            None
+        } else {
+            Some(Duration::from_secs_f64((amount - self.available) / self.per_sec))
+        }
+    }
+
+    fn debit(&mut self, amount: f64) {
+        self.refill();
+        self.available -= amount;
+    }
+}
+
+pub struct RateLimiter {
+    requests: Option<Mutex<TokenBucket>>,
+    tokens: Option<Mutex<TokenBucket>>,
+}
+
+impl RateLimiter {
+    /// 兩項限制都未設定時回傳 `None`
+    pub fn new(requests_per_minute: Option<u32>, tokens_per_minute: Option<u32>) -> Option<Self> {
+        if requests_per_minute.is_none() && tokens_per_minute.is_none() {
+            return None;
+        }
+        Some(Self {
+            requests: requests_per_minute.map(|limit| Mutex::new(TokenBucket::per_minute(limit))),
+            tokens: tokens_per_minute.map(|limit| Mutex::new(TokenBucket::per_minute(limit))),
+        })
+    }
+
+    async fn take(bucket: &Mutex<TokenBucket>, amount: f64) {
+        loop {
+            let wait = bucket.lock().await.try_take(amount);
+            match wait {
+                None => return,
+                Some(wait) => {
+                    debug!("達到速率限制，等待 {:.1} 秒", wait.as_secs_f64());
+                    tokio::time::sleep(wait).await;
+                }
+            }
+        }
+    }
+
+    /// 等待直到可以送出一個預估使用 `tokens` 的請求
+    pub async fn acquire(&self, tokens: usize) {
+        if let Some(bucket) = &self.requests {
+            Self::take(bucket, 1.0).await;
+        }
+        if let Some(bucket) = &self.tokens {
+            Self::take(bucket, tokens as f64).await;
+        }
+    }
+
+    /// 記錄回應消耗的 token，讓後續請求等待補回
+    pub async fn record(&self, tokens: usize) {
+        if let Some(bucket) = &self.tokens {
+            bucket.lock().await.debit(tokens as f64);
+        }
+    }
+}
+
+/// 在請求前後套用速率限制的服務提供者
+pub struct RateLimitedProvider {
+    inner: Box<dyn LlmProvider>,
+    limiter: RateLimiter,
+}
+
+impl RateLimitedProvider {
+    pub fn new(inner: Box<dyn LlmProvider>, limiter: RateLimiter) -> Self {
+        Self { inner, limiter }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for RateLimitedProvider {
+    fn kind(&self) -> ProviderKind {
+        self.inner.kind()
+    }
+
+    fn model(&self) -> &str {
+        self.inner.model()
+    }
+
+    async fn complete(&self, request: &ChatRequest) -> Result<String> {
+        let prompt_tokens = estimate_tokens(&request.system) + estimate_tokens(&request.prompt);
+        self.limiter.acquire(prompt_tokens).await;
+        let response = self.inner.complete(request).await?;
+        self.limiter.record(estimate_tokens(&response)).await;
+        Ok(response)
+    }
+
+    async fn list_models(&self) -> Result<Vec<String>> {
+        self.inner.list_models().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::tests::FakeProvider;
+
+    #[tokio::test(start_paused = true)]
+    async fn bucket_refills_over_time() {
+        let mut bucket = TokenBucket::per_minute(60);
+        for _ in 0..60 {
+            assert_eq!(bucket.try_take(1.0), None);
+        }
+        // 每秒補回一個
+        assert_eq!(bucket.try_take(1.0), Some(Duration::from_secs(1)));
+        assert_eq!(bucket.try_take(30.0), Some(Duration::from_secs(30)));
+        tokio::time::advance(Duration::from_secs(10)).await;
+        assert_eq!(bucket.try_take(10.0), None);
+        assert_eq!(bucket.try_take(1.0), Some(Duration::from_secs(1)));
+        // 補回的量不超過容量
+        tokio::time::advance(Duration::from_secs(600)).await;
+        assert_eq!(bucket.try_take(60.0), None);
+        assert!(bucket.try_take(1.0).is_some());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn oversized_requests_wait_for_a_full_bucket() {
+        let mut bucket = TokenBucket::per_minute(120);
+        assert_eq!(bucket.try_take(60.0), None);
+        // 超過容量的用量只需要等到桶滿：尚缺 60，每秒補回 2
+        assert_eq!(bucket.try_take(500.0), Some(Duration::from_secs(30)));
+        tokio::time::advance(Duration::from_secs(30)).await;
+        assert_eq!(bucket.try_take(500.0), None);
+        // 回應的用量可以讓桶變成負值，之後的請求等待補回
+        bucket.debit(240.0);
+        assert_eq!(bucket.try_take(1.0), Some(Duration::from_secs_f64(120.5)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn limited_provider_spaces_requests() {
+        let provider = FakeProvider::default();
+        let limited = RateLimitedProvider::new(Box::new(provider.clone()), RateLimiter::new(Some(2), None).unwrap());
+        let request = ChatRequest {
+            system: String::new(),
+            prompt: "x".to_string(),
+            temperature: None,
+            timeout: None,
+            response_schema: None,
+        };
+        let start = Instant::now();
+        for _ in 0..4 {
+            limited.complete(&request).await.unwrap();
+        }
+        // 每分鐘 2 個請求：前兩個立即送出，之後每 30 秒一個
+        assert_eq!(start.elapsed().as_secs(), 60);
+        assert_eq!(provider.calls.lock().unwrap().len(), 4);
+        assert!(RateLimiter::new(None, None).is_none());
+    }
+}
@@ -0,0 +1,173 @@
+//! JSON 報告的讀寫、格式版本與遷移。
+//!
+//! 讀取報告時先解析為 `serde_json::Value`，依 `schema_version` 逐版套用遷移後再轉為
+//! [`ProjectAnalysis`]，因此 `models.rs` 的變更不會讓舊報告無法讀取。修改報告結構時，
+//! 遞增 [`SCHEMA_VERSION`] 並在 [`MIGRATIONS`] 加入對應的遷移函式。
+
+use anyhow::{Result, anyhow};
+use serde_json::{json, Map, Value};
+use std::path::Path;
+use tracing::info;
+use crate::models::ProjectAnalysis;
+
+/// 目前的報告格式版本
+pub const SCHEMA_VERSION: u32 = 2;
+
+type Migration = fn(&mut Map<String, Value>);
+
+/// `MIGRATIONS[n]` 將版本 `n` 的報告升級為版本 `n + 1`
+const MIGRATIONS: &[Migration] = &[v0_to_v1, v1_to_v2];
+
+/// 版本 0：加入版本欄位之前的報告。補上之後新增的欄位。
+fn v0_to_v1(report: &mut Map<String, Value>) {
+    report.entry("metadata").or_insert_with(|| json!({}));
+    report.entry("crates").or_insert_with(|| json!([]));
+    report.entry("modules").or_insert_with(|| json!([]));
+    let files = report
+        .get_mut("file_analyses")
+        .and_then(Value::as_array_mut)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_object_mut);
+    for file in files {
+        for key in ["doc_comment_lines", "inner_doc_lines", "attribute_lines"] {
+            file.entry(key).or_insert_with(|| json!(0));
+        }
+        file.entry("items").or_insert_with(|| json!([]));
+    }
+}
+
+/// 版本 1：尚未記錄 `use` 路徑、crate 依賴與分析語言的報告。
+///
+/// 缺少 `imports` 的檔案標記為 `null`（未知），`update` 時會重新擷取，架構圖則略過這些檔案；
+/// 加入語言目錄之前的 AI 分析都以繁體中文進行。
+fn v1_to_v2(report: &mut Map<String, Value>) {
+    if let Some(metadata) = report.get_mut("metadata").and_then(Value::as_object_mut) {
+        if metadata.get("provider").is_some_and(|p| !p.is_null()) {
+            metadata.entry("language").or_insert_with(|| json!("zh-TW"));
+        }
+    }
+    for krate in report.get_mut("crates").and_then(Value::as_array_mut).into_iter().flatten() {
+        if let Some(krate) = krate.as_object_mut() {
+            krate.entry("dependencies").or_insert_with(|| json!([]));
+        }
+    }
+    let files = report
+        .get_mut("file_analyses")
+        .and_then(Value::as_array_mut)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_object_mut);
+    for file in files {
+        file.entry("imports").or_insert(Value::Null);
+    }
+}
+
+/// 將任意版本的報告升級為目前的版本
+pub fn migrate(mut value: Value) -> Result<Value> {
+    let report = value
+        .as_object_mut()
+        .ok_or_else(|| anyhow!("報告的最外層必須是 JSON 物件"))?;
+    let version = match report.get("schema_version") {
+        None => 0,
+        Some(version) => version
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| anyhow!("無效的 schema_version：{}", version))?,
+    };
+    if version > SCHEMA_VERSION {
+        return Err(anyhow!(
+            "報告格式版本 {} 比目前支援的版本 {} 新，請更新 rs2know",
+            version,
+            SCHEMA_VERSION
+        ));
+    }
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        info!("遷移報告格式：版本 {} -> {}", from, from + 1);
+        migration(report);
+    }
+    report.insert("schema_version".to_string(), json!(SCHEMA_VERSION));
+    Ok(value)
+}
+
+/// 讀取報告並遷移至目前的版本
+pub fn load(path: impl AsRef<Path>) -> Result<ProjectAnalysis> {
+    let path = path.as_ref();
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("無法讀取報告：{} - {}", path.display(), e))?;
+    let value = migrate(serde_json::from_str(&content)?)?;
+    serde_path_to_error::deserialize(value)
+        .map_err(|e| anyhow!("報告欄位 `{}` 不符合格式：{}", e.path(), e.inner()))
+}
+
+/// 以目前的版本寫入報告
+pub fn save(path: impl AsRef<Path>, report: &mut ProjectAnalysis) -> Result<()> {
+    report.schema_version = SCHEMA_VERSION;
+    std::fs::write(path, serde_json::to_string_pretty(report)?)?;
+    Ok(())
+}
+
+/// `ProjectAnalysis` 的 JSON Schema
+pub fn json_schema() -> Value {
+    let schema = schemars::schema_for!(ProjectAnalysis);
+    serde_json::to_value(schema).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn v1_report_marks_imports_unknown() {
+        let v1 = json!({
+            "schema_version": 1,
+            "project_name": "demo",
+            "metadata": { "provider": "openai" },
+            "crates": [{ "name": "demo", "path": "", "overview": null }],
+            "modules": [],
+            "file_analyses": [{ "file_path": "src/lib.rs", "items": [] }],
+        });
+        let migrated = migrate(v1).unwrap();
+        assert_eq!(migrated["schema_version"], json!(SCHEMA_VERSION));
+        assert_eq!(migrated["metadata"]["language"], json!("zh-TW"));
+        assert_eq!(migrated["crates"][0]["dependencies"], json!([]));
+        assert!(migrated["file_analyses"][0]["imports"].is_null());
+    }
+
+    #[test]
+    fn rejects_newer_schema() {
+        let future = json!({ "schema_version": SCHEMA_VERSION + 1 });
+        assert!(migrate(future).is_err());
+    }
+
+    #[test]
+    fn baseline_report_loads_with_defaults() {
+        // 加入版本欄位之前、最初格式的報告
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/report/v0.json");
+        let report = load(path).unwrap();
+        assert_eq!(report.schema_version, SCHEMA_VERSION);
+        assert_eq!(report.summary.total_loc, 42);
+        assert_eq!(report.summary.tech_stack, ["tokio", "serde"]);
+        assert!(report.crates.is_empty() && report.modules.is_empty());
+
+        // 沒有記錄服務提供者，因此不推定分析語言
+        let metadata = &report.metadata;
+        assert!(metadata.generator_version.is_empty());
+        assert_eq!((metadata.provider.as_deref(), metadata.language.as_deref()), (None, None));
+        assert_eq!((metadata.git_commit.as_deref(), metadata.git_dirty), (None, false));
+
+        let [main, config] = &report.file_analyses[..] else {
+            panic!("應有兩個檔案");
+        };
+        assert_eq!((main.file_path.as_str(), main.loc, main.code_lines), ("src/main.rs", 30, 20));
+        assert_eq!((main.doc_comment_lines, main.inner_doc_lines, main.attribute_lines), (0, 0, 0));
+        assert!(main.items.is_empty());
+        // `imports` 未知，`update` 時重新擷取
+        assert_eq!(main.imports, None);
+        assert!(!main.ai_stale && main.analyzed_at.is_none() && main.model.is_none());
+        let ai = main.ai_analysis.as_ref().unwrap();
+        assert_eq!(ai.core_structs[0].name, "Config");
+        assert_eq!(ai.functions_details[0].return_type, "Result<()>");
+        assert!(config.ai_analysis.is_none());
+    }
+}
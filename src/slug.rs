@@ -0,0 +1,174 @@
+//! 與 GitHub 相同規則的 Markdown 標題錨點。
+//!
+//! GitHub 將標題文字轉為小寫，移除字母、數字、`-`、`_` 與空白以外的字元，再將空白換成
+//! `-`。同一份文件中重複的錨點依出現順序加上 `-1`、`-2` 等後綴。
+
+use std::collections::{HashMap, HashSet};
+
+/// 標題文字的錨點，不處理重複
+pub fn slug(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            '-' | '_' => Some(c),
+            c if c.is_alphanumeric() => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// 依文件順序產生不重複的錨點
+#[derive(Default)]
+pub struct Slugger {
+    occurrences: HashMap<String, usize>,
+}
+
+impl Slugger {
+    pub fn slug(&mut self, text: &str) -> String {
+        let original = slug(text);
+        let mut result = original.clone();
+        while self.occurrences.contains_key(&result) {
+            let count = self.occurrences.entry(original.clone()).or_default();
+            *count += 1;
+            result = format!("{}-{}", original, count);
+        }
+        self.occurrences.insert(result.clone(), 0);
+        result
+    }
+}
+
+/// 文件中的一個標題
+pub struct Heading {
+    pub text: String,
+    pub id: String,
+}
+
+/// 依序列出文件中的 ATX 標題（`#` 開頭）與其錨點，略過程式碼區塊中的內容
+pub fn headings(markdown: &str) -> Vec<Heading> {
+    let mut slugger = Slugger::default();
+    let mut fence: Option<&str> = None;
+    let mut headings = Vec::new();
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        if line.len() - trimmed.len() > 3 {
+            continue;
+        }
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+            continue;
+        }
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fence = Some(&trimmed[..3]);
+            continue;
+        }
+        if let Some(text) = heading_text(trimmed) {
+            let id = slugger.slug(&text);
+            headings.push(Heading { text, id });
+        }
+    }
+    headings
+}
+
+fn heading_text(line: &str) -> Option<String> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &line[level..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    // 結尾的 `#` 序列前必須是空白才算是結束標記
+    let rest = rest.trim();
+    let without_closing = rest.trim_end_matches('#');
+    let text = if without_closing.is_empty() || without_closing.ends_with([' ', '\t']) {
+        without_closing.trim_end()
+    } else {
+        rest
+    };
+    Some(text.to_string())
+}
+
+/// 文件內 `](#anchor)` 連結中找不到對應標題的錨點
+pub fn broken_links(markdown: &str) -> Vec<String> {
+    let ids: HashSet<String> = headings(markdown).into_iter().map(|h| h.id).collect();
+    let mut broken = Vec::new();
+    let mut rest = markdown;
+    while let Some(start) = rest.find("](#") {
+        let after = &rest[start + 3..];
+        let Some(end) = after.find(')') else {
+            break;
+        };
+        let anchor = &after[..end];
+        if !ids.contains(anchor) && !broken.iter().any(|b| b == anchor) {
+            broken.push(anchor.to_string());
+        }
+        rest = &after[end..];
+    }
+    broken
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 依序產生錨點，預期值取自 github-slugger
+    fn slugs(texts: &[&str]) -> Vec<String> {
+        let mut slugger = Slugger::default();
+        texts.iter().map(|text| slugger.slug(text)).collect()
+    }
+
+    #[test]
+    fn matches_github_slugger() {
+        let cases = [
+            ("Hello World", "hello-world"),
+            ("Hello, World!", "hello-world"),
+            ("src/main.rs", "srcmainrs"),
+            ("src/a.b (c)/x-y_z.rs", "srcab-cx-y_zrs"),
+            ("a  b", "a--b"),
+            ("Foo_Bar", "foo_bar"),
+            ("😄 smile", "-smile"),
+            ("Übersicht", "übersicht"),
+            ("專案總結", "專案總結"),
+            ("標題（說明）、附註", "標題說明附註"),
+            ("模組 架構", "模組-架構"),
+            ("`code` and *emphasis*", "code-and-emphasis"),
+            ("1.2.3", "123"),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(slug(text), expected, "{}", text);
+        }
+    }
+
+    #[test]
+    fn numbers_duplicates_like_github_slugger() {
+        assert_eq!(slugs(&["foo", "foo", "foo"]), ["foo", "foo-1", "foo-2"]);
+        assert_eq!(slugs(&["foo", "foo", "foo-1"]), ["foo", "foo-1", "foo-1-1"]);
+        assert_eq!(slugs(&["foo-1", "foo", "foo"]), ["foo-1", "foo", "foo-2"]);
+        assert_eq!(slugs(&["mod.rs", "mod.rs", "Mod.rs"]), ["modrs", "modrs-1", "modrs-2"]);
+    }
+
+    #[test]
+    fn headings_skip_code_blocks_and_closing_hashes() {
+        let markdown = "# Title #\n\n```rust\n# not a heading\n```\n\n## Title\n####### too deep\n#no-space\n    # indented code\n### C# ##\n";
+        let headings: Vec<(String, String)> = headings(markdown).into_iter().map(|h| (h.text, h.id)).collect();
+        assert_eq!(
+            headings,
+            [
+                ("Title".to_string(), "title".to_string()),
+                ("Title".to_string(), "title-1".to_string()),
+                ("C#".to_string(), "c".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_broken_links_once() {
+        let markdown = "# A\n\n[a](#a) [b](#b) [b again](#b)\n";
+        assert_eq!(broken_links(markdown), ["b"]);
+    }
+}
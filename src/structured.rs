@@ -0,0 +1,208 @@
+//! 結構化輸出：由資料型別產生 JSON Schema 交給模型，並解析、驗證模型的回應。
+//!
+//! 支援結構化輸出的服務會直接回傳符合 schema 的 JSON；其餘情況下回應可能夾帶
+//! markdown 區塊或說明文字，由 [`parse_response`] 的修復流程取出 JSON 物件。
+
+use anyhow::{Result, anyhow};
+use schemars::gen::SchemaSettings;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use std::fmt;
+
+/// 傳給服務提供者的回應格式
+#[derive(Debug, Clone)]
+pub struct ResponseSchema {
+    /// schema 名稱，OpenAI 的 `json_schema.name` 與 Anthropic 的工具名稱
+    pub name: &'static str,
+    pub schema: serde_json::Value,
+}
+
+impl ResponseSchema {
+    /// 由型別產生內嵌所有子 schema 的 JSON Schema，避免服務端不支援 `$ref`
+    pub fn of<T: JsonSchema>(name: &'static str) -> Self {
+        let settings = SchemaSettings::draft07().with(|s| {
+            s.inline_subschemas = true;
+            s.meta_schema = None;
+        });
+        let schema = settings.into_generator().into_root_schema_for::<T>();
+        Self {
+            name,
+            schema: serde_json::to_value(schema).unwrap_or_default(),
+        }
+    }
+}
+
+/// 模型回應不符合預期結構時的錯誤，指出出錯的欄位
+#[derive(Debug)]
+pub struct ValidationError {
+    /// 例如 `functions_details[2].name`；根層級為 `.`
+    pub field: String,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "回應欄位 `{}` 不符合格式：{}", self.field, self.message)
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// 解析模型回應：先嘗試直接解析，失敗時取出第一個完整的 JSON 物件並修復常見問題
+pub fn parse_response<T: DeserializeOwned>(content: &str) -> Result<T> {
+    let trimmed = content.trim();
+    if let Ok(value) = serde_json::from_str::<serde_json::Value>(trimmed) {
+        return validate(value);
+    }
+
+    let object = extract_object(trimmed)
+        .ok_or_else(|| anyhow!("在 AI 回應中找不到 JSON 物件"))?;
+    let value = match serde_json::from_str::<serde_json::Value>(object) {
+        Ok(value) => value,
+        Err(_) => serde_json::from_str(&remove_trailing_commas(object))
+            .map_err(|e| anyhow!("AI 回應不是有效的 JSON：{}", e))?,
+    };
+    validate(value)
+}
+
+fn validate<T: DeserializeOwned>(value: serde_json::Value) -> Result<T> {
+    serde_path_to_error::deserialize(value).map_err(|e| {
+        let message = e.inner().to_string();
+        let path = e.path().to_string();
+        // 缺少欄位時，錯誤路徑只到父層，將欄位名稱補上
+        let missing = message
+            .strip_prefix("missing field `")
+            .and_then(|rest| rest.split('`').next());
+        let field = match missing {
+            Some(name) if path == "." => name.to_string(),
+            Some(name) => format!("{}.{}", path, name),
+            None => path,
+        };
+        anyhow::Error::new(ValidationError { field, message })
+    })
+}
+
+/// 取出第一個括號平衡的 `{...}`，忽略字串中的括號
+fn extract_object(content: &str) -> Option<&str> {
+    let start = content.find('{')?;
+    let mut depth = 0_usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (offset, c) in content[start..].char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&content[start..=start + offset]);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// 移除 `}` 或 `]` 前多餘的逗號
+fn remove_trailing_commas(json: &str) -> String {
+    let mut result = String::with_capacity(json.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    let chars: Vec<char> = json.chars().collect();
+    for (i, &c) in chars.iter().enumerate() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            result.push(c);
+            continue;
+        }
+        if c == '"' {
+            in_string = true;
+        }
+        if c == ',' {
+            let next = chars[i + 1..].iter().find(|c| !c.is_whitespace());
+            if matches!(next, Some('}') | Some(']')) {
+                continue;
+            }
+        }
+        result.push(c);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::AIAnalysis;
+
+    #[derive(Debug, serde::Deserialize, PartialEq)]
+    struct Sample {
+        name: String,
+        tags: Vec<String>,
+    }
+
+    fn sample(name: &str, tags: &[&str]) -> Sample {
+        Sample { name: name.to_string(), tags: tags.iter().map(|t| t.to_string()).collect() }
+    }
+
+    #[test]
+    fn repairs_common_wrappers() {
+        let cases = [
+            ("fenced", "```json\n{\"name\": \"a\", \"tags\": []}\n```", sample("a", &[])),
+            ("prose", "以下是分析結果：\n{\"name\": \"a\", \"tags\": [\"x\"]}\n希望有幫助！", sample("a", &["x"])),
+            ("trailing commas", "{\"name\": \"a\", \"tags\": [\"x\", \"y\",],\n}", sample("a", &["x", "y"])),
+            (
+                "braces and commas in strings",
+                "結果：{\"name\": \"fn f() { g(a,}) }\", \"tags\": [\"{\", \"\\\"}\", \",]\",]} 完",
+                sample("fn f() { g(a,}) }", &["{", "\"}", ",]"]),
+            ),
+        ];
+        for (name, content, expected) in cases {
+            assert_eq!(parse_response::<Sample>(content).unwrap(), expected, "{}", name);
+        }
+        assert!(parse_response::<Sample>("沒有 JSON").is_err());
+        assert!(parse_response::<Sample>("{\"name\": \"a\"").is_err());
+    }
+
+    fn field_of(error: anyhow::Error) -> String {
+        error.downcast::<ValidationError>().expect("應為 ValidationError").field
+    }
+
+    #[test]
+    fn reports_field_paths() {
+        let detail = |name: &str| format!(
+            "{{{}\"description\": \"d\", \"parameters\": [], \"return_type\": \"()\"}}",
+            if name.is_empty() { String::new() } else { format!("\"name\": \"{}\", ", name) }
+        );
+        let missing = format!(
+            "{{\"main_functions\": [], \"core_structs\": [], \"error_types\": [], \"functions_details\": [{}, {}, {}]}}",
+            detail("a"),
+            detail("b"),
+            detail("")
+        );
+        assert_eq!(field_of(parse_response::<AIAnalysis>(&missing).unwrap_err()), "functions_details[2].name");
+
+        let missing_root = r#"{"main_functions": [], "core_structs": [], "error_types": []}"#;
+        assert_eq!(field_of(parse_response::<AIAnalysis>(missing_root).unwrap_err()), "functions_details");
+
+        let mismatch = r#"{"main_functions": "main", "core_structs": [], "error_types": [], "functions_details": []}"#;
+        let error = parse_response::<AIAnalysis>(mismatch).unwrap_err();
+        let message = error.to_string();
+        assert_eq!(field_of(error), "main_functions");
+        assert!(message.contains("invalid type"), "{}", message);
+    }
+}
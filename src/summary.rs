@@ -0,0 +1,599 @@
+//! 分層的專案總結：先總結每個目錄（模組），再總結每個 crate，最後總結整個專案。
+//!
+//! 每一層只把下一層的精簡摘要交給模型，內容超過 `max_input_tokens` 時分批總結，
+//! 再把各批結果合併總結，因此不會把所有檔案分析一次放進單一提示詞。`update` 時只重新
+//! 總結包含變更檔案的模組與其上層，其餘沿用先前報告中的總結。
+
+use std::collections::{BTreeMap, HashSet};
+use std::future::Future;
+use std::path::Path;
+use futures::stream::{self, StreamExt};
+use tracing::{info, warn};
+use crate::ai::Analyzer;
+use crate::analysis::{discover_manifests, relative_path, FileFilter};
+use crate::models::{
+    CrateOverview, CrateSummary, FileAnalysis, ModuleOverview, ModuleSummary, ProjectAnalysis,
+    ProjectSummary,
+};
+use crate::prompts::{render, DigestLabels};
+use crate::tokens::{estimate_tokens, truncate_to_tokens};
+
+/// 各層級的總結
+pub struct Summaries {
+    pub summary: ProjectSummary,
+    pub crates: Vec<CrateSummary>,
+    pub modules: Vec<ModuleSummary>,
+}
+
+impl Summaries {
+    /// 沿用先前報告中同名 crate、同路徑模組與專案的 AI 總結，只保留新計算的統計數字
+    pub fn keep_overviews(&mut self, previous: &mut ProjectAnalysis) {
+        for krate in &mut self.crates {
+            if let Some(old) = previous.crates.iter_mut().find(|c| c.name == krate.name) {
+                krate.overview = std::mem::take(&mut old.overview);
+            }
+        }
+        for module in &mut self.modules {
+            if let Some(old) = previous.modules.iter_mut().find(|m| m.path == module.path) {
+                module.overview = std::mem::take(&mut old.overview);
+            }
+        }
+        let summary = std::mem::take(&mut previous.summary);
+        self.summary = ProjectSummary {
+            total_files: self.summary.total_files,
+            total_loc: self.summary.total_loc,
+            ..summary
+        };
+    }
+}
+
+/// `update` 時的先前報告與變更的檔案
+pub struct Previous<'a> {
+    pub report: &'a ProjectAnalysis,
+    /// 新增、修改、刪除或重新命名（新舊路徑皆列入）的檔案，相對於專案目錄
+    pub changed: &'a HashSet<String>,
+}
+
+impl Previous<'_> {
+    /// 需要重新總結的模組：包含變更檔案的目錄與其所有上層目錄
+    fn dirty_modules(&self) -> HashSet<String> {
+        let mut dirty = HashSet::new();
+        for path in self.changed {
+            let mut dir = Path::new(path).parent();
+            while let Some(current) = dir {
+                dirty.insert(module_path(&current.to_string_lossy()));
+                dir = current.parent();
+            }
+        }
+        dirty
+    }
+}
+
+struct CrateRoot {
+    name: String,
+    /// 相對於專案根目錄，根目錄為 `.`
+    path: String,
+    dependencies: Vec<String>,
+}
+
+/// 找出專案中所有具有 `[package]` 的 `Cargo.toml`，依路徑排序。與尋找原始碼檔案時遵循相同的
+/// 忽略與排除規則，例如 `vendor/` 中的 crate 不會列入。
+fn discover_crates(project_path: &Path, filter: &FileFilter) -> Vec<CrateRoot> {
+    let mut crates: Vec<CrateRoot> = discover_manifests(project_path, filter)
+        .into_iter()
+        .filter_map(|manifest_path| {
+            let manifest: toml::Value = std::fs::read_to_string(&manifest_path).ok()?.parse().ok()?;
+            let name = manifest.get("package")?.get("name")?.as_str()?.to_string();
+            let dir = manifest_path.parent()?;
+            Some(CrateRoot {
+                name,
+                path: module_path(&relative_path(project_path, dir)),
+                dependencies: manifest_dependencies(&manifest),
+            })
+        })
+        .collect();
+    crates.sort_by(|a, b| a.path.cmp(&b.path));
+    crates
+}
+
+/// `[dependencies]` 與 `[target.*.dependencies]` 中的套件名稱；以 `package` 改名的依賴使用原本的套件名稱
+fn manifest_dependencies(manifest: &toml::Value) -> Vec<String> {
+    let targets = manifest
+        .get("target")
+        .and_then(toml::Value::as_table)
+        .into_iter()
+        .flat_map(|targets| targets.values())
+        .filter_map(|target| target.get("dependencies"));
+    let mut dependencies: Vec<String> = manifest
+        .get("dependencies")
+        .into_iter()
+        .chain(targets)
+        .filter_map(toml::Value::as_table)
+        .flatten()
+        .map(|(key, value)| {
+            value.get("package").and_then(toml::Value::as_str).unwrap_or(key).to_string()
+        })
+        .collect();
+    dependencies.sort();
+    dependencies.dedup();
+    dependencies
+}
+
+/// 空路徑代表根目錄，統一以 `.` 表示
+fn module_path(path: &str) -> String {
+    if path.is_empty() {
+        ".".to_string()
+    } else {
+        path.to_string()
+    }
+}
+
+fn contains(dir: &str, file_path: &str) -> bool {
+    dir == "." || file_path.strip_prefix(dir).is_some_and(|rest| rest.starts_with('/'))
+}
+
+fn project_name(project_path: &Path) -> String {
+    project_path
+        .canonicalize()
+        .ok()
+        .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+        .unwrap_or_else(|| ".".to_string())
+}
+
+/// 單一檔案的精簡摘要：頂層項目與 AI 分析重點
+fn file_digest(labels: &DigestLabels, analysis: &FileAnalysis) -> String {
+    let loc = analysis.loc.to_string();
+    let mut digest = render(&labels.file, &[("path", &analysis.file_path), ("loc", &loc)]);
+    digest.push('\n');
+    for item in analysis.items.iter().filter(|item| item.parent.is_none()) {
+        let visibility = if item.visibility == "private" { "" } else { "pub " };
+        let name = match &item.trait_name {
+            Some(trait_name) => format!("{} for {}", trait_name, item.name),
+            None => item.name.clone(),
+        };
+        digest.push_str(&format!("- {}{} {}", visibility, item.kind.as_str(), name));
+        if let Some(line) = item.doc.as_deref().and_then(|doc| doc.lines().next()) {
+            digest.push_str(&format!("{}{}", labels.colon, line));
+        }
+        digest.push('\n');
+    }
+    if let Some(ai) = &analysis.ai_analysis {
+        digest.push_str(&list(labels, &labels.main_functions, &ai.main_functions));
+        for core_struct in &ai.core_structs {
+            let vars = [("name", core_struct.name.as_str()), ("description", core_struct.description.as_str())];
+            digest.push_str(&render(&labels.core_struct, &vars));
+            digest.push('\n');
+        }
+        digest.push_str(&list(labels, &labels.error_types, &ai.error_types));
+    }
+    digest
+}
+
+fn line(labels: &DigestLabels, label: &str, value: &str) -> String {
+    format!("{}{}{}\n", label, labels.colon, value)
+}
+
+fn list(labels: &DigestLabels, label: &str, values: &[String]) -> String {
+    if values.is_empty() {
+        String::new()
+    } else {
+        line(labels, label, &values.join(&labels.list_separator))
+    }
+}
+
+fn module_digest(labels: &DigestLabels, module: &ModuleSummary) -> String {
+    let files = module.total_files.to_string();
+    let loc = module.total_loc.to_string();
+    let heading = render(&labels.module, &[("path", &module.path), ("files", &files), ("loc", &loc)]);
+    format!("{}\n{}", heading, module_overview_digest(labels, &module.overview))
+}
+
+fn module_overview_digest(labels: &DigestLabels, overview: &ModuleOverview) -> String {
+    format!(
+        "{}{}{}",
+        line(labels, &labels.purpose, &overview.purpose),
+        list(labels, &labels.responsibilities, &overview.responsibilities),
+        list(labels, &labels.key_components, &overview.key_components),
+    )
+}
+
+fn crate_digest(labels: &DigestLabels, summary: &CrateSummary) -> String {
+    let files = summary.total_files.to_string();
+    let loc = summary.total_loc.to_string();
+    let heading = render(&labels.krate, &[
+        ("name", &summary.name),
+        ("path", &summary.path),
+        ("files", &files),
+        ("loc", &loc),
+    ]);
+    format!("{}\n{}", heading, crate_overview_digest(labels, &summary.overview))
+}
+
+fn crate_overview_digest(labels: &DigestLabels, overview: &CrateOverview) -> String {
+    format!(
+        "{}{}{}{}",
+        line(labels, &labels.purpose, &overview.purpose),
+        line(labels, &labels.architecture, &overview.code_architecture),
+        list(labels, &labels.main_features, &overview.main_features),
+        list(labels, &labels.key_components, &overview.key_components),
+    )
+}
+
+fn project_digest(labels: &DigestLabels, summary: &ProjectSummary) -> String {
+    format!(
+        "{}{}{}{}{}",
+        line(labels, &labels.architecture, &summary.code_architecture),
+        list(labels, &labels.main_features, &summary.main_features),
+        list(labels, &labels.key_components, &summary.key_components),
+        list(labels, &labels.tech_stack, &summary.tech_stack),
+        list(labels, &labels.recommendations, &summary.recommendations),
+    )
+}
+
+/// 將條目依序裝入不超過 `budget` 的批次；單一條目過大時截斷
+fn pack(entries: &[String], budget: usize) -> Vec<String> {
+    let mut batches = Vec::new();
+    let mut current = String::new();
+    let mut used = 0;
+    for entry in entries {
+        // 保留條目後換行字元的額度
+        let entry = truncate_to_tokens(entry, budget.saturating_sub(1).max(1));
+        let cost = estimate_tokens(entry) + 1;
+        if used + cost > budget && !current.is_empty() {
+            batches.push(std::mem::take(&mut current));
+            used = 0;
+        }
+        current.push_str(entry);
+        current.push('\n');
+        used += cost;
+    }
+    if !current.is_empty() || batches.is_empty() {
+        batches.push(current);
+    }
+    batches
+}
+
+/// 分批總結條目；超過一批時，把各批結果轉回條目再次總結，直到剩下單一結果
+async fn reduce<T, F, Fut>(
+    mut entries: Vec<String>,
+    budget: usize,
+    to_entry: impl Fn(&T) -> String,
+    mut summarize: F,
+) -> Option<T>
+where
+    F: FnMut(String, Option<(usize, usize)>) -> Fut,
+    Fut: Future<Output = Option<T>>,
+{
+    loop {
+        let batches = pack(&entries, budget);
+        let count = batches.len();
+        if count == 1 {
+            return summarize(batches.into_iter().next()?, None).await;
+        }
+
+        let mut partials = Vec::new();
+        for (index, batch) in batches.into_iter().enumerate() {
+            match summarize(batch, Some((index + 1, count))).await {
+                Some(partial) => partials.push(partial),
+                None => warn!("略過總結失敗的第 {}/{} 部分", index + 1, count),
+            }
+        }
+        if partials.len() <= 1 {
+            return partials.pop();
+        }
+
+        let next: Vec<String> = partials.iter().map(&to_entry).collect();
+        // 各批結果仍然過大而無法收斂時，平均截斷後放進同一批
+        entries = if next.len() >= entries.len() {
+            let share = (budget / next.len()).saturating_sub(1).max(1);
+            next.iter().map(|entry| truncate_to_tokens(entry, share).to_string()).collect()
+        } else {
+            next
+        };
+    }
+}
+
+/// 依目錄與 crate 分組並逐層總結；未提供 `analyzer` 時只計算各層的統計數字。
+/// 提供 `previous` 時，未受變更影響的模組、crate 與專案沿用先前的總結，不呼叫 AI。
+pub async fn summarize_project(
+    project_path: &Path,
+    filter: &FileFilter,
+    analyses: &[FileAnalysis],
+    analyzer: Option<&Analyzer>,
+    previous: Option<&Previous<'_>>,
+    jobs: usize,
+) -> Summaries {
+    let total_files = analyses.len();
+    let total_loc: usize = analyses.iter().map(|a| a.loc).sum();
+
+    let mut crates = discover_crates(project_path, filter);
+    // 不屬於任何 crate 的檔案歸入以專案目錄命名的 crate
+    let orphaned = analyses
+        .iter()
+        .any(|a| !crates.iter().any(|c| contains(&c.path, &a.file_path)));
+    if orphaned && !crates.iter().any(|c| c.path == ".") {
+        crates.insert(0, CrateRoot {
+            name: project_name(project_path),
+            path: ".".to_string(),
+            dependencies: Vec::new(),
+        });
+    }
+
+    // 目錄 -> (所屬 crate, 檔案)
+    let mut groups: BTreeMap<String, (usize, Vec<&FileAnalysis>)> = BTreeMap::new();
+    for analysis in analyses {
+        let dir = Path::new(&analysis.file_path)
+            .parent()
+            .map(|p| module_path(&p.to_string_lossy()))
+            .unwrap_or_else(|| ".".to_string());
+        let crate_index = crates
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| contains(&c.path, &analysis.file_path))
+            .max_by_key(|(_, c)| c.path.len())
+            .map(|(index, _)| index)
+            .unwrap_or_default();
+        groups.entry(dir).or_insert_with(|| (crate_index, Vec::new())).1.push(analysis);
+    }
+
+    let budget = analyzer.map(|a| a.max_input_tokens()).unwrap_or_default().max(1);
+    let dirty = previous.map(Previous::dirty_modules).unwrap_or_default();
+    let dirty = &dirty;
+
+    // 第一層：模組。第二個值表示是否重新總結
+    let modules: Vec<(ModuleSummary, bool)> = stream::iter(&groups)
+        .map(|(dir, (crate_index, files))| {
+            let crate_name = &crates[*crate_index].name;
+            async move {
+                let reused = previous
+                    .filter(|_| !dirty.contains(dir))
+                    .and_then(|previous| previous.report.modules.iter().find(|m| m.path == *dir))
+                    // 先前總結失敗的模組重新總結
+                    .filter(|module| !module.overview.purpose.is_empty())
+                    .map(|module| module.overview.clone());
+                let (overview, fresh) = match (analyzer, reused) {
+                    (Some(_), Some(overview)) => (Some(overview), false),
+                    (Some(analyzer), None) => {
+                        let labels = &analyzer.catalog().digest;
+                        let entries = files.iter().map(|f| file_digest(labels, f)).collect();
+                        let to_entry = |overview: &ModuleOverview| module_overview_digest(labels, overview);
+                        let overview = reduce(entries, budget, to_entry, |digest, part| async move {
+                            analyzer.summarize_module(dir, crate_name, &digest, part).await
+                        }).await;
+                        (overview, true)
+                    }
+                    (None, _) => (None, false),
+                };
+                let module = ModuleSummary {
+                    path: dir.clone(),
+                    crate_name: crate_name.clone(),
+                    total_files: files.len(),
+                    total_loc: files.iter().map(|f| f.loc).sum(),
+                    overview: overview.unwrap_or_default(),
+                };
+                (module, fresh)
+            }
+        })
+        .buffered(jobs.max(1))
+        .collect()
+        .await;
+    if analyzer.is_some() {
+        let fresh = modules.iter().filter(|(_, fresh)| *fresh).count();
+        info!("已完成 {} 個模組的總結（沿用 {} 個未變更的模組）", fresh, modules.len() - fresh);
+    }
+
+    // 第二層：crate
+    let mut crate_summaries = Vec::new();
+    let mut any_crate_fresh = false;
+    for (index, root) in crates.iter().enumerate() {
+        let members: Vec<&(ModuleSummary, bool)> = groups
+            .values()
+            .zip(&modules)
+            .filter(|((crate_index, _), _)| *crate_index == index)
+            .map(|(_, module)| module)
+            .collect();
+        if members.is_empty() {
+            continue;
+        }
+        let reused = previous
+            .filter(|_| members.iter().all(|(_, fresh)| !fresh))
+            .and_then(|previous| previous.report.crates.iter().find(|c| c.name == root.name && c.path == root.path))
+            .filter(|krate| !krate.overview.purpose.is_empty())
+            .map(|krate| krate.overview.clone());
+        let overview = match (analyzer, reused) {
+            (Some(_), Some(overview)) => Some(overview),
+            (Some(analyzer), None) => {
+                any_crate_fresh = true;
+                let labels = &analyzer.catalog().digest;
+                let entries = members.iter().map(|(m, _)| module_digest(labels, m)).collect();
+                let to_entry = |overview: &CrateOverview| crate_overview_digest(labels, overview);
+                reduce(entries, budget, to_entry, |digest, part| async move {
+                    analyzer.summarize_crate(&root.name, &digest, part).await
+                }).await
+            }
+            (None, _) => None,
+        };
+        crate_summaries.push(CrateSummary {
+            name: root.name.clone(),
+            path: root.path.clone(),
+            dependencies: root.dependencies.clone(),
+            total_files: members.iter().map(|(m, _)| m.total_files).sum(),
+            total_loc: members.iter().map(|(m, _)| m.total_loc).sum(),
+            overview: overview.unwrap_or_default(),
+        });
+    }
+
+    // 第三層：整個專案；crate 的總結或組成改變時才重新總結
+    let reused = previous
+        .filter(|previous| {
+            let names = |crates: &[CrateSummary]| crates.iter().map(|c| c.name.clone()).collect::<Vec<_>>();
+            !any_crate_fresh
+                && names(&previous.report.crates) == names(&crate_summaries)
+                && !previous.report.summary.code_architecture.is_empty()
+        })
+        .map(|previous| previous.report.summary.clone());
+    let summary = match (analyzer, reused) {
+        (Some(_), Some(summary)) => Some(summary),
+        (Some(analyzer), None) if !crate_summaries.is_empty() => {
+            let labels = &analyzer.catalog().digest;
+            let entries = crate_summaries.iter().map(|c| crate_digest(labels, c)).collect();
+            let to_entry = |summary: &ProjectSummary| project_digest(labels, summary);
+            reduce(entries, budget, to_entry, |digest, part| async move {
+                analyzer.summarize_workspace(&digest, part).await
+            }).await
+        }
+        _ => None,
+    };
+    let mut summary = summary.unwrap_or_default();
+    // 統計數字以本地計算為準
+    summary.total_files = total_files;
+    summary.total_loc = total_loc;
+
+    let modules = modules.into_iter().map(|(module, _)| module).collect();
+    Summaries { summary, crates: crate_summaries, modules }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::{analyze_files, discover_rust_files, tests::temp_project};
+    use crate::locale::Catalog;
+    use crate::markdown::tests::sample_report;
+    use crate::provider::tests::FakeProvider;
+
+    #[test]
+    fn pack_respects_budget() {
+        let budget = 40;
+        let mut entries: Vec<String> = (0..20).map(|i| format!("entry {} {}", i, "x".repeat(i * 10))).collect();
+        // 剛好等於預算的條目
+        entries.insert(5, "w".repeat(budget * 4));
+        let batches = pack(&entries, budget);
+        assert!(batches.len() > 1);
+        for batch in &batches {
+            assert!(estimate_tokens(batch) <= budget, "{}", batch);
+        }
+        // 條目依序分批，除了過大而被截斷的條目之外內容不變
+        let joined: String = batches.concat();
+        let fitting: Vec<&String> = entries.iter().filter(|e| estimate_tokens(e) < budget).collect();
+        assert!(fitting.len() > 2);
+        for pair in fitting.windows(2) {
+            assert!(joined.find(pair[0].as_str()).unwrap() < joined.find(pair[1].as_str()).unwrap());
+        }
+        let oversized = entries.last().unwrap();
+        assert!(estimate_tokens(oversized) > budget);
+        assert!(joined.contains(truncate_to_tokens(oversized, budget - 1)));
+
+        assert_eq!(pack(&[], budget), [""]);
+    }
+
+    #[tokio::test]
+    async fn reduce_ends_in_a_single_summary() {
+        for summary_size in [2, 30] {
+            let entries: Vec<String> = (0..12).map(|i| format!("entry {} {}", i, "y".repeat(40))).collect();
+            let mut parts = Vec::new();
+            let result = reduce(entries, 40, |summary: &String| summary.clone(), |digest, part| {
+                parts.push(part);
+                // 每次總結的結果大小固定，過大的結果需要截斷才能收斂
+                let summary = format!("{}{}", parts.len(), "z".repeat(summary_size * 4));
+                assert!(estimate_tokens(&digest) <= 40);
+                async move { Some(summary) }
+            })
+            .await;
+            assert!(result.is_some());
+            assert!(parts.len() > 1);
+            // 只有最後一次總結不分批
+            assert_eq!(parts.iter().filter(|part| part.is_none()).count(), 1);
+            assert_eq!(parts.last(), Some(&None));
+        }
+    }
+
+    #[test]
+    fn dirty_modules_include_ancestors() {
+        let report = sample_report();
+        let changed: HashSet<String> = ["b/src/cli/mod.rs", "build.rs"].iter().map(|s| s.to_string()).collect();
+        let previous = Previous { report: &report, changed: &changed };
+        let mut dirty: Vec<String> = previous.dirty_modules().into_iter().collect();
+        dirty.sort();
+        assert_eq!(dirty, [".", "b", "b/src", "b/src/cli"]);
+    }
+
+    const WORKSPACE: &[(&str, &str)] = &[
+        ("Cargo.toml", "[package]\nname = \"demo\"\n"),
+        ("src/lib.rs", "pub mod a;\npub mod b;\n"),
+        ("src/a/mod.rs", "pub fn a() {}\n"),
+        ("src/b/mod.rs", "pub fn b() {}\n"),
+        ("tools/Cargo.toml", "[package]\nname = \"tool\"\n"),
+        ("tools/src/main.rs", "fn main() {}\n"),
+    ];
+
+    /// 先前的報告：各層級的總結都標記為 `previous`，以分辨沿用與重新總結
+    fn previous_report(summaries: Summaries) -> ProjectAnalysis {
+        let mut report = sample_report();
+        report.modules = summaries.modules;
+        report.crates = summaries.crates;
+        report.summary = summaries.summary;
+        for module in &mut report.modules {
+            module.overview.purpose = format!("previous {}", module.path);
+        }
+        for krate in &mut report.crates {
+            krate.overview.purpose = format!("previous {}", krate.name);
+        }
+        report.summary.code_architecture = "previous".to_string();
+        report
+    }
+
+    /// 以假的服務提供者總結，回傳結果與依名稱排序的請求 schema
+    async fn summarize_with(
+        dir: &Path,
+        filter: &FileFilter,
+        analyses: &[FileAnalysis],
+        changed: &[&str],
+        report: Option<&ProjectAnalysis>,
+    ) -> (Summaries, Vec<String>) {
+        let provider = FakeProvider::default();
+        let analyzer = Analyzer::with_provider(Box::new(provider.clone()), Catalog::load("en").unwrap());
+        let changed: HashSet<String> = changed.iter().map(|s| s.to_string()).collect();
+        let previous = report.map(|report| Previous { report, changed: &changed });
+        let summaries = summarize_project(dir, filter, analyses, Some(&analyzer), previous.as_ref(), 2).await;
+        let mut schemas = provider.schemas();
+        schemas.sort();
+        (summaries, schemas)
+    }
+
+    #[tokio::test]
+    async fn unchanged_overviews_are_reused() {
+        let dir = temp_project("summary-reuse", WORKSPACE);
+        let filter = FileFilter::new(&dir, &[], &[]).unwrap();
+        let analyses = analyze_files(&dir, &discover_rust_files(&dir, &filter), None, 1).await;
+        let run = |changed: &'static [&'static str], report| summarize_with(&dir, &filter, &analyses, changed, report);
+
+        let (full, schemas) = run(&[], None).await;
+        assert_eq!(schemas, [&["crate_overview"; 2][..], &["module_overview"; 4], &["project_summary"]].concat());
+        let report = previous_report(full);
+
+        // 沒有變更時不呼叫 AI
+        let (summaries, schemas) = run(&[], Some(&report)).await;
+        assert!(schemas.is_empty(), "{:?}", schemas);
+        assert!(summaries.modules.iter().all(|m| m.overview.purpose.starts_with("previous")));
+        assert_eq!(summaries.summary.code_architecture, "previous");
+
+        // 變更 src/b 只重新總結 src/b、src、所屬的 crate 與專案
+        let (summaries, schemas) = run(&["src/b/mod.rs"], Some(&report)).await;
+        assert_eq!(schemas, ["crate_overview", "module_overview", "module_overview", "project_summary"]);
+        let purposes: BTreeMap<&str, &str> =
+            summaries.modules.iter().map(|m| (m.path.as_str(), m.overview.purpose.as_str())).collect();
+        assert_eq!(
+            purposes,
+            BTreeMap::from([
+                ("src", "fake module"),
+                ("src/a", "previous src/a"),
+                ("src/b", "fake module"),
+                ("tools/src", "previous tools/src"),
+            ])
+        );
+        let crates: Vec<(&str, &str)> = summaries.crates.iter().map(|c| (c.name.as_str(), c.overview.purpose.as_str())).collect();
+        assert_eq!(crates, [("demo", "fake crate"), ("tool", "previous tool")]);
+        assert_eq!(summaries.summary.code_architecture, "fake project");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
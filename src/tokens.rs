@@ -0,0 +1,61 @@
+//! 粗略的 token 數估算。
+//!
+//! 不同模型的分詞器不同，這裡只需要足以做速率限制與切塊判斷的估計值：
+//! ASCII 文字約每 4 個字元一個 token，CJK 等非 ASCII 字元約每字一個 token。
+
+/// 估算文字的 token 數
+pub fn estimate_tokens(text: &str) -> usize {
+    let mut ascii = 0_usize;
+    let mut other = 0_usize;
+    for c in text.chars() {
+        if c.is_ascii() {
+            ascii += 1;
+        } else {
+            other += 1;
+        }
+    }
+    ascii.div_ceil(4) + other
+}
+
+/// 截斷文字，使估計的 token 數不超過 `max_tokens`
+pub fn truncate_to_tokens(text: &str, max_tokens: usize) -> &str {
+    let mut ascii = 0_usize;
+    let mut other = 0_usize;
+    for (offset, c) in text.char_indices() {
+        if c.is_ascii() {
+            ascii += 1;
+        } else {
+            other += 1;
+        }
+        if ascii.div_ceil(4) + other > max_tokens {
+            return &text[..offset];
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimates_ascii_and_cjk() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("abcde"), 2);
+        assert_eq!(estimate_tokens("分析程式碼"), 5);
+        assert_eq!(estimate_tokens("fn 分析"), 3);
+    }
+
+    #[test]
+    fn truncates_within_budget() {
+        let text = "abcdefgh分析程式碼";
+        for max_tokens in 0..=estimate_tokens(text) {
+            let truncated = truncate_to_tokens(text, max_tokens);
+            assert!(estimate_tokens(truncated) <= max_tokens);
+            assert!(text.starts_with(truncated));
+        }
+        assert_eq!(truncate_to_tokens(text, 3), "abcdefgh分");
+        assert_eq!(truncate_to_tokens(text, 100), text);
+    }
+}
@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+
+/// 第一個頂層函數
+pub fn first(values: &[u32]) -> u32 {
+    values.iter().copied().filter(|value| value % 2 == 0).sum()
+}
+
+/// 第二個頂層函數
+pub fn second(name: &str) -> String {
+    format!("hello, {}", name.trim())
+}
+
+pub struct Registry {
+    entries: HashMap<String, u32>,
+}
+
+impl Registry {
+    /// 建立空的登錄表
+    pub fn new() -> Self {
+        Self { entries: HashMap::new() }
+    }
+
+    pub fn insert(&mut self, key: &str, value: u32) {
+        self.entries.insert(key.to_string(), value);
+    }
+
+    pub fn get(&self, key: &str) -> Option<u32> {
+        self.entries.get(key).copied()
+    }
+
+    /// 過長的方法，只能逐行切分
+    pub fn report(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        lines.push(format!("entries: {}", self.entries.len()));
+        lines.push(format!("total: {}", self.entries.values().sum::<u32>()));
+        lines.push(format!("max: {:?}", self.entries.values().max()));
+        lines.push(format!("min: {:?}", self.entries.values().min()));
+        lines.push(format!("keys: {:?}", self.entries.keys().collect::<Vec<_>>()));
+        lines.push(format!("even: {}", self.entries.values().filter(|v| *v % 2 == 0).count()));
+        lines.push(format!("odd: {}", self.entries.values().filter(|v| *v % 2 == 1).count()));
+        lines.push(format!("empty: {}", self.entries.is_empty()));
+        lines.push(format!("first: {:?}", self.entries.keys().min()));
+        lines.push(format!("last: {:?}", self.entries.keys().max()));
+        lines
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<u32> {
+        self.entries.remove(key)
+    }
+}
+
+/// 最後一個頂層函數
+pub fn third(registry: &Registry) -> usize {
+    registry.entries.len()
+}
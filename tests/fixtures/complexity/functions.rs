@@ -0,0 +1,77 @@
+// 複雜度計算的已知分數樣本；預期值列於 src/complexity.rs 的測試中。
+
+fn straight(a: i32) -> i32 {
+    a + 1
+}
+
+fn nested_if_match(x: Option<i32>, flag: bool) -> i32 {
+    if flag {
+        match x {
+            Some(v) if v > 0 => v,
+            Some(v) => -v,
+            None => 0,
+        }
+    } else if x.is_some() {
+        1
+    } else {
+        2
+    }
+}
+
+fn logical(a: bool, b: bool, c: bool, d: bool) -> bool {
+    if a && b && c {
+        return true;
+    }
+    a || b && c || d
+}
+
+fn closures(items: &[i32]) -> Vec<i32> {
+    items
+        .iter()
+        .filter(|v| {
+            if **v > 0 {
+                true
+            } else {
+                false
+            }
+        })
+        .map(|v| v * 2)
+        .collect()
+}
+
+fn search(grid: &[Vec<i32>], target: i32) -> bool {
+    'outer: for row in grid {
+        for &cell in row {
+            if cell == target {
+                break 'outer;
+            }
+        }
+    }
+    search(&grid[1..], target)
+}
+
+fn countdown(mut n: u32) -> u32 {
+    let mut steps = 0;
+    while n > 0 {
+        loop {
+            n -= 1;
+            if n % 2 == 0 {
+                continue;
+            }
+            break;
+        }
+        steps += 1;
+    }
+    steps
+}
+
+fn outer() -> i32 {
+    fn inner(x: i32) -> i32 {
+        if x > 0 {
+            x
+        } else {
+            0
+        }
+    }
+    inner(1)
+}
@@ -0,0 +1,5 @@
+use std::env;
+
+fn main() {
+    let _ = env::var("OUT_DIR");
+}
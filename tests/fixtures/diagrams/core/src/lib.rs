@@ -0,0 +1,5 @@
+pub struct Store;
+
+pub trait Backend {
+    fn load(&self) -> Vec<u8>;
+}
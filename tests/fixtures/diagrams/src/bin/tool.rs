@@ -0,0 +1,5 @@
+use app::net::start;
+
+fn main() {
+    start();
+}
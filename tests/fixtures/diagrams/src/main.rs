@@ -0,0 +1,10 @@
+mod net;
+mod util;
+
+use crate::net::client::Client;
+use core_lib::Store;
+use util::{fmt::pad, helper};
+
+fn main() {
+    let _ = (Client, Store, pad(""), helper());
+}
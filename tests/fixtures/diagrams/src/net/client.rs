@@ -0,0 +1,23 @@
+use super::super::util::helper;
+use super::{server::Handler, start};
+use core_lib::Backend;
+
+pub struct Client;
+
+impl Handler for Client {
+    fn handle(&self) {
+        let _ = (helper(), start());
+    }
+}
+
+impl Backend for Client {
+    fn load(&self) -> Vec<u8> {
+        Vec::new()
+    }
+}
+
+impl<'a> std::fmt::Display for &'a Client {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "client")
+    }
+}
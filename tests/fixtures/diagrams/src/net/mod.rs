@@ -0,0 +1,8 @@
+pub mod client;
+pub mod server;
+
+use self::server::Server;
+
+pub fn start() -> Server {
+    Server
+}
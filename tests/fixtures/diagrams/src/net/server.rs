@@ -0,0 +1,19 @@
+use std::collections::HashMap;
+
+pub struct Server;
+
+pub trait Handler {
+    fn handle(&self);
+}
+
+impl Handler for Server {
+    fn handle(&self) {
+        let _: HashMap<u8, u8> = HashMap::new();
+    }
+}
+
+impl Clone for Server {
+    fn clone(&self) -> Self {
+        Server
+    }
+}
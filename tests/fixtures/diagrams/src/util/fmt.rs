@@ -0,0 +1,5 @@
+use crate::util::helper;
+
+pub fn pad(text: &str) -> String {
+    format!("{:width$}", text, width = helper())
+}
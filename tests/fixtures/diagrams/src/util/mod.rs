@@ -0,0 +1,5 @@
+pub mod fmt;
+
+pub fn helper() -> usize {
+    fmt::pad("").len()
+}
@@ -0,0 +1,79 @@
+//! Fixture for the item extractor: impl blocks, trait items, nested modules and macros.
+
+use std::fmt::{self, Display};
+
+/// A shape with an area.
+pub trait Shape {
+    const SIDES: usize;
+    type Unit;
+
+    /// Area of the shape.
+    fn area(&self) -> f64;
+
+    fn describe(&self) -> String {
+        if self.area() > 1.0 {
+            "large".to_string()
+        } else {
+            "small".to_string()
+        }
+    }
+}
+
+pub(crate) trait Internal {
+    fn hidden(&self);
+}
+
+trait Private {
+    fn secret(&self);
+}
+
+pub struct Square<T> {
+    pub side: T,
+}
+
+impl<T> Square<T> {
+    pub const NAME: &'static str = "square";
+
+    pub fn new(side: T) -> Self {
+        Self { side }
+    }
+
+    fn helper(&self) {}
+}
+
+impl Shape for Square<f64> {
+    const SIDES: usize = 4;
+    type Unit = f64;
+
+    fn area(&self) -> f64 {
+        self.side * self.side
+    }
+}
+
+impl Display for Square<f64> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.side)
+    }
+}
+
+pub mod outer {
+    pub mod inner {
+        pub fn deep() {}
+
+        pub(super) struct Hidden;
+    }
+
+    fn shallow() {}
+}
+
+/// Builds a square.
+#[macro_export]
+macro_rules! square {
+    ($side:expr) => {
+        $crate::Square::new($side)
+    };
+}
+
+macro_rules! local {
+    () => {};
+}
@@ -0,0 +1,17 @@
+//! Crate docs
+//! more
+#![allow(dead_code)]
+#![cfg_attr(
+    feature = "x",
+    deny(missing_docs)
+)]
+
+/// Outer doc
+//// not a doc comment
+#[derive(Debug,
+         Clone)]
+pub struct S; // comment
+#[cfg(test)] mod tests {}
+#[doc = "attr // not comment ]"]
+  	
+fn f() {}
@@ -0,0 +1,15 @@
+/* outer /* inner */ still comment */
+fn a() {} /* trailing
+   comment continues */
+/*
+ * nested /* level 2 /* level 3 */ */
+ */
+
+/** outer doc block */
+/*! inner doc block
+    continued */
+/**/
+/***/
+//// four slashes are a plain comment
+/// outer doc
+fn b() {} // trailing comment
@@ -0,0 +1,3 @@
+fn last() {}
+
+// no trailing newline
\ No newline at end of file
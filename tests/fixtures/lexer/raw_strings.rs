@@ -0,0 +1,13 @@
+fn main() {
+    let url = "http://example.com"; // trailing comment
+    let raw = r"not // a comment";
+    let hashes = r#"contains "quotes" and /* no comment */"#;
+    let multi = r##"
+// inside a raw string
+/* still inside "# */
+"##;
+    let escaped = "a \" // b /* c";
+    let bytes = (b"//", br#"/*"#, b'/');
+    let chars = ('/', '"', '\'', '*');
+    let lifetime: &'static str = "/*";
+}